@@ -0,0 +1,34 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+const MAX_LIGHTS: u32 = 16u;
+
+struct LightingMaterial {
+    // Tint over the whole scene, alpha is how dark it gets
+    ambient: vec4<f32>,
+    // xy world position, z radius, w intensity
+    lights: array<vec4<f32>, 16>,
+    light_count: u32,
+};
+
+@group(2) @binding(0) var<uniform> material: LightingMaterial;
+
+// Size of the blocks the light falloff is snapped to, keeps the pixel art look
+const PIXEL_SIZE: f32 = 2.0;
+const LIGHT_STEPS: f32 = 4.0;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let position = floor(in.world_position.xy / PIXEL_SIZE) * PIXEL_SIZE;
+
+    var light = 0.0;
+    for (var i = 0u; i < min(material.light_count, MAX_LIGHTS); i++) {
+        let l = material.lights[i];
+        let dist = length(position - l.xy) / max(l.z, 0.001);
+        // Stepped falloff, brightest in the middle and gone at the radius
+        let falloff = ceil((1.0 - clamp(dist, 0.0, 1.0)) * LIGHT_STEPS) / LIGHT_STEPS;
+        light = max(light, falloff * l.w);
+    }
+
+    let darkness = material.ambient.a * (1.0 - clamp(light, 0.0, 1.0));
+    return vec4<f32>(material.ambient.rgb, darkness);
+}
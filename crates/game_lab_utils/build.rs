@@ -0,0 +1,92 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Comma separated folders under assets to embed whole, e.g. GAME_LAB_EMBED_FOLDERS=data,shaders,internal/maps
+const EMBED_FOLDERS_ENV: &str = "GAME_LAB_EMBED_FOLDERS";
+const DEFAULT_EMBED_FOLDERS: &str = "data,shaders";
+const INTERNAL_PREFIX: &str = "internal://";
+const INTERNAL_FOLDER: &str = "internal";
+
+// With `embedded_assets` the games' data and shaders are written out as a table of include_bytes!
+// so InternalAssetPlugin can serve them from memory. Of the shared art packs in internal only the
+// files the data names with internal:// go in, anything they pull in themselves (e.g. a Tiled map's
+// tilesets) needs its folder listed in GAME_LAB_EMBED_FOLDERS. Without the feature there's nothing to do
+fn main() {
+    println!("cargo:rerun-if-env-changed=GAME_LAB_ASSET_ROOT");
+    println!("cargo:rerun-if-env-changed={}", EMBED_FOLDERS_ENV);
+    if env::var_os("CARGO_FEATURE_EMBEDDED_ASSETS").is_none() {
+        return;
+    }
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let root = env::var_os("GAME_LAB_ASSET_ROOT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_dir.join("../../assets"));
+    let root = root.canonicalize().unwrap_or_else(|e| panic!("Can't embed assets from {}: {}", root.display(), e));
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let folders = env::var(EMBED_FOLDERS_ENV).unwrap_or_else(|_| DEFAULT_EMBED_FOLDERS.to_string());
+    let mut files = vec!();
+    for folder in folders.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        let dir = root.join(folder);
+        if !dir.is_dir() {
+            println!("cargo:warning={} isn't a folder, nothing embedded from it", dir.display());
+            continue;
+        }
+        collect_files(&dir, &mut files);
+    }
+    for path in referenced_internal_files(&files) {
+        let file = root.join(INTERNAL_FOLDER).join(&path);
+        if !file.is_file() {
+            println!("cargo:warning=internal://{} is referenced but not in {}", path, root.join(INTERNAL_FOLDER).display());
+            continue;
+        }
+        files.push(file);
+    }
+    files.sort();
+    files.dedup();
+
+    let mut table = String::from("pub static EMBEDDED_ASSETS: &[(&str, &[u8])] = &[\n");
+    for file in files {
+        println!("cargo:rerun-if-changed={}", file.display());
+        // Asset paths always use forward slashes
+        let relative = file.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+        table.push_str(&format!("    ({:?}, include_bytes!({:?})),\n", relative, file.display().to_string()));
+    }
+    table.push_str("];\n");
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("embedded_assets.rs");
+    fs::write(out, table).unwrap();
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = fs::read_dir(dir).unwrap_or_else(|e| panic!("Can't read {}: {}", dir.display(), e));
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            println!("cargo:rerun-if-changed={}", path.display());
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// Every internal://... string in the data files, e.g. images in assets.json or icon sheets in items.json
+fn referenced_internal_files(files: &[PathBuf]) -> Vec<String> {
+    let mut paths = vec!();
+    for file in files.iter().filter(|f| f.extension().is_some_and(|e| e == "json" || e == "ron")) {
+        let Ok(text) = fs::read_to_string(file) else {
+            continue;
+        };
+        for (start, _) in text.match_indices(INTERNAL_PREFIX) {
+            let rest = &text[start + INTERNAL_PREFIX.len()..];
+            let end = rest.find(['"', '\'']).unwrap_or(rest.len());
+            paths.push(rest[..end].to_string());
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    paths
+}
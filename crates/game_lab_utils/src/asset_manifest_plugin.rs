@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use bevy::app::{App, Plugin, PreStartup, Update};
+use bevy::asset::{Asset, AssetEvent, AssetServer, Assets, Handle};
+use bevy::image::Image;
+use bevy::math::UVec2;
+use bevy::prelude::{error, Commands, EventReader, Res, ResMut, Resource, Shader, TextureAtlas, TextureAtlasLayout};
+use bevy::render::render_resource::RenderPipelineDescriptor;
+use serde::Deserialize;
+use crate::internal_asset_plugin::{asset_root, AssetRoots};
+use crate::loading_plugin::LoadingTracker;
+use crate::texture_atlas_layout::AtlasGrid;
+
+// Every file a game loads, by name. Atlases point at an image by its key rather than its path
+#[derive(Resource, Deserialize, Clone, Debug, Default)]
+pub struct AssetManifest {
+    #[serde(default)]
+    pub images: HashMap<String, String>,
+    #[serde(default)]
+    pub shaders: HashMap<String, String>,
+    // Files loaded through a game's own asset types, e.g. levels and crop definitions
+    #[serde(default)]
+    pub data: HashMap<String, String>,
+    #[serde(default)]
+    pub atlases: HashMap<String, AtlasDefinition>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct AtlasDefinition {
+    pub image: String,
+    pub tile_size: [u32; 2],
+    pub columns: u32,
+    pub rows: u32,
+    #[serde(default)]
+    pub spacing: [u32; 2],
+    #[serde(default)]
+    pub margin: [u32; 2],
+    // For sheets whose last row isn't full
+    #[serde(default)]
+    pub count: Option<usize>,
+    #[serde(default)]
+    pub column_major: bool,
+}
+
+impl AtlasDefinition {
+    pub fn grid(&self) -> AtlasGrid {
+        let mut grid = AtlasGrid::new(UVec2::from(self.tile_size), self.columns, self.rows)
+            .with_spacing(UVec2::from(self.spacing))
+            .with_margin(UVec2::from(self.margin));
+        grid.count = self.count;
+        grid.column_major = self.column_major;
+        grid
+    }
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, serde_json::Error),
+    Invalid(PathBuf, Vec<String>),
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestError::Read(path, e) => write!(f, "Could not read asset manifest {}: {}", path.display(), e),
+            ManifestError::Parse(path, e) => write!(f, "Could not parse asset manifest {}: {}", path.display(), e),
+            ManifestError::Invalid(path, problems) => {
+                writeln!(f, "Asset manifest {} has {} problem(s):", path.display(), problems.len())?;
+                for problem in problems {
+                    writeln!(f, "  - {}", problem)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl AssetManifest {
+    pub fn read(roots: &AssetRoots, path: &str) -> Result<Self, ManifestError> {
+        let full_path = roots.resolve(path);
+        let contents = roots.read(path).map_err(|e| ManifestError::Read(full_path.clone(), e))?;
+        let manifest: AssetManifest = serde_json::from_slice(&contents).map_err(|e| ManifestError::Parse(full_path.clone(), e))?;
+
+        let problems = manifest.problems(|p| roots.exists(p));
+        if !problems.is_empty() {
+            return Err(ManifestError::Invalid(full_path, problems));
+        }
+        Ok(manifest)
+    }
+
+    // Everything wrong with the manifest at once, so one run shows the whole list
+    pub fn problems(&self, exists: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut problems = vec!();
+        for (section, entries) in [("images", &self.images), ("shaders", &self.shaders), ("data", &self.data)] {
+            for (key, path) in entries {
+                if !exists(path) {
+                    problems.push(format!("{} \"{}\" is missing: {}", section, key, path));
+                }
+            }
+        }
+        for (key, atlas) in &self.atlases {
+            if atlas.tile_size.contains(&0) {
+                problems.push(format!("atlas \"{}\" has no tile size", key));
+            }
+            if !self.images.contains_key(&atlas.image) {
+                problems.push(format!("atlas \"{}\" uses unknown image \"{}\"", key, atlas.image));
+            }
+            if atlas.columns == 0 || atlas.rows == 0 {
+                problems.push(format!("atlas \"{}\" has no tiles", key));
+            }
+        }
+        problems.sort();
+        problems
+    }
+
+    pub fn data_path(&self, key: &str) -> &str {
+        self.data.get(key).unwrap_or_else(|| panic!("No data named \"{}\" in the asset manifest", key))
+    }
+
+    pub fn load_data<A: Asset>(&self, asset_server: &AssetServer, key: &str) -> Handle<A> {
+        asset_server.load(self.data_path(key).to_string())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ManifestAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub columns: u32,
+    pub rows: u32,
+    pub grid: AtlasGrid,
+}
+
+impl ManifestAtlas {
+    pub fn texture_atlas(&self, index: usize) -> TextureAtlas {
+        TextureAtlas { layout: self.layout.clone(), index }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ImageAssets {
+    images: HashMap<String, Handle<Image>>,
+    atlases: HashMap<String, ManifestAtlas>,
+}
+
+impl ImageAssets {
+    pub fn image(&self, key: &str) -> Handle<Image> {
+        self.images.get(key).cloned().unwrap_or_else(|| panic!("No image named \"{}\" in the asset manifest", key))
+    }
+
+    pub fn atlas(&self, key: &str) -> &ManifestAtlas {
+        self.atlases.get(key).unwrap_or_else(|| panic!("No atlas named \"{}\" in the asset manifest", key))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ShaderAssets {
+    shaders: HashMap<String, Handle<Shader>>,
+}
+
+impl ShaderAssets {
+    pub fn shader(&self, key: &str) -> Handle<Shader> {
+        self.shaders.get(key).cloned().unwrap_or_else(|| panic!("No shader named \"{}\" in the asset manifest", key))
+    }
+}
+
+// Material2d only takes a static shader path, so materials holding a manifest shader
+// use this as their bind group data and swap it in when the pipeline is specialised
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct FragmentShader(pub Handle<Shader>);
+
+impl FragmentShader {
+    pub fn specialize(&self, descriptor: &mut RenderPipelineDescriptor) {
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader = self.0.clone();
+        }
+    }
+}
+
+// Reads and checks the manifest while the app is built, a missing file stops the game before a window opens
+pub struct AssetManifestPlugin {
+    path: String,
+}
+
+impl AssetManifestPlugin {
+    pub fn new(path: &str) -> Self {
+        Self { path: path.to_string() }
+    }
+}
+
+impl Plugin for AssetManifestPlugin {
+    fn build(&self, app: &mut App) {
+        // Without AssetSourcesPlugin only plain paths under the asset root can be checked
+        let roots = app.world().get_resource::<AssetRoots>().cloned().unwrap_or_else(|| AssetRoots::new(asset_root()));
+        let manifest = match AssetManifest::read(&roots, &self.path) {
+            Ok(manifest) => manifest,
+            Err(e) => panic!("{}", e),
+        };
+        app.insert_resource(manifest)
+            .init_resource::<ImageAssets>()
+            .init_resource::<ShaderAssets>()
+            .add_systems(PreStartup, load_manifest_assets)
+            .add_systems(Update, check_atlas_images);
+    }
+}
+
+// PreStartup so every Startup system can already ask for assets by key
+fn load_manifest_assets(
+    mut commands: Commands,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut tracker: Option<ResMut<LoadingTracker>>,
+    manifest: Res<AssetManifest>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut images = ImageAssets::default();
+    for (key, path) in &manifest.images {
+        let handle: Handle<Image> = asset_server.load(path.clone());
+        if let Some(tracker) = tracker.as_mut() {
+            tracker.track(key, handle.clone());
+        }
+        images.images.insert(key.clone(), handle);
+    }
+    for (key, atlas) in &manifest.atlases {
+        let grid = atlas.grid();
+        // Sized to fit for now, check_atlas_images swaps in the real size once the image is in
+        images.atlases.insert(key.clone(), ManifestAtlas {
+            image: images.image(&atlas.image),
+            layout: texture_atlas_layouts.add(grid.layout(grid.required_size())),
+            columns: atlas.columns,
+            rows: atlas.rows,
+            grid,
+        });
+    }
+
+    let mut shaders = ShaderAssets::default();
+    for (key, path) in &manifest.shaders {
+        let handle: Handle<Shader> = asset_server.load(path.clone());
+        // Materials load their shader lazily, tracking it here lets the loading screen wait on it
+        if let Some(tracker) = tracker.as_mut() {
+            tracker.track(key, handle.clone());
+        }
+        shaders.shaders.insert(key.clone(), handle);
+    }
+
+    commands.insert_resource(images);
+    commands.insert_resource(shaders);
+}
+
+fn check_atlas_images(
+    mut reader: EventReader<AssetEvent<Image>>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    images: Res<ImageAssets>,
+    loaded: Res<Assets<Image>>,
+) {
+    for event in reader.read() {
+        for (key, atlas) in &images.atlases {
+            if !event.is_loaded_with_dependencies(&atlas.image) && !event.is_modified(&atlas.image) {
+                continue;
+            }
+            let Some(image) = loaded.get(&atlas.image) else {
+                continue;
+            };
+            if let Err(e) = atlas.grid.validate(image.size()) {
+                error!("Atlas \"{}\" doesn't match its image: {}", key, e);
+            }
+            if let Some(layout) = texture_atlas_layouts.get_mut(&atlas.layout) {
+                *layout = atlas.grid.layout(image.size());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn problems() {
+        struct TestCase {
+            manifest: &'static str,
+            expected: Vec<&'static str>,
+        }
+
+        let cases = vec!(
+            TestCase {
+                manifest: r#"{ "images": { "coins": "coins.png" }, "atlases": { "coins": { "image": "coins", "tile_size": [32, 32], "columns": 4, "rows": 1 } } }"#,
+                expected: vec!(),
+            },
+            TestCase {
+                manifest: r#"{ "images": { "grass": "missing/grass.png" }, "shaders": { "shadow": "missing/shadow.wgsl" } }"#,
+                expected: vec!("images \"grass\" is missing: missing/grass.png", "shaders \"shadow\" is missing: missing/shadow.wgsl"),
+            },
+            TestCase {
+                manifest: r#"{ "atlases": { "water": { "image": "water", "tile_size": [16, 16], "columns": 0, "rows": 1 } } }"#,
+                expected: vec!("atlas \"water\" has no tiles", "atlas \"water\" uses unknown image \"water\""),
+            },
+        );
+
+        for c in cases {
+            let manifest: AssetManifest = serde_json::from_str(c.manifest).unwrap();
+            assert_eq!(manifest.problems(|p| !p.starts_with("missing/")), c.expected);
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use bevy::app::{App, First, Plugin, PostUpdate, Update};
+use bevy::asset::{Assets, Handle, RenderAssetUsages};
+use bevy::image::Image;
+use bevy::input::ButtonInput;
+use bevy::math::UVec2;
+use bevy::prelude::{error, info, Camera, Camera2d, Commands, Component, DespawnRecursiveExt, Entity, GlobalTransform, IntoSystemConfigs, KeyCode, OrthographicProjection, Query, Res, ResMut, Resource, Transform, Trigger, With};
+use bevy::render::camera::{CameraUpdateSystem, RenderTarget};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy::tasks::IoTaskPool;
+use bevy::time::{Real, Time, Timer, TimerMode};
+use bevy::transform::TransformSystem;
+use bevy::window::{PrimaryWindow, Window};
+use bevy_egui::{egui, EguiContexts};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame, RgbaImage};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    Gif,
+    // A folder of numbered PNGs, for when a GIF's 256 colours aren't enough
+    PngSequence,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct CaptureSettings {
+    pub screenshot_key: KeyCode,
+    pub record_key: KeyCode,
+    // Relative to the working directory, like the save folders
+    pub directory: PathBuf,
+    pub format: RecordFormat,
+    pub frame_rate: u32,
+    // Recorded frames are resized by this, screenshots are kept at full size
+    pub scale: f32,
+    // Recording stops by itself after this, every frame is held in memory until then
+    pub max_length: Duration,
+    // Captures what the world camera sees, without egui or UI drawn over it
+    pub world_only: bool,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            screenshot_key: KeyCode::F12,
+            record_key: KeyCode::F9,
+            directory: PathBuf::from("captures"),
+            format: RecordFormat::Gif,
+            frame_rate: 15,
+            scale: 0.5,
+            max_length: Duration::from_secs(30),
+            world_only: false,
+        }
+    }
+}
+
+// Stand-in for the world camera that renders into an image. It's only around from PostUpdate until
+// the next frame's First, so game systems that expect a single camera never see it
+#[derive(Component)]
+pub struct CaptureCamera;
+
+struct Recording {
+    started: String,
+    frames: Vec<RgbaImage>,
+    timer: Timer,
+    elapsed: Duration,
+}
+
+#[derive(Resource, Default)]
+struct CaptureState {
+    screenshot: bool,
+    frame: bool,
+    recording: Option<Recording>,
+    // Sized to the window, remade when that changes
+    target: Option<(Handle<Image>, UVec2)>,
+}
+
+pub(crate) struct CapturePlugin {
+    pub settings: CaptureSettings,
+}
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .init_resource::<CaptureState>()
+            .add_systems(First, despawn_capture_cameras)
+            .add_systems(Update, (capture_hotkeys, recording_indicator).chain())
+            .add_systems(PostUpdate, spawn_captures.before(CameraUpdateSystem).before(TransformSystem::TransformPropagate));
+    }
+}
+
+// UTC, as 2024-05-01_13-45-09
+pub fn timestamp(since_epoch: Duration) -> String {
+    let secs = since_epoch.as_secs();
+    let (days, time) = ((secs / 86_400) as i64, secs % 86_400);
+    // Days to a civil date, from Howard Hinnant's date algorithms
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}_{:02}-{:02}-{:02}", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+fn now() -> String {
+    timestamp(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default())
+}
+
+pub fn scaled_size(width: u32, height: u32, scale: f32) -> (u32, u32) {
+    let scale = |v: u32| ((v as f32 * scale).round() as u32).max(1);
+    (scale(width), scale(height))
+}
+
+// Doesn't overwrite, a second capture in the same second gets -2 and so on
+fn unused_path(directory: &Path, name: &str, extension: &str) -> PathBuf {
+    let mut path = directory.join(format!("{name}{extension}"));
+    let mut n = 2;
+    while path.exists() {
+        path = directory.join(format!("{name}-{n}{extension}"));
+        n += 1;
+    }
+    path
+}
+
+// Returns where it went, a .gif file or a folder of frames
+pub fn write_recording(directory: &Path, name: &str, frames: Vec<RgbaImage>, format: RecordFormat, frame_rate: u32) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+    match format {
+        RecordFormat::Gif => {
+            let path = unused_path(directory, name, ".gif");
+            let file = File::create(&path).map_err(|e| e.to_string())?;
+            let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+            encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+            let delay = Delay::from_numer_denom_ms(1000, frame_rate.max(1));
+            encoder.encode_frames(frames.into_iter().map(|f| Frame::from_parts(f, 0, 0, delay))).map_err(|e| e.to_string())?;
+            Ok(path)
+        }
+        RecordFormat::PngSequence => {
+            let path = unused_path(directory, name, "");
+            std::fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+            for (i, frame) in frames.iter().enumerate() {
+                frame.save(path.join(format!("frame-{i:04}.png"))).map_err(|e| e.to_string())?;
+            }
+            Ok(path)
+        }
+    }
+}
+
+// Works whether or not debugging is enabled, so clips don't have to show the debug windows
+fn capture_hotkeys(keys: Res<ButtonInput<KeyCode>>, settings: Res<CaptureSettings>, time: Res<Time<Real>>, mut state: ResMut<CaptureState>) {
+    if keys.just_pressed(settings.screenshot_key) {
+        state.screenshot = true;
+    }
+    let too_long = state.recording.as_ref().is_some_and(|r| r.elapsed >= settings.max_length);
+    if keys.just_pressed(settings.record_key) || too_long {
+        match state.recording.take() {
+            Some(recording) => finish_recording(recording, &settings),
+            None => {
+                info!("Recording, {:?} again to stop", settings.record_key);
+                let interval = Duration::from_secs_f32(1.0 / settings.frame_rate.max(1) as f32);
+                state.recording = Some(Recording {
+                    started: now(),
+                    frames: vec!(),
+                    timer: Timer::new(interval, TimerMode::Repeating),
+                    elapsed: Duration::ZERO,
+                });
+                state.frame = true;
+            }
+        }
+    }
+    if let Some(recording) = &mut state.recording {
+        recording.elapsed += time.delta();
+        if recording.timer.tick(time.delta()).just_finished() {
+            state.frame = true;
+        }
+    }
+}
+
+fn finish_recording(recording: Recording, settings: &CaptureSettings) {
+    if recording.frames.is_empty() {
+        info!("Recording stopped before any frames came back");
+        return;
+    }
+    let (directory, format, frame_rate) = (settings.directory.clone(), settings.format, settings.frame_rate);
+    info!("Writing {} frames", recording.frames.len());
+    IoTaskPool::get().spawn(async move {
+        match write_recording(&directory, &format!("recording-{}", recording.started), recording.frames, format, frame_rate) {
+            Ok(path) => info!("Saved recording {}", path.display()),
+            Err(e) => error!("Could not save recording: {}", e),
+        }
+    }).detach();
+}
+
+fn render_target(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+fn spawn_captures(
+    mut commands: Commands,
+    mut state: ResMut<CaptureState>,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<CaptureSettings>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &OrthographicProjection, &GlobalTransform), With<Camera2d>>,
+) {
+    if !state.screenshot && !state.frame {
+        return;
+    }
+    let target = if settings.world_only {
+        // The same camera the gizmos draw for, the lowest ordered 2d one
+        let (Ok(window), Some((camera, projection, transform))) = (window.get_single(), cameras.iter().filter(|(c, ..)| c.is_active).min_by_key(|(c, ..)| c.order)) else {
+            return;
+        };
+        let size = window.physical_size();
+        let Some(handle) = state.target.as_ref().filter(|(_, s)| *s == size).map(|(h, _)| h.clone()) else {
+            // Ready by next frame, the capture waits for it
+            state.target = Some((images.add(render_target(size)), size));
+            return;
+        };
+        commands.spawn((
+            CaptureCamera,
+            Camera2d,
+            Camera { target: RenderTarget::Image(handle.clone()), ..camera.clone() },
+            projection.clone(),
+            Transform::from(*transform),
+            *transform,
+        ));
+        RenderTarget::Image(handle)
+    } else {
+        Screenshot::primary_window().0
+    };
+    if state.screenshot {
+        commands.spawn(Screenshot(target.clone())).observe(save_screenshot);
+    }
+    if state.frame {
+        commands.spawn(Screenshot(target)).observe(record_frame);
+    }
+    state.screenshot = false;
+    state.frame = false;
+}
+
+fn despawn_capture_cameras(mut commands: Commands, cameras: Query<Entity, With<CaptureCamera>>) {
+    for entity in cameras.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn to_rgba(image: &Image) -> Option<RgbaImage> {
+    image.clone().try_into_dynamic()
+        .inspect_err(|e| error!("Could not read capture: {}", e))
+        .ok()
+        .map(|image| image.to_rgba8())
+}
+
+fn save_screenshot(trigger: Trigger<ScreenshotCaptured>, settings: Res<CaptureSettings>) {
+    let Some(image) = to_rgba(&trigger.event().0) else {
+        return;
+    };
+    let directory = settings.directory.clone();
+    IoTaskPool::get().spawn(async move {
+        let path = unused_path(&directory, &format!("screenshot-{}", now()), ".png");
+        let result = std::fs::create_dir_all(&directory).map_err(|e| e.to_string())
+            .and_then(|_| image.save(&path).map_err(|e| e.to_string()));
+        match result {
+            Ok(_) => info!("Saved screenshot {}", path.display()),
+            Err(e) => error!("Could not save screenshot {}: {}", path.display(), e),
+        }
+    }).detach();
+}
+
+// Frames still on their way back after recording stops are dropped
+fn record_frame(trigger: Trigger<ScreenshotCaptured>, settings: Res<CaptureSettings>, mut state: ResMut<CaptureState>) {
+    let Some(recording) = &mut state.recording else {
+        return;
+    };
+    let Some(image) = to_rgba(&trigger.event().0) else {
+        return;
+    };
+    let (width, height) = scaled_size(image.width(), image.height(), settings.scale);
+    // Nearest keeps pixel art crisp, and is quick enough to do every frame
+    recording.frames.push(image::imageops::resize(&image, width, height, FilterType::Nearest));
+}
+
+// Only shown when it can't end up in the capture
+fn recording_indicator(mut contexts: EguiContexts, settings: Res<CaptureSettings>, state: Res<CaptureState>) {
+    let Some(recording) = &state.recording else {
+        return;
+    };
+    if !settings.world_only {
+        return;
+    }
+    let ctx = contexts.ctx_mut();
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("recording")));
+    let position = ctx.screen_rect().right_top() + egui::vec2(-12.0, 12.0);
+    let text = format!("● REC {:.1}s", recording.elapsed.as_secs_f32());
+    painter.text(position, egui::Align2::RIGHT_TOP, text, egui::FontId::monospace(14.0), egui::Color32::RED);
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{AnimationDecoder, Rgba};
+    use image::codecs::gif::GifDecoder;
+    use super::*;
+
+    #[test]
+    fn names_and_sizes() {
+        struct TestCase {
+            since_epoch: u64,
+            expected: &'static str,
+        }
+
+        let cases = vec!(
+            TestCase { since_epoch: 0, expected: "1970-01-01_00-00-00" },
+            TestCase { since_epoch: 951_782_400, expected: "2000-02-29_00-00-00" },
+            TestCase { since_epoch: 1_714_571_109, expected: "2024-05-01_13-45-09" },
+            TestCase { since_epoch: 1_735_689_599, expected: "2024-12-31_23-59-59" },
+        );
+
+        for c in cases {
+            assert_eq!(timestamp(Duration::from_secs(c.since_epoch)), c.expected);
+        }
+
+        assert_eq!(scaled_size(1280, 720, 0.5), (640, 360));
+        assert_eq!(scaled_size(3, 1, 0.1), (1, 1));
+    }
+
+    #[test]
+    fn recordings() {
+        let directory = std::env::temp_dir().join(format!("capture_test_{}", std::process::id()));
+        let frames: Vec<RgbaImage> = [0, 255].iter().map(|v| RgbaImage::from_pixel(4, 2, Rgba([*v, 0, 0, 255]))).collect();
+
+        let gif = write_recording(&directory, "clip", frames.clone(), RecordFormat::Gif, 10).unwrap();
+        assert_eq!(gif, directory.join("clip.gif"));
+        let decoded: Vec<Frame> = GifDecoder::new(std::io::BufReader::new(File::open(&gif).unwrap())).unwrap()
+            .into_frames().collect_frames().unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[1].delay().numer_denom_ms(), (100, 1));
+
+        let pngs = write_recording(&directory, "clip", frames.clone(), RecordFormat::PngSequence, 10).unwrap();
+        assert_eq!(image::open(pngs.join("frame-0001.png")).unwrap().to_rgba8(), frames[1]);
+        // Neither overwrites the last
+        assert_eq!(write_recording(&directory, "clip", frames, RecordFormat::Gif, 10).unwrap(), directory.join("clip-2.gif"));
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
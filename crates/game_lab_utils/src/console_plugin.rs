@@ -0,0 +1,672 @@
+use std::collections::{BTreeMap, VecDeque};
+use bevy::app::{App, Plugin, PostStartup, PreUpdate, Update};
+use bevy::ecs::system::SystemId;
+use bevy::input::{ButtonInput, InputSystem};
+use bevy::prelude::{error, info, In, IntoSystem, IntoSystemConfigs, KeyCode, Res, ResMut, Resource, World};
+use bevy_egui::egui::text::{CCursor, CCursorRange};
+use bevy_egui::{egui, EguiContexts};
+use crate::internal_asset_plugin::AssetRoots;
+
+const MAX_LOG_LINES: usize = 500;
+// Scripts can exec other scripts this deep
+const MAX_EXEC_DEPTH: usize = 8;
+// The rest of a long script carries on next frame rather than stalling this one
+const MAX_LINES_PER_FRAME: usize = 100;
+
+pub type ConsoleResult = Result<String, String>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgKind {
+    Int,
+    Float,
+    Text,
+    // A clock time like 18:00
+    Time,
+}
+
+impl ArgKind {
+    fn label(&self) -> &'static str {
+        match self {
+            ArgKind::Int => "int",
+            ArgKind::Float => "number",
+            ArgKind::Text => "text",
+            ArgKind::Time => "hh:mm",
+        }
+    }
+
+    fn parse(&self, word: &str) -> Option<Arg> {
+        match self {
+            ArgKind::Int => word.parse().ok().map(Arg::Int),
+            ArgKind::Float => word.parse().ok().map(Arg::Float),
+            ArgKind::Text => Some(Arg::Text(word.to_string())),
+            ArgKind::Time => {
+                let (hours, minutes) = word.split_once(':')?;
+                let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+                (hours < 24 && minutes < 60).then_some(Arg::Time(hours, minutes))
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arg {
+    Int(i64),
+    Float(f32),
+    Text(String),
+    Time(u32, u32),
+}
+
+// Already checked against the command's arguments, so each accessor only falls back to its
+// default for an optional argument that was left out
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConsoleArgs(pub Vec<Arg>);
+
+impl ConsoleArgs {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn int(&self, index: usize) -> i64 {
+        match self.0.get(index) {
+            Some(Arg::Int(v)) => *v,
+            _ => 0,
+        }
+    }
+
+    pub fn float(&self, index: usize) -> f32 {
+        match self.0.get(index) {
+            Some(Arg::Float(v)) => *v,
+            Some(Arg::Int(v)) => *v as f32,
+            _ => 0.0,
+        }
+    }
+
+    pub fn text(&self, index: usize) -> &str {
+        match self.0.get(index) {
+            Some(Arg::Text(v)) => v,
+            _ => "",
+        }
+    }
+
+    pub fn time(&self, index: usize) -> (u32, u32) {
+        match self.0.get(index) {
+            Some(Arg::Time(hours, minutes)) => (*hours, *minutes),
+            _ => (0, 0),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ArgSpec {
+    name: &'static str,
+    kind: ArgKind,
+    optional: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConsoleCommand {
+    pub name: &'static str,
+    pub help: &'static str,
+    args: Vec<ArgSpec>,
+}
+
+impl ConsoleCommand {
+    pub fn new(name: &'static str, help: &'static str) -> Self {
+        Self { name, help, args: vec!() }
+    }
+
+    pub fn arg(mut self, name: &'static str, kind: ArgKind) -> Self {
+        self.args.push(ArgSpec { name, kind, optional: false });
+        self
+    }
+
+    // Only at the end, after the required ones
+    pub fn optional_arg(mut self, name: &'static str, kind: ArgKind) -> Self {
+        self.args.push(ArgSpec { name, kind, optional: true });
+        self
+    }
+
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.to_string();
+        for arg in &self.args {
+            if arg.optional {
+                usage.push_str(&format!(" [{}:{}]", arg.name, arg.kind.label()));
+            } else {
+                usage.push_str(&format!(" <{}:{}>", arg.name, arg.kind.label()));
+            }
+        }
+        usage
+    }
+
+    pub fn parse(&self, words: &[String]) -> Result<ConsoleArgs, String> {
+        let required = self.args.iter().filter(|a| !a.optional).count();
+        if words.len() < required || words.len() > self.args.len() {
+            return Err(format!("Usage: {}", self.usage()));
+        }
+        words.iter().zip(&self.args)
+            .map(|(word, spec)| spec.kind.parse(word).ok_or_else(|| format!("{} should be {}, got \"{}\"", spec.name, spec.kind.label(), word)))
+            .collect::<Result<Vec<Arg>, String>>()
+            .map(ConsoleArgs)
+    }
+}
+
+type CommandSystem = SystemId<In<ConsoleArgs>, ConsoleResult>;
+
+// Filled by add_console_command, whether or not ConsolePlugin is in yet
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(BTreeMap<&'static str, (ConsoleCommand, CommandSystem)>);
+
+impl ConsoleCommands {
+    pub fn names(&self) -> Vec<&'static str> {
+        self.0.keys().copied().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConsoleCommand> {
+        self.0.get(name).map(|(command, _)| command)
+    }
+}
+
+pub trait ConsoleApp {
+    fn add_console_command<M>(&mut self, command: ConsoleCommand, system: impl IntoSystem<In<ConsoleArgs>, ConsoleResult, M> + 'static) -> &mut Self;
+}
+
+impl ConsoleApp for App {
+    fn add_console_command<M>(&mut self, command: ConsoleCommand, system: impl IntoSystem<In<ConsoleArgs>, ConsoleResult, M> + 'static) -> &mut Self {
+        let id = self.world_mut().register_system(system);
+        self.world_mut().get_resource_or_init::<ConsoleCommands>().0.insert(command.name, (command, id));
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    Input,
+    Output,
+    Error,
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    log: VecDeque<(LineKind, String)>,
+    history: Vec<String>,
+    // Where Up/Down is in the history, None while editing a new line
+    history_index: Option<usize>,
+    queue: VecDeque<QueuedLine>,
+    // Scripts the line being run came from, outermost first
+    running: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct QueuedLine {
+    line: String,
+    scripts: Vec<String>,
+}
+
+impl Console {
+    pub fn submit(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+        }
+        self.history_index = None;
+        self.queue.push_back(QueuedLine { line: line.to_string(), scripts: vec!() });
+    }
+
+    // Runs ahead of anything already queued, so a script started from a command finishes first.
+    // Fails if the script is already running further up, or scripts are nested too deep
+    pub fn run_script(&mut self, path: &str, script: &str) -> Result<usize, String> {
+        if self.running.iter().any(|p| p == path) {
+            return Err(format!("{} is already running, scripts can't exec themselves", path));
+        }
+        if self.running.len() >= MAX_EXEC_DEPTH {
+            return Err(format!("Scripts can only exec {} deep", MAX_EXEC_DEPTH));
+        }
+        let mut scripts = self.running.clone();
+        scripts.push(path.to_string());
+        let lines = script_lines(script);
+        for line in lines.iter().rev() {
+            self.queue.push_front(QueuedLine { line: line.to_string(), scripts: scripts.clone() });
+        }
+        Ok(lines.len())
+    }
+
+    pub fn print(&mut self, kind: LineKind, text: &str) {
+        for line in text.lines() {
+            match kind {
+                LineKind::Error => error!("console: {}", line),
+                _ => info!("console: {}", line),
+            }
+            self.log.push_back((kind, line.to_string()));
+        }
+        while self.log.len() > MAX_LOG_LINES {
+            self.log.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.log.clear();
+    }
+
+    pub fn log(&self) -> impl Iterator<Item = &(LineKind, String)> {
+        self.log.iter()
+    }
+
+    pub fn history_previous(&mut self) {
+        let index = match self.history_index {
+            Some(i) => i.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    pub fn history_next(&mut self) {
+        let Some(index) = self.history_index else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.history_index = Some(index + 1);
+            self.input = self.history[index + 1].clone();
+        } else {
+            self.history_index = None;
+            self.input.clear();
+        }
+    }
+}
+
+// Blank lines and lines starting with # are skipped
+pub fn script_lines(script: &str) -> Vec<&str> {
+    script.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+// Whitespace separated, double quotes keep spaces together
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = vec!();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            },
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    words.push(std::mem::take(&mut word));
+                    started = false;
+                }
+            },
+            c => {
+                word.push(c);
+                started = true;
+            },
+        }
+    }
+    if quoted {
+        return Err("Unclosed quote".to_string());
+    }
+    if started {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+// Completes the command name as far as every match agrees
+pub fn complete(names: &[&str], input: &str) -> Option<String> {
+    if input.contains(char::is_whitespace) {
+        return None;
+    }
+    let matches: Vec<&str> = names.iter().copied().filter(|name| name.starts_with(input)).collect();
+    match matches.as_slice() {
+        [] => None,
+        [name] => Some(format!("{} ", name)),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.len(), |len, name| {
+                first.chars().zip(name.chars()).take(len).take_while(|(a, b)| a == b).count()
+            });
+            (common > input.len()).then(|| first[..common].to_string())
+        },
+    }
+}
+
+pub struct ConsolePlugin {
+    toggle_key: KeyCode,
+    startup_script: Option<String>,
+}
+
+impl Default for ConsolePlugin {
+    fn default() -> Self {
+        Self { toggle_key: KeyCode::F10, startup_script: None }
+    }
+}
+
+impl ConsolePlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_toggle_key(mut self, key: KeyCode) -> Self {
+        self.toggle_key = key;
+        self
+    }
+
+    // Commands to run once Startup is done, a missing file is skipped. Takes asset paths like game://console.txt
+    pub fn with_startup_script(mut self, path: &str) -> Self {
+        self.startup_script = Some(path.to_string());
+        self
+    }
+}
+
+#[derive(Resource)]
+struct ConsoleSettings {
+    toggle_key: KeyCode,
+    startup_script: Option<String>,
+}
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            .insert_resource(ConsoleSettings { toggle_key: self.toggle_key, startup_script: self.startup_script.clone() })
+            .add_console_command(ConsoleCommand::new("help", "Lists commands, or shows one").optional_arg("command", ArgKind::Text), help)
+            .add_console_command(ConsoleCommand::new("clear", "Clears the console"), clear)
+            .add_console_command(ConsoleCommand::new("exec", "Runs the commands in a file").arg("path", ArgKind::Text), exec)
+            .add_systems(PostStartup, run_startup_script)
+            .add_systems(PreUpdate, capture_keyboard.after(InputSystem))
+            .add_systems(Update, (console_window, run_console_commands).chain());
+    }
+}
+
+fn read_script(roots: Option<&AssetRoots>, path: &str) -> Result<String, String> {
+    let bytes = match roots {
+        Some(roots) => roots.read(path),
+        None => std::fs::read(path),
+    };
+    bytes.map_err(|e| format!("Could not read {}: {}", path, e))
+        .and_then(|bytes| String::from_utf8(bytes).map_err(|e| format!("Could not read {}: {}", path, e)))
+}
+
+fn help(In(args): In<ConsoleArgs>, commands: Res<ConsoleCommands>) -> ConsoleResult {
+    if !args.is_empty() {
+        let command = commands.get(args.text(0)).ok_or_else(|| format!("Unknown command \"{}\"", args.text(0)))?;
+        return Ok(format!("{}\n  {}", command.usage(), command.help));
+    }
+    Ok(commands.0.values().map(|(command, _)| format!("{} - {}", command.usage(), command.help)).collect::<Vec<_>>().join("\n"))
+}
+
+fn clear(In(_): In<ConsoleArgs>, mut console: ResMut<Console>) -> ConsoleResult {
+    console.clear();
+    Ok(String::new())
+}
+
+fn exec(In(args): In<ConsoleArgs>, mut console: ResMut<Console>, roots: Option<Res<AssetRoots>>) -> ConsoleResult {
+    let script = read_script(roots.as_deref(), args.text(0))?;
+    let count = console.run_script(args.text(0), &script)?;
+    Ok(format!("Running {} command(s) from {}", count, args.text(0)))
+}
+
+fn run_startup_script(mut console: ResMut<Console>, settings: Res<ConsoleSettings>, roots: Option<Res<AssetRoots>>) {
+    let Some(path) = &settings.startup_script else {
+        return;
+    };
+    if let Ok(script) = read_script(roots.as_deref(), path)
+        && let Err(e) = console.run_script(path, &script) {
+        console.print(LineKind::Error, &e);
+    }
+}
+
+// Typing in the console shouldn't walk the player around
+fn capture_keyboard(mut keys: ResMut<ButtonInput<KeyCode>>, mut console: ResMut<Console>, settings: Res<ConsoleSettings>) {
+    if keys.just_pressed(settings.toggle_key) {
+        console.open = !console.open;
+    }
+    if console.open {
+        keys.reset_all();
+    }
+}
+
+fn run_console_commands(world: &mut World) {
+    for _ in 0..MAX_LINES_PER_FRAME {
+        let Some(queued) = world.resource_mut::<Console>().queue.pop_front() else {
+            break;
+        };
+        let mut console = world.resource_mut::<Console>();
+        console.print(LineKind::Input, &format!("> {}", queued.line));
+        console.running = queued.scripts;
+        let result = run_line(world, &queued.line);
+        let mut console = world.resource_mut::<Console>();
+        console.running.clear();
+        match result {
+            Ok(output) => console.print(LineKind::Output, &output),
+            Err(e) => console.print(LineKind::Error, &e),
+        }
+    }
+}
+
+fn run_line(world: &mut World, line: &str) -> ConsoleResult {
+    let words = split_words(line)?;
+    let Some((name, rest)) = words.split_first() else {
+        return Ok(String::new());
+    };
+    let (command, id) = world.resource::<ConsoleCommands>().0.get(name.as_str()).cloned()
+        .ok_or_else(|| format!("Unknown command \"{}\", try help", name))?;
+    let args = command.parse(rest)?;
+    world.run_system_with_input(id, args).map_err(|e| format!("{} failed: {}", name, e))?
+}
+
+fn console_window(mut contexts: EguiContexts, mut console: ResMut<Console>, commands: Res<ConsoleCommands>) {
+    if !console.open {
+        return;
+    }
+    let names = commands.names();
+    egui::TopBottomPanel::top("console").show(contexts.ctx_mut(), |ui| {
+        egui::ScrollArea::vertical().max_height(200.0).stick_to_bottom(true).show(ui, |ui| {
+            for (kind, line) in console.log() {
+                let color = match kind {
+                    LineKind::Input => egui::Color32::LIGHT_BLUE,
+                    LineKind::Output => egui::Color32::LIGHT_GRAY,
+                    LineKind::Error => egui::Color32::LIGHT_RED,
+                };
+                ui.label(egui::RichText::new(line).monospace().color(color));
+            }
+        });
+
+        // Taken before the text box sees them so Tab doesn't move focus and the arrows don't move the cursor
+        let (tab, up, down) = ui.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+        ));
+        let mut moved = false;
+        if tab && let Some(completed) = complete(&names, &console.input) {
+            console.input = completed;
+            moved = true;
+        }
+        if up {
+            console.history_previous();
+            moved = true;
+        }
+        if down {
+            console.history_next();
+            moved = true;
+        }
+
+        let response = ui.add(egui::TextEdit::singleline(&mut console.input)
+            .font(egui::TextStyle::Monospace)
+            .desired_width(f32::INFINITY)
+            .hint_text("help"));
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let line = std::mem::take(&mut console.input);
+            console.submit(&line);
+        }
+        response.request_focus();
+        if moved && let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), response.id) {
+            let end = CCursor::new(console.input.chars().count());
+            state.cursor.set_char_range(Some(CCursorRange::one(end)));
+            state.store(ui.ctx(), response.id);
+        }
+
+        // Matching commands while the name is being typed
+        let typed = console.input.split_whitespace().next().unwrap_or_default();
+        let hints: Vec<String> = names.iter()
+            .filter(|name| !typed.is_empty() && (name.starts_with(typed) || typed == **name))
+            .filter_map(|name| commands.get(name).map(ConsoleCommand::usage))
+            .collect();
+        if !hints.is_empty() {
+            ui.weak(hints.join("   "));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        struct TestCase {
+            line: &'static str,
+            expected: Result<ConsoleArgs, &'static str>,
+        }
+
+        let command = ConsoleCommand::new("give", "")
+            .arg("item", ArgKind::Text)
+            .arg("at", ArgKind::Time)
+            .optional_arg("count", ArgKind::Int);
+        let cases = vec!(
+            TestCase {
+                line: "wheat_seeds 18:30 10",
+                expected: Ok(ConsoleArgs(vec!(Arg::Text("wheat_seeds".to_string()), Arg::Time(18, 30), Arg::Int(10)))),
+            },
+            TestCase {
+                line: "\"wheat seeds\" 06:00",
+                expected: Ok(ConsoleArgs(vec!(Arg::Text("wheat seeds".to_string()), Arg::Time(6, 0)))),
+            },
+            TestCase { line: "wheat_seeds", expected: Err("Usage: give <item:text> <at:hh:mm> [count:int]") },
+            TestCase { line: "wheat_seeds 18:30 10 11", expected: Err("Usage: give <item:text> <at:hh:mm> [count:int]") },
+            TestCase { line: "wheat_seeds 25:00", expected: Err("at should be hh:mm, got \"25:00\"") },
+            TestCase { line: "wheat_seeds 18:30 lots", expected: Err("count should be int, got \"lots\"") },
+        );
+
+        for c in cases {
+            let words = split_words(c.line).unwrap();
+            assert_eq!(command.parse(&words), c.expected.map_err(str::to_string), "{}", c.line);
+        }
+
+        assert_eq!(split_words("tp \"1"), Err("Unclosed quote".to_string()));
+        assert_eq!(split_words("  tp   1  2 "), Ok(vec!("tp".to_string(), "1".to_string(), "2".to_string())));
+        assert_eq!(split_words("echo \"\""), Ok(vec!("echo".to_string(), String::new())));
+    }
+
+    #[test]
+    fn completion() {
+        struct TestCase {
+            input: &'static str,
+            expected: Option<&'static str>,
+        }
+
+        let names = ["give", "help", "speed", "spawn", "tp"];
+        let cases = vec!(
+            TestCase { input: "g", expected: Some("give ") },
+            TestCase { input: "sp", expected: None },
+            TestCase { input: "s", expected: Some("sp") },
+            TestCase { input: "x", expected: None },
+            TestCase { input: "tp 1", expected: None },
+        );
+
+        for c in cases {
+            assert_eq!(complete(&names, c.input), c.expected.map(str::to_string), "{}", c.input);
+        }
+    }
+
+    #[test]
+    fn history_and_scripts() {
+        let mut console = Console::default();
+        // Repeats run again but only go in the history once
+        console.submit("tp 1 2");
+        console.submit("tp 1 2");
+        console.submit("speed 4");
+
+        console.history_previous();
+        assert_eq!(console.input, "speed 4");
+        console.history_previous();
+        console.history_previous();
+        assert_eq!(console.input, "tp 1 2");
+        console.history_next();
+        assert_eq!(console.input, "speed 4");
+        console.history_next();
+        assert_eq!(console.input, "");
+
+        // Scripts jump the queue but keep their own order
+        assert_eq!(console.run_script("warp.cfg", "# warp to the farm\ntp 10 20\n\n  time 18:00  \n"), Ok(2));
+        let queue: Vec<&str> = console.queue.iter().map(|q| q.line.as_str()).collect();
+        assert_eq!(queue, vec!("tp 10 20", "time 18:00", "tp 1 2", "tp 1 2", "speed 4"));
+    }
+
+    #[test]
+    fn run_commands() {
+        #[derive(Resource, Default)]
+        struct Speed(f32);
+
+        let mut app = App::new();
+        app.init_resource::<Speed>()
+            .add_console_command(ConsoleCommand::new("speed", "").arg("speed", ArgKind::Float), |In(args): In<ConsoleArgs>, mut speed: ResMut<Speed>| {
+                speed.0 = args.float(0);
+                Ok(format!("Speed is {}", speed.0))
+            })
+            .add_plugins(ConsolePlugin::new());
+
+        let mut console = app.world_mut().resource_mut::<Console>();
+        console.submit("speed 4.5");
+        console.submit("speed fast");
+        console.submit("fly");
+        run_console_commands(app.world_mut());
+
+        assert_eq!(app.world().resource::<Speed>().0, 4.5);
+        let log: Vec<(LineKind, String)> = app.world().resource::<Console>().log().cloned().collect();
+        assert_eq!(log, vec!(
+            (LineKind::Input, "> speed 4.5".to_string()),
+            (LineKind::Output, "Speed is 4.5".to_string()),
+            (LineKind::Input, "> speed fast".to_string()),
+            (LineKind::Error, "speed should be number, got \"fast\"".to_string()),
+            (LineKind::Input, "> fly".to_string()),
+            (LineKind::Error, "Unknown command \"fly\", try help".to_string()),
+        ));
+    }
+
+    #[test]
+    fn recursive_exec() {
+        let dir = std::env::temp_dir().join(format!("console-exec-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let outer = dir.join("outer.cfg").to_string_lossy().to_string();
+        let inner = dir.join("inner.cfg").to_string_lossy().to_string();
+        std::fs::write(&outer, format!("exec {}", inner)).unwrap();
+        std::fs::write(&inner, format!("exec {}", outer)).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(ConsolePlugin::new());
+        app.world_mut().resource_mut::<Console>().submit(&format!("exec {}", outer));
+        for _ in 0..3 {
+            run_console_commands(app.world_mut());
+        }
+
+        let console = app.world().resource::<Console>();
+        assert!(console.queue.is_empty());
+        assert_eq!(console.log().last(), Some(&(LineKind::Error, format!("{} is already running, scripts can't exec themselves", outer))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,67 +1,285 @@
-use std::any::{TypeId};
 use std::collections::{BTreeMap, HashMap};
-use bevy::app::{App, Plugin, Startup, Update};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use bevy::app::{App, AppExit, Last, Plugin, PreStartup, Startup, Update};
 use bevy::input::ButtonInput;
-use bevy::prelude::{IntoSystemConfigs, KeyCode, Res, ResMut, Resource, };
+use bevy::prelude::{error, DetectChanges, DetectChangesMut, EventReader, IntoSystemConfigs, KeyCode, Local, Res, ResMut, Resource};
 use bevy::utils::default;
-use bevy_egui::egui::{FontId, TextStyle};
+use bevy::time::common_conditions::on_timer;
+use bevy_egui::egui::{FontId, Order, TextStyle};
 use bevy_egui::egui::FontFamily::{Monospace, Proportional};
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use bevy_egui::egui::epaint::text::{FontInsert, InsertFontFamily};
+use serde::{Deserialize, Serialize};
+use crate::capture_plugin::{CapturePlugin, CaptureSettings};
 use crate::diagnostic_plugin::DiagnosticPlugin;
+use crate::inspector_plugin::InspectorPlugin;
 
-#[derive(Resource)]
+// --debug or --no-debug on the command line beats GAME_LAB_DEBUG, which beats the saved state
+pub const DEBUG_FLAG: &str = "--debug";
+pub const NO_DEBUG_FLAG: &str = "--no-debug";
+pub const DEBUG_ENV: &str = "GAME_LAB_DEBUG";
+
+// A debug toggle. Slashes nest it under categories in the panel, e.g. "Player/Animation"
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DebugKey {
+    path: &'static str,
+    hotkey: Option<KeyCode>,
+}
+
+impl DebugKey {
+    pub const fn new(path: &'static str) -> Self {
+        Self { path, hotkey: None }
+    }
+
+    // Flips the toggle while debugging is enabled
+    pub const fn with_hotkey(mut self, key: KeyCode) -> Self {
+        self.hotkey = Some(key);
+        self
+    }
+
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    pub fn hotkey(&self) -> Option<KeyCode> {
+        self.hotkey
+    }
+}
+
+// Every key added with add_debug_system, whether or not DebugPlugin was there yet
+#[derive(Resource, Default, Debug)]
+pub struct DebugKeys(pub BTreeMap<&'static str, DebugKey>);
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
 pub struct DebugState {
     pub enabled: bool,
-    pub keys: HashMap<String, bool>,
-    pub av: Vec<TypeId>
+    // By path, so categories sort together
+    pub keys: BTreeMap<String, bool>,
+    // Left-top corner of every egui window by its id, handed back to debug_window as the default position
+    pub window_positions: HashMap<u64, [f32; 2]>,
 }
 
-impl Default for DebugState {
-    fn default() -> Self {
-        Self { enabled: true, keys: HashMap::new(), av: Vec::new() }
+impl DebugState {
+    pub fn read(path: &PathBuf) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&text)
+            .inspect_err(|e| error!("Ignoring debug state {}: {}", path.display(), e))
+            .ok()
     }
 }
 
+#[derive(Resource, Clone, Debug)]
+pub struct DebugSettings {
+    pub toggle_key: KeyCode,
+    pub state_file: Option<PathBuf>,
+    // Toggles from the last run, picked up as each debug system registers its key
+    pub saved_keys: BTreeMap<String, bool>,
+}
+
+pub fn startup_enabled(args: &[String], env: Option<&str>, saved: Option<bool>, default: bool) -> bool {
+    let flag = args.iter().rev().find_map(|arg| match arg.as_str() {
+        DEBUG_FLAG => Some(true),
+        NO_DEBUG_FLAG => Some(false),
+        _ => None,
+    });
+    let env = env.and_then(|value| match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "on" => Some(true),
+        "0" | "false" | "off" => Some(false),
+        _ => None,
+    });
+    flag.or(env).or(saved).unwrap_or(default)
+}
+
 pub struct DebugPlugin {
     pub enabled: bool,
+    pub toggle_key: KeyCode,
+    pub state_file: Option<PathBuf>,
+    pub capture: CaptureSettings,
 }
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(DebugState{ enabled:self.enabled,..default()})
+        let saved = self.state_file.as_ref().and_then(DebugState::read).unwrap_or_default();
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let env = std::env::var(DEBUG_ENV).ok();
+        let saved_enabled = self.state_file.as_ref().filter(|path| path.exists()).map(|_| saved.enabled);
+        let enabled = startup_enabled(&args, env.as_deref(), saved_enabled, self.enabled);
+
+        app.insert_resource(DebugSettings {
+                toggle_key: self.toggle_key,
+                state_file: self.state_file.clone(),
+                saved_keys: saved.keys,
+            })
+            .insert_resource(DebugState { enabled, window_positions: saved.window_positions, ..default() })
+            .init_resource::<DebugKeys>()
             .add_plugins(EguiPlugin)
             .add_plugins(DiagnosticPlugin)
+            .add_plugins(InspectorPlugin)
+            .add_plugins(CapturePlugin { settings: self.capture.clone() })
+            .add_systems(PreStartup, register_debug_keys)
             .add_systems(Startup, load_and_set_egui_fonts)
-            .add_systems(Update, toggle_debug);
+            .add_systems(Update, (toggle_debug, debug_hotkeys, track_window_positions.run_if(on_timer(Duration::from_secs(1)))))
+            .add_systems(Last, save_debug_state);
     }
 }
 
 impl DebugPlugin {
     pub fn new(enabled: bool) -> Self {
-        Self { enabled }
+        Self { enabled, toggle_key: KeyCode::Backquote, state_file: None, capture: CaptureSettings::default() }
+    }
+
+    pub fn with_toggle_key(mut self, key: KeyCode) -> Self {
+        self.toggle_key = key;
+        self
+    }
+
+    // Toggles, window positions and the enabled flag are kept here between runs
+    pub fn with_state_file(mut self, path: &str) -> Self {
+        self.state_file = Some(PathBuf::from(path));
+        self
+    }
+
+    // Screenshot and recording keys, where captures go and how clips are recorded
+    pub fn with_capture(mut self, settings: CaptureSettings) -> Self {
+        self.capture = settings;
+        self
     }
 }
 
 pub fn debug_enable(engine_state: Res<DebugState>) -> bool {
     engine_state.enabled
 }
-pub fn debug_enable_for_key(key: String) -> impl FnMut(Res<DebugState>) -> bool {
+pub fn debug_enable_for_key(key: DebugKey) -> impl FnMut(Res<DebugState>) -> bool {
     move |state: Res<DebugState>| {
-        if let Some(v) = state.keys.get(&key) {
-            v.clone()
+        if let Some(v) = state.keys.get(key.path()) {
+            *v
         } else {
             false
         }
     }
 }
 
-fn toggle_debug(keys: Res<ButtonInput<KeyCode>>, mut engine_state: ResMut<DebugState>) {
-    if keys.just_pressed(KeyCode::Backquote) {
+// Splits paths into the ones that sit at this level and the ones under each category
+pub fn group_paths<'a>(paths: &[&'a str]) -> (Vec<&'a str>, BTreeMap<&'a str, Vec<&'a str>>) {
+    let mut leaves = vec!();
+    let mut categories: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for path in paths {
+        match path.split_once('/') {
+            Some((category, rest)) => categories.entry(category).or_default().push(rest),
+            None => leaves.push(*path),
+        }
+    }
+    (leaves, categories)
+}
+
+// Checkboxes for the toggles, each category in its own collapsing section
+pub fn debug_keys_ui(ui: &mut egui::Ui, state: &mut DebugState, keys: &DebugKeys) {
+    let paths: Vec<&str> = keys.0.keys().copied().collect();
+    keys_ui(ui, "", &paths, state, keys);
+}
+
+fn keys_ui(ui: &mut egui::Ui, prefix: &str, paths: &[&str], state: &mut DebugState, keys: &DebugKeys) {
+    let (leaves, categories) = group_paths(paths);
+    for leaf in leaves {
+        let path = format!("{}{}", prefix, leaf);
+        let label = match keys.0.get(path.as_str()).and_then(|k| k.hotkey()) {
+            Some(hotkey) => format!("{} ({:?})", leaf, hotkey),
+            None => leaf.to_string(),
+        };
+        let mut on = state.keys.get(&path).copied().unwrap_or(false);
+        if ui.checkbox(&mut on, label).changed() {
+            state.keys.insert(path, on);
+        }
+    }
+    for (category, rest) in categories {
+        let prefix = format!("{}{}/", prefix, category);
+        egui::CollapsingHeader::new(category).id_salt(&prefix).default_open(true).show(ui, |ui| {
+            keys_ui(ui, &prefix, &rest, state, keys);
+        });
+    }
+}
+
+// The usual debug window, opening where it was left last run
+pub fn debug_window(title: &str, state: &DebugState) -> egui::Window<'static> {
+    let window = egui::Window::new(title.to_string()).max_width(300.0).resizable([false, false]);
+    match state.window_positions.get(&egui::Id::new(title).value()) {
+        Some([x, y]) => window.default_pos([*x, *y]),
+        None => window,
+    }
+}
+
+// Keys added after the state was loaded start as they were left last run
+fn register_debug_keys(keys: Res<DebugKeys>, settings: Res<DebugSettings>, mut state: ResMut<DebugState>) {
+    for path in keys.0.keys() {
+        if !state.keys.contains_key(*path) {
+            state.keys.insert(path.to_string(), settings.saved_keys.get(*path).copied().unwrap_or(false));
+        }
+    }
+}
+
+fn debug_hotkeys(input: Res<ButtonInput<KeyCode>>, keys: Res<DebugKeys>, mut state: ResMut<DebugState>) {
+    if !state.enabled {
+        return;
+    }
+    for key in keys.0.values() {
+        if let Some(hotkey) = key.hotkey() && input.just_pressed(hotkey) {
+            let on = state.keys.entry(key.path().to_string()).or_default();
+            *on = !*on;
+        }
+    }
+}
+
+fn toggle_debug(keys: Res<ButtonInput<KeyCode>>, settings: Res<DebugSettings>, mut engine_state: ResMut<DebugState>) {
+    if keys.just_pressed(settings.toggle_key) {
         engine_state.enabled = !engine_state.enabled;
     }
 }
 
+fn track_window_positions(mut contexts: EguiContexts, mut state: ResMut<DebugState>) {
+    let positions: Vec<(u64, [f32; 2])> = contexts.ctx_mut().memory(|memory| {
+        memory.layer_ids()
+            .filter(|layer| layer.order == Order::Middle)
+            .filter_map(|layer| memory.area_rect(layer.id).map(|rect| (layer.id.value(), [rect.min.x, rect.min.y])))
+            .collect()
+    });
+    // Only a moved window counts as a change, so the state isn't rewritten every second
+    for (id, position) in positions {
+        if state.window_positions.get(&id) != Some(&position) {
+            state.bypass_change_detection().window_positions.insert(id, position);
+            state.set_changed();
+        }
+    }
+}
+
+fn save_debug_state(
+    mut exit: EventReader<AppExit>,
+    mut written: Local<Option<DebugState>>,
+    state: Res<DebugState>,
+    settings: Res<DebugSettings>,
+) {
+    let Some(path) = &settings.state_file else {
+        return;
+    };
+    let exiting = exit.read().count() > 0;
+    if (!exiting && !state.is_changed()) || written.as_ref() == Some(&*state) {
+        return;
+    }
+    // Keys registered this run win, the rest are carried over for systems that weren't added
+    let mut saved = state.clone();
+    for (key, value) in &settings.saved_keys {
+        saved.keys.entry(key.clone()).or_insert(*value);
+    }
+    let result = path.parent().map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(path, serde_json::to_string_pretty(&saved).unwrap_or_default()));
+    match result {
+        Ok(_) => *written = Some(state.clone()),
+        Err(e) => error!("Could not write debug state {}: {}", path.display(), e),
+    }
+}
+
 fn load_and_set_egui_fonts(contexts: EguiContexts) {
     let text_styles: BTreeMap<TextStyle, FontId> = [
         (TextStyle::Heading, FontId::new(18.0, Proportional)),
@@ -70,7 +288,7 @@ fn load_and_set_egui_fonts(contexts: EguiContexts) {
         (TextStyle::Button, FontId::new(15.0, Proportional)),
         (TextStyle::Small, FontId::new(12.0, Proportional)),
     ].into();
-    
+
     contexts.ctx().all_styles_mut(move |style| style.text_styles = text_styles.clone());
     contexts.ctx().add_font(FontInsert::new(
         "debugger_font",
@@ -84,15 +302,103 @@ fn load_and_set_egui_fonts(contexts: EguiContexts) {
 }
 
 pub trait Debugger {
-    fn add_debug_system<M>(&mut self, systems: impl IntoSystemConfigs<M>, key: String) -> &mut Self;
+    fn add_debug_system<M>(&mut self, systems: impl IntoSystemConfigs<M>, key: DebugKey) -> &mut Self;
 }
 
 impl Debugger for App {
-    fn add_debug_system<M>(&mut self, systems: impl IntoSystemConfigs<M>, key: String) -> &mut Self {
-        if let Some(mut res) = self.world_mut().get_resource_mut::<DebugState>() {
-            res.keys.insert(key.clone(), false);
-        }
+    fn add_debug_system<M>(&mut self, systems: impl IntoSystemConfigs<M>, key: DebugKey) -> &mut Self {
+        self.world_mut().get_resource_or_init::<DebugKeys>().0.insert(key.path(), key);
         self.add_systems(Update, systems.run_if(debug_enable).run_if(debug_enable_for_key(key)));
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn startup() {
+        struct TestCase {
+            args: Vec<&'static str>,
+            env: Option<&'static str>,
+            saved: Option<bool>,
+            default: bool,
+            expected: bool,
+        }
+
+        let cases = vec!(
+            TestCase { args: vec!(), env: None, saved: None, default: true, expected: true },
+            TestCase { args: vec!(), env: None, saved: Some(false), default: true, expected: false },
+            TestCase { args: vec!(), env: Some("1"), saved: Some(false), default: false, expected: true },
+            TestCase { args: vec!(), env: Some(" Off "), saved: Some(true), default: true, expected: false },
+            TestCase { args: vec!(), env: Some("maybe"), saved: Some(true), default: false, expected: true },
+            TestCase { args: vec!("--debug"), env: Some("0"), saved: Some(false), default: false, expected: true },
+            TestCase { args: vec!("--no-debug"), env: Some("1"), saved: None, default: true, expected: false },
+            // The last flag wins
+            TestCase { args: vec!("--debug", "--level", "2", "--no-debug"), env: None, saved: None, default: true, expected: false },
+        );
+
+        for c in cases {
+            let args: Vec<String> = c.args.iter().map(|a| a.to_string()).collect();
+            assert_eq!(startup_enabled(&args, c.env, c.saved, c.default), c.expected, "{:?} {:?}", c.args, c.env);
+        }
+    }
+
+    #[test]
+    fn group() {
+        struct TestCase {
+            paths: Vec<&'static str>,
+            leaves: Vec<&'static str>,
+            categories: Vec<(&'static str, Vec<&'static str>)>,
+        }
+
+        let cases = vec!(
+            TestCase { paths: vec!(), leaves: vec!(), categories: vec!() },
+            TestCase {
+                paths: vec!("Inspector", "Map/Collision", "Player/Animation", "Player/Bounds/Sprite", "Saves"),
+                leaves: vec!("Inspector", "Saves"),
+                categories: vec!(("Map", vec!("Collision")), ("Player", vec!("Animation", "Bounds/Sprite"))),
+            },
+        );
+
+        for c in cases {
+            let (leaves, categories) = group_paths(&c.paths);
+            assert_eq!(leaves, c.leaves);
+            assert_eq!(categories.into_iter().collect::<Vec<_>>(), c.categories);
+        }
+    }
+
+    #[test]
+    fn register_before_plugin() {
+        const ANIMATION: DebugKey = DebugKey::new("Player/Animation").with_hotkey(KeyCode::F2);
+        const BOUNDS: DebugKey = DebugKey::new("Player/Bounds");
+
+        // A game plugin registering ahead of the debug resources
+        let mut app = App::new();
+        app.add_debug_system(|| {}, ANIMATION)
+            .add_debug_system(|| {}, BOUNDS);
+        let mut saved_keys = BTreeMap::new();
+        saved_keys.insert("Player/Bounds".to_string(), true);
+        app.insert_resource(DebugSettings { toggle_key: KeyCode::Backquote, state_file: None, saved_keys })
+            .insert_resource(DebugState { enabled: true, ..default() })
+            .add_systems(PreStartup, register_debug_keys);
+        app.update();
+
+        let state = app.world().resource::<DebugState>();
+        assert_eq!(state.keys.iter().map(|(k, v)| (k.as_str(), *v)).collect::<Vec<_>>(), vec!(("Player/Animation", false), ("Player/Bounds", true)));
+        assert_eq!(app.world().resource::<DebugKeys>().0["Player/Animation"].hotkey(), Some(KeyCode::F2));
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let mut state = DebugState { enabled: true, ..Default::default() };
+        state.keys.insert("Player".to_string(), true);
+        state.window_positions.insert(egui::Id::new("Diagnostics").value(), [12.0, 40.5]);
+
+        let text = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<DebugState>(&text).unwrap(), state);
+        // Older files and hand edits can leave fields out
+        assert_eq!(serde_json::from_str::<DebugState>(r#"{ "enabled": true }"#).unwrap(), DebugState { enabled: true, ..Default::default() });
+    }
+}
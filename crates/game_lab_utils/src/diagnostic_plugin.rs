@@ -1,41 +1,353 @@
-use std::time::Duration;
-use bevy::app::{App, Plugin, Update};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use bevy::app::{App, Last, Plugin, Update};
+use bevy::asset::{Asset, Assets};
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
-use bevy::prelude::{IntoSystemConfigs, Res, ResMut, Resource};
+use bevy::image::Image;
+use bevy::prelude::{error, info, ColorMaterial, Font, IntoSystemConfigs, Mesh, Res, ResMut, Resource, Shader, TextureAtlasLayout, World};
 use bevy::time::common_conditions::on_timer;
+use bevy::time::{Real, Time};
+use bevy_egui::egui::{Color32, Stroke};
 use bevy_egui::{egui, EguiContexts};
-use crate::debug_plugin::{debug_enable, DebugState};
+use crate::debug_plugin::{debug_enable, debug_keys_ui, debug_window, DebugKeys, DebugState};
+use crate::inspector_plugin::short_name;
+use crate::system_timings::{collect_system_timings, SpanKind, SystemTimings};
+
+// About five seconds at 60fps
+const FRAME_HISTORY: usize = 300;
+const SLOWEST_SHOWN: usize = 10;
+// Relative to the working directory, so cargo run drops them next to the project
+const EXPORT_FOLDER: &str = "diagnostics";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    // Average fps over the slowest 1% of frames
+    pub one_percent_low_fps: f64,
+}
+
+impl FrameStats {
+    pub fn from_frame_times(frame_times_ms: &[f64]) -> Self {
+        if frame_times_ms.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = frame_times_ms.to_vec();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        let slowest = &sorted[..sorted.len().div_ceil(100)];
+        let slowest_avg = slowest.iter().sum::<f64>() / slowest.len() as f64;
+
+        Self {
+            min_ms: sorted[sorted.len() - 1],
+            avg_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            max_ms: sorted[0],
+            one_percent_low_fps: if slowest_avg > 0.0 { 1000.0 / slowest_avg } else { 0.0 },
+        }
+    }
+}
+
+// Everything the panel shows, refreshed every 100ms
+#[derive(Resource, Clone, Debug, Default)]
+pub struct DiagnosticsReport {
+    pub fps: f64,
+    pub frame: FrameStats,
+    pub entities: usize,
+    pub assets: Vec<(String, usize)>,
+    pub memory_bytes: Option<u64>,
+    pub systems: Vec<(String, f64)>,
+    pub schedules: Vec<(String, f64)>,
+}
+
+impl DiagnosticsReport {
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("kind,name,value\n");
+        let mut row = |kind: &str, name: &str, value: String| {
+            let _ = writeln!(csv, "{},{},{}", kind, csv_field(name), value);
+        };
+        row("frame", "fps", format!("{:.2}", self.fps));
+        row("frame", "min_ms", format!("{:.3}", self.frame.min_ms));
+        row("frame", "avg_ms", format!("{:.3}", self.frame.avg_ms));
+        row("frame", "max_ms", format!("{:.3}", self.frame.max_ms));
+        row("frame", "one_percent_low_fps", format!("{:.2}", self.frame.one_percent_low_fps));
+        row("world", "entities", self.entities.to_string());
+        if let Some(bytes) = self.memory_bytes {
+            row("memory", "resident_bytes", bytes.to_string());
+        }
+        for (name, count) in &self.assets {
+            row("assets", name, count.to_string());
+        }
+        for (name, ms) in &self.schedules {
+            row(SpanKind::Schedule.label(), name, format!("{:.4}", ms));
+        }
+        for (name, ms) in &self.systems {
+            row(SpanKind::System.label(), name, format!("{:.4}", ms));
+        }
+        csv
+    }
+}
+
+// Generic type names have commas in them
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Resident memory of the process from /proc, so only on Linux
+pub fn process_memory() -> Option<u64> {
+    fs::read_to_string("/proc/self/status").ok().as_deref().and_then(parse_vm_rss)
+}
+
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.trim_start_matches("VmRSS:").trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[derive(Resource, Default)]
+struct FrameHistory {
+    frame_times_ms: VecDeque<f64>,
+}
+
+type AssetCounter = fn(&World) -> usize;
 
 #[derive(Resource)]
-struct InternalDiagnostics {
-    fps: f64,
+struct AssetCounters(Vec<(String, AssetCounter)>);
+
+fn count_assets<A: Asset>(world: &World) -> usize {
+    world.get_resource::<Assets<A>>().map_or(0, |assets| assets.len())
+}
+
+pub trait DiagnosticsApp {
+    // Adds a row to the panel's asset counts, e.g. for a game's own materials
+    fn add_asset_count<A: Asset>(&mut self) -> &mut Self;
+}
+
+impl DiagnosticsApp for App {
+    fn add_asset_count<A: Asset>(&mut self) -> &mut Self {
+        if let Some(mut counters) = self.world_mut().get_resource_mut::<AssetCounters>() {
+            counters.0.push((short_name(std::any::type_name::<A>()), count_assets::<A>));
+        }
+        self
+    }
 }
 
 pub struct DiagnosticPlugin;
 
 impl Plugin for DiagnosticPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(InternalDiagnostics { fps: 0.0 })
+        app.init_resource::<DiagnosticsReport>()
+            .init_resource::<FrameHistory>()
+            .insert_resource(AssetCounters(vec!()))
+            .add_asset_count::<Image>()
+            .add_asset_count::<TextureAtlasLayout>()
+            .add_asset_count::<Mesh>()
+            .add_asset_count::<ColorMaterial>()
+            .add_asset_count::<Shader>()
+            .add_asset_count::<Font>()
             .add_plugins(FrameTimeDiagnosticsPlugin::default())
-            .add_systems(Update, (update_fps.run_if(on_timer(Duration::from_secs_f32(0.100))), diagnostics.run_if(debug_enable)));
+            .add_systems(Update, (record_frame_time, update_report.run_if(on_timer(Duration::from_secs_f32(0.100))), diagnostics.run_if(debug_enable)))
+            .add_systems(Last, collect_system_timings);
     }
 }
 
-fn update_fps(mut diagnostics: ResMut<InternalDiagnostics>, store: Res<DiagnosticsStore>) {
-    if let Some(fps) = store.get(&FrameTimeDiagnosticsPlugin::FPS) {
-        diagnostics.fps = fps.value().unwrap_or(0.0);
-    } else {
-        diagnostics.fps = 0.0;
+fn record_frame_time(mut history: ResMut<FrameHistory>, time: Res<Time<Real>>) {
+    if history.frame_times_ms.len() == FRAME_HISTORY {
+        history.frame_times_ms.pop_front();
     }
+    history.frame_times_ms.push_back(time.delta_secs_f64() * 1000.0);
+}
+
+fn update_report(world: &mut World) {
+    let fps = world.resource::<DiagnosticsStore>()
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.value())
+        .unwrap_or(0.0);
+    let frame_times: Vec<f64> = world.resource::<FrameHistory>().frame_times_ms.iter().copied().collect();
+    let assets = world.resource::<AssetCounters>().0.iter()
+        .map(|(name, count)| (name.clone(), count(world)))
+        .collect();
+    let (systems, schedules) = match world.get_resource::<SystemTimings>() {
+        Some(timings) => {
+            let owned = |spans: Vec<(&str, f64)>| spans.into_iter().map(|(name, ms)| (name.to_string(), ms)).collect();
+            (owned(timings.slowest(SpanKind::System, usize::MAX)), owned(timings.slowest(SpanKind::Schedule, usize::MAX)))
+        },
+        None => (vec!(), vec!()),
+    };
+
+    let report = DiagnosticsReport {
+        fps,
+        frame: FrameStats::from_frame_times(&frame_times),
+        entities: world.entities().len() as usize,
+        assets,
+        memory_bytes: process_memory(),
+        systems,
+        schedules,
+    };
+    world.insert_resource(report);
+}
+
+fn export_csv(report: &DiagnosticsReport) {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let path = PathBuf::from(EXPORT_FOLDER).join(format!("diagnostics-{}.csv", secs));
+    match fs::create_dir_all(EXPORT_FOLDER).and_then(|_| fs::write(&path, report.to_csv())) {
+        Ok(_) => info!("Diagnostics written to {}", path.display()),
+        Err(e) => error!("Could not write diagnostics to {}: {}", path.display(), e),
+    }
+}
+
+fn frame_graph(ui: &mut egui::Ui, frame_times_ms: &VecDeque<f64>) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(280.0, 60.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, Color32::from_black_alpha(120));
+
+    // 30fps at the top unless a spike goes past it, the line marks 60fps
+    let top = frame_times_ms.iter().copied().fold(1000.0 / 30.0, f64::max);
+    let y = |ms: f64| rect.bottom() - (ms / top) as f32 * rect.height();
+    painter.hline(rect.x_range(), y(1000.0 / 60.0), Stroke::new(1.0, Color32::DARK_GREEN));
+
+    let step = rect.width() / (FRAME_HISTORY - 1) as f32;
+    let points = frame_times_ms.iter().enumerate()
+        .map(|(i, ms)| egui::pos2(rect.left() + i as f32 * step, y(*ms)))
+        .collect();
+    painter.add(egui::Shape::line(points, Stroke::new(1.0, Color32::LIGHT_GREEN)));
 }
 
-fn diagnostics(mut ctx: EguiContexts, diagnostics: Res<InternalDiagnostics>, mut debug_state: ResMut<DebugState>) {
-    egui::Window::new("Diagnostics").show(ctx.ctx_mut(), |ui| {
-        ui.label(format!("FPS: {:.2}", diagnostics.fps));
-        for (key, value) in debug_state.keys.clone() {
-            if ui.button(key.clone()).clicked() {
-                debug_state.keys.insert(key.clone(), !value.clone());
+fn timings_grid(ui: &mut egui::Ui, id: &str, spans: &[(String, f64)]) {
+    egui::Grid::new(id).num_columns(2).striped(true).show(ui, |ui| {
+        for (name, ms) in spans.iter().take(SLOWEST_SHOWN) {
+            ui.label(name);
+            ui.label(format!("{:.3} ms", ms));
+            ui.end_row();
+        }
+    });
+}
+
+fn diagnostics(mut ctx: EguiContexts, report: Res<DiagnosticsReport>, history: Res<FrameHistory>, keys: Res<DebugKeys>, mut debug_state: ResMut<DebugState>) {
+    debug_window("Diagnostics", &debug_state).show(ctx.ctx_mut(), |ui| {
+        ui.label(format!("FPS: {:.2}", report.fps));
+        frame_graph(ui, &history.frame_times_ms);
+
+        egui::Grid::new("diagnostics_frame").num_columns(2).striped(true).show(ui, |ui| {
+            ui.label("Frame min / avg / max");
+            ui.label(format!("{:.1} / {:.1} / {:.1} ms", report.frame.min_ms, report.frame.avg_ms, report.frame.max_ms));
+            ui.end_row();
+            ui.label("1% low");
+            ui.label(format!("{:.1} fps", report.frame.one_percent_low_fps));
+            ui.end_row();
+            ui.label("Entities");
+            ui.label(report.entities.to_string());
+            ui.end_row();
+            ui.label("Memory");
+            ui.label(report.memory_bytes.map_or("n/a".to_string(), |b| format!("{:.1} MiB", b as f64 / 1024.0 / 1024.0)));
+            ui.end_row();
+        });
+
+        egui::CollapsingHeader::new("Assets").show(ui, |ui| {
+            egui::Grid::new("diagnostics_assets").num_columns(2).striped(true).show(ui, |ui| {
+                for (name, count) in &report.assets {
+                    ui.label(name);
+                    ui.label(count.to_string());
+                    ui.end_row();
+                }
+            });
+        });
+
+        egui::CollapsingHeader::new("Systems").show(ui, |ui| {
+            if report.systems.is_empty() && report.schedules.is_empty() {
+                ui.weak("No timings, run with --features game_lab_utils/system_timings");
+                return;
             }
+            ui.label("Schedules");
+            timings_grid(ui, "diagnostics_schedules", &report.schedules);
+            ui.label("Slowest systems");
+            timings_grid(ui, "diagnostics_systems", &report.systems);
+        });
+
+        if ui.button("Export CSV").clicked() {
+            export_csv(&report);
         }
+
+        ui.separator();
+        debug_keys_ui(ui, &mut debug_state, &keys);
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_stats() {
+        struct TestCase {
+            frame_times_ms: Vec<f64>,
+            expected: FrameStats,
+        }
+
+        let mut steady_with_spike = vec!(10.0; 199);
+        steady_with_spike.push(50.0);
+
+        let cases = vec!(
+            TestCase { frame_times_ms: vec!(), expected: FrameStats::default() },
+            TestCase {
+                frame_times_ms: vec!(20.0, 10.0, 30.0),
+                expected: FrameStats { min_ms: 10.0, avg_ms: 20.0, max_ms: 30.0, one_percent_low_fps: 1000.0 / 30.0 },
+            },
+            // Two frames make up the slowest 1% of 200
+            TestCase {
+                frame_times_ms: steady_with_spike,
+                expected: FrameStats { min_ms: 10.0, avg_ms: 10.2, max_ms: 50.0, one_percent_low_fps: 1000.0 / 30.0 },
+            },
+        );
+
+        for c in cases {
+            let stats = FrameStats::from_frame_times(&c.frame_times_ms);
+            assert!((stats.min_ms - c.expected.min_ms).abs() < 1e-9, "{:?}", stats);
+            assert!((stats.avg_ms - c.expected.avg_ms).abs() < 1e-9, "{:?}", stats);
+            assert!((stats.max_ms - c.expected.max_ms).abs() < 1e-9, "{:?}", stats);
+            assert!((stats.one_percent_low_fps - c.expected.one_percent_low_fps).abs() < 1e-9, "{:?}", stats);
+        }
+    }
+
+    #[test]
+    fn vm_rss() {
+        let cases = vec!(
+            ("Name:\tgame-1\nVmPeak:\t  900 kB\nVmRSS:\t  123456 kB\nThreads:\t12\n", Some(123456 * 1024)),
+            ("Name:\tgame-1\n", None),
+        );
+
+        for (status, expected) in cases {
+            assert_eq!(parse_vm_rss(status), expected);
+        }
+    }
+
+    #[test]
+    fn csv() {
+        let report = DiagnosticsReport {
+            fps: 60.0,
+            frame: FrameStats { min_ms: 16.0, avg_ms: 16.5, max_ms: 20.0, one_percent_low_fps: 50.0 },
+            entities: 42,
+            assets: vec!(("Image".to_string(), 7)),
+            memory_bytes: None,
+            systems: vec!(("apply<A, B>".to_string(), 0.25)),
+            schedules: vec!(("Update".to_string(), 2.0)),
+        };
+
+        assert_eq!(report.to_csv(), "kind,name,value\n\
+            frame,fps,60.00\n\
+            frame,min_ms,16.000\n\
+            frame,avg_ms,16.500\n\
+            frame,max_ms,20.000\n\
+            frame,one_percent_low_fps,50.00\n\
+            world,entities,42\n\
+            assets,Image,7\n\
+            schedule,Update,2.0000\n\
+            system,\"apply<A, B>\",0.2500\n");
+    }
+}
@@ -0,0 +1,231 @@
+use std::time::Duration;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::color::{Alpha, Color};
+use bevy::input::ButtonInput;
+use bevy::prelude::{in_state, AppExtStates, BackgroundColor, Commands, Component, Condition, Event, EventReader, EventWriter, GlobalZIndex, IntoSystemConfigs, KeyCode, NextState, Node, OnEnter, PositionType, Res, ResMut, Resource, Single, State, StateScoped, States, Text, TextFont, UiRect, Val, With};
+use bevy::state::state::FreelyMutableState;
+use bevy::time::{Real, Time, Timer, TimerMode};
+
+const DEFAULT_FADE: Duration = Duration::from_millis(400);
+// Above the game UI, egui draws on top of this anyway
+const FADE_Z_INDEX: i32 = 1000;
+const DEFAULT_START_KEY: KeyCode = KeyCode::Enter;
+const DEFAULT_PAUSE_KEY: KeyCode = KeyCode::Escape;
+
+// A project declares its own enum and says which variants are the menu, loading, playing and paused
+pub trait GameStates: States + FreelyMutableState + Copy {
+    const MAIN_MENU: Self;
+    const LOADING: Self;
+    const PLAYING: Self;
+    const PAUSED: Self;
+}
+
+// Fades to black, switches state, then fades back in
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TransitionRequest<S: GameStates>(pub S);
+
+// Flips between playing and paused straight away, anything else is ignored
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TogglePause;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum FadePhase {
+    #[default]
+    Idle,
+    Out,
+    In,
+}
+
+#[derive(Resource)]
+pub struct Fade<S: GameStates> {
+    pub phase: FadePhase,
+    pub timer: Timer,
+    pub target: Option<S>,
+}
+
+impl<S: GameStates> Fade<S> {
+    pub fn is_fading(&self) -> bool {
+        self.phase != FadePhase::Idle
+    }
+
+    // 0.0 is clear, 1.0 is fully black
+    pub fn alpha(&self) -> f32 {
+        match self.phase {
+            FadePhase::Idle => 0.0,
+            FadePhase::Out => self.timer.fraction(),
+            FadePhase::In => 1.0 - self.timer.fraction(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct FadeOverlay;
+
+pub struct GameStatePlugin<S: GameStates> {
+    initial: S,
+    fade: Duration,
+    keys: StateKeys,
+}
+
+#[derive(Resource, Clone, Copy)]
+struct StateKeys {
+    start: KeyCode,
+    // None when the game sends TogglePause itself, e.g. from a rebindable action
+    pause: Option<KeyCode>,
+}
+
+impl<S: GameStates> GameStatePlugin<S> {
+    pub fn new(initial: S) -> Self {
+        Self {
+            initial,
+            fade: DEFAULT_FADE,
+            keys: StateKeys { start: DEFAULT_START_KEY, pause: Some(DEFAULT_PAUSE_KEY) },
+        }
+    }
+
+    // Starts the game from the main menu
+    pub fn with_start_key(mut self, key: KeyCode) -> Self {
+        self.keys.start = key;
+        self
+    }
+
+    pub fn with_pause_key(mut self, key: KeyCode) -> Self {
+        self.keys.pause = Some(key);
+        self
+    }
+
+    // For games that send TogglePause from their own input
+    pub fn without_pause_key(mut self) -> Self {
+        self.keys.pause = None;
+        self
+    }
+
+    // Length of each half of a transition
+    pub fn with_fade(mut self, fade: Duration) -> Self {
+        self.fade = fade;
+        self
+    }
+}
+
+impl<S: GameStates> Plugin for GameStatePlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.insert_state(self.initial)
+            .enable_state_scoped_entities::<S>()
+            .add_event::<TransitionRequest<S>>()
+            .add_event::<TogglePause>()
+            .insert_resource(self.keys)
+            .insert_resource(Fade::<S> {
+                phase: FadePhase::Idle,
+                timer: Timer::new(self.fade, TimerMode::Once),
+                target: None,
+            })
+            .add_systems(Startup, spawn_fade_overlay)
+            .add_systems(OnEnter(S::MAIN_MENU), show_main_menu::<S>)
+            .add_systems(OnEnter(S::PAUSED), show_paused::<S>)
+            .add_systems(Update, start_game::<S>.run_if(in_state(S::MAIN_MENU)))
+            .add_systems(Update, send_pause.run_if(in_state(S::PLAYING).or(in_state(S::PAUSED))).before(toggle_pause::<S>))
+            .add_systems(Update, (toggle_pause::<S>, start_transition::<S>, update_fade::<S>).chain());
+    }
+}
+
+fn spawn_fade_overlay(mut commands: Commands) {
+    commands.spawn((
+        FadeOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..Default::default()
+        },
+        BackgroundColor(Color::NONE),
+        GlobalZIndex(FADE_Z_INDEX),
+    ));
+}
+
+// Centred text that goes away when the state is left
+pub fn spawn_banner<S: GameStates>(commands: &mut Commands, state: S, text: &str) {
+    commands.spawn((
+        Text::new(text),
+        TextFont { font_size: 42.0, ..Default::default() },
+        Node {
+            margin: UiRect::all(Val::Auto),
+            top: Val::Percent(40.0),
+            ..Default::default()
+        },
+        StateScoped(state),
+    ));
+}
+
+fn show_main_menu<S: GameStates>(mut commands: Commands) {
+    spawn_banner(&mut commands, S::MAIN_MENU, "Press Enter to start");
+}
+
+fn show_paused<S: GameStates>(mut commands: Commands) {
+    spawn_banner(&mut commands, S::PAUSED, "Paused");
+}
+
+fn start_game<S: GameStates>(keys: Res<ButtonInput<KeyCode>>, state_keys: Res<StateKeys>, mut writer: EventWriter<TransitionRequest<S>>) {
+    if keys.just_pressed(state_keys.start) {
+        writer.send(TransitionRequest(S::LOADING));
+    }
+}
+
+fn send_pause(keys: Res<ButtonInput<KeyCode>>, state_keys: Res<StateKeys>, mut writer: EventWriter<TogglePause>) {
+    if let Some(key) = state_keys.pause && keys.just_pressed(key) {
+        writer.send(TogglePause);
+    }
+}
+
+fn toggle_pause<S: GameStates>(
+    mut reader: EventReader<TogglePause>,
+    mut next_state: ResMut<NextState<S>>,
+    state: Res<State<S>>,
+    fade: Res<Fade<S>>,
+) {
+    // Several toggles in one frame count as one, the state only moves at the end of it
+    if reader.read().last().is_none() || fade.is_fading() {
+        return;
+    }
+    if *state.get() == S::PLAYING {
+        next_state.set(S::PAUSED);
+    } else if *state.get() == S::PAUSED {
+        next_state.set(S::PLAYING);
+    }
+}
+
+fn start_transition<S: GameStates>(mut reader: EventReader<TransitionRequest<S>>, mut fade: ResMut<Fade<S>>) {
+    // A transition already under way wins, the request is dropped
+    if let Some(request) = reader.read().last() && !fade.is_fading() {
+        fade.phase = FadePhase::Out;
+        fade.target = Some(request.0);
+        fade.timer.reset();
+    }
+}
+
+fn update_fade<S: GameStates>(
+    mut fade: ResMut<Fade<S>>,
+    mut next_state: ResMut<NextState<S>>,
+    overlay: Single<&mut BackgroundColor, With<FadeOverlay>>,
+    time: Res<Time<Real>>,
+) {
+    if !fade.is_fading() {
+        return;
+    }
+    // Real time so a paused virtual clock doesn't stop the fade
+    fade.timer.tick(time.delta());
+
+    if fade.timer.finished() {
+        match fade.phase {
+            FadePhase::Out => {
+                if let Some(target) = fade.target.take() {
+                    next_state.set(target);
+                }
+                fade.phase = FadePhase::In;
+                fade.timer.reset();
+            },
+            _ => fade.phase = FadePhase::Idle,
+        }
+    }
+
+    overlay.into_inner().0 = Color::BLACK.with_alpha(fade.alpha());
+}
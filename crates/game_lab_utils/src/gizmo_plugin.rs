@@ -0,0 +1,249 @@
+use bevy::app::{App, Last, Plugin};
+use bevy::color::palettes::css::{LIME, WHITE};
+use bevy::color::{Alpha, Color};
+use bevy::math::{IVec2, Isometry2d, Rect, Vec2};
+use bevy::prelude::{Camera, Camera2d, Component, GizmoPrimitive2d, Gizmos, GlobalTransform, KeyCode, Query, Rectangle, Res, ResMut, Resource, With};
+use bevy_egui::{egui, EguiContexts};
+use crate::debug_plugin::{DebugKey, Debugger};
+
+// Games add their own drawing to these categories with add_debug_system, e.g. a path from their pathfinding
+pub const GRID_KEY: DebugKey = DebugKey::new("Gizmos/Grid").with_hotkey(KeyCode::F4);
+pub const TILE_INDEX_KEY: DebugKey = DebugKey::new("Gizmos/Tile indices");
+pub const PATHS_KEY: DebugKey = DebugKey::new("Gizmos/Paths");
+pub const COLLIDERS_KEY: DebugKey = DebugKey::new("Gizmos/Colliders");
+pub const LABELS_KEY: DebugKey = DebugKey::new("Gizmos/Labels");
+
+// Past this the grid and tile indices are too dense to read, so they're skipped
+const MAX_GRID_LINES: usize = 400;
+const MAX_TILE_LABELS: usize = 2000;
+
+#[derive(Resource, Clone, Debug)]
+pub struct GizmoSettings {
+    pub tile_size: Vec2,
+    // Corner of tile (0, 0)
+    pub origin: Vec2,
+    // Rows count down the screen, like a level laid out in rows
+    pub rows_down: bool,
+}
+
+impl GizmoSettings {
+    pub fn tile_at(&self, position: Vec2) -> IVec2 {
+        let offset = (position - self.origin) / self.tile_size;
+        let row = if self.rows_down { -offset.y } else { offset.y };
+        IVec2::new(offset.x.floor() as i32, row.floor() as i32)
+    }
+
+    pub fn tile_center(&self, tile: IVec2) -> Vec2 {
+        let row = if self.rows_down { -(tile.y as f32 + 0.5) } else { tile.y as f32 + 0.5 };
+        self.origin + Vec2::new(tile.x as f32 + 0.5, row) * self.tile_size
+    }
+}
+
+// Where the lines between tiles fall from `min` to `max` along one axis
+pub fn grid_lines(min: f32, max: f32, origin: f32, size: f32) -> Vec<f32> {
+    if size <= 0.0 || (max - min) / size > MAX_GRID_LINES as f32 {
+        return vec!();
+    }
+    let first = ((min - origin) / size).ceil() as i32;
+    let last = ((max - origin) / size).floor() as i32;
+    (first..=last).map(|i| origin + i as f32 * size).collect()
+}
+
+// Outline drawn under Gizmos/Colliders, for whatever an entity blocks
+#[derive(Component, Clone, Debug)]
+pub struct DebugCollider {
+    pub size: Vec2,
+    pub offset: Vec2,
+}
+
+impl DebugCollider {
+    pub fn new(size: Vec2) -> Self {
+        Self { size, offset: Vec2::ZERO }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+// Text that follows an entity, shown under Gizmos/Labels
+#[derive(Component, Clone, Debug)]
+pub struct DebugLabel(pub String);
+
+// Labels for this frame only, the same way gizmos are drawn
+#[derive(Resource, Default)]
+pub struct WorldLabels(Vec<(Vec2, String)>);
+
+impl WorldLabels {
+    pub fn add(&mut self, position: Vec2, text: impl Into<String>) {
+        self.0.push((position, text.into()));
+    }
+}
+
+pub fn draw_path(gizmos: &mut Gizmos, points: &[Vec2], color: Color) {
+    gizmos.linestrip_2d(points.iter().copied(), color);
+    for point in points {
+        gizmos.circle_2d(Isometry2d::from_translation(*point), 3.0, color);
+    }
+    if let Some(end) = points.last() {
+        gizmos.primitive_2d(&Rectangle::new(10.0, 10.0), Isometry2d::from_translation(*end), color);
+    }
+}
+
+pub struct GizmoPlugin {
+    settings: GizmoSettings,
+}
+
+impl Default for GizmoPlugin {
+    fn default() -> Self {
+        Self { settings: GizmoSettings { tile_size: Vec2::splat(32.0), origin: Vec2::ZERO, rows_down: false } }
+    }
+}
+
+impl GizmoPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tile_size(mut self, size: f32) -> Self {
+        self.settings.tile_size = Vec2::splat(size);
+        self
+    }
+
+    pub fn with_origin(mut self, origin: Vec2) -> Self {
+        self.settings.origin = origin;
+        self
+    }
+
+    pub fn with_rows_down(mut self) -> Self {
+        self.settings.rows_down = true;
+        self
+    }
+}
+
+impl Plugin for GizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .init_resource::<WorldLabels>()
+            .add_debug_system(draw_grid, GRID_KEY)
+            .add_debug_system(draw_tile_indices, TILE_INDEX_KEY)
+            .add_debug_system(draw_colliders, COLLIDERS_KEY)
+            .add_debug_system(draw_labels, LABELS_KEY)
+            .add_systems(Last, clear_world_labels);
+    }
+}
+
+// The part of the world the main 2d camera can see, along with what turns world positions into screen ones
+fn visible_world(cameras: &Query<(&Camera, &GlobalTransform), With<Camera2d>>) -> Option<(Rect, Camera, GlobalTransform)> {
+    let (camera, transform) = cameras.iter().filter(|(c, _)| c.is_active).min_by_key(|(c, _)| c.order)?;
+    let viewport = camera.logical_viewport_rect()?;
+    let min = camera.viewport_to_world_2d(transform, viewport.min).ok()?;
+    let max = camera.viewport_to_world_2d(transform, viewport.max).ok()?;
+    Some((Rect::from_corners(min, max), camera.clone(), *transform))
+}
+
+fn draw_grid(mut gizmos: Gizmos, settings: Res<GizmoSettings>, cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>) {
+    let Some((view, _, _)) = visible_world(&cameras) else {
+        return;
+    };
+    let color = WHITE.with_alpha(0.2);
+    for x in grid_lines(view.min.x, view.max.x, settings.origin.x, settings.tile_size.x) {
+        gizmos.line_2d(Vec2::new(x, view.min.y), Vec2::new(x, view.max.y), color);
+    }
+    for y in grid_lines(view.min.y, view.max.y, settings.origin.y, settings.tile_size.y) {
+        gizmos.line_2d(Vec2::new(view.min.x, y), Vec2::new(view.max.x, y), color);
+    }
+}
+
+fn draw_colliders(mut gizmos: Gizmos, colliders: Query<(&DebugCollider, &GlobalTransform)>) {
+    for (collider, transform) in colliders.iter() {
+        let center = transform.translation().truncate() + collider.offset;
+        gizmos.primitive_2d(&Rectangle::new(collider.size.x, collider.size.y), Isometry2d::from_translation(center), LIME);
+    }
+}
+
+// Text goes through egui's background layer since gizmos can't draw it
+fn paint_labels<'a>(contexts: &mut EguiContexts, camera: &Camera, transform: &GlobalTransform, labels: impl Iterator<Item = (Vec2, &'a str)>, color: egui::Color32) {
+    let painter = contexts.ctx_mut().layer_painter(egui::LayerId::background());
+    for (position, text) in labels {
+        if let Ok(screen) = camera.world_to_viewport(transform, position.extend(0.0)) {
+            painter.text(egui::pos2(screen.x, screen.y), egui::Align2::CENTER_CENTER, text, egui::FontId::monospace(10.0), color);
+        }
+    }
+}
+
+fn draw_tile_indices(mut contexts: EguiContexts, settings: Res<GizmoSettings>, cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>) {
+    let Some((view, camera, transform)) = visible_world(&cameras) else {
+        return;
+    };
+    let (a, b) = (settings.tile_at(view.min), settings.tile_at(view.max));
+    let (min, max) = (a.min(b), a.max(b));
+    let count = ((max.x - min.x + 1) * (max.y - min.y + 1)) as usize;
+    if count > MAX_TILE_LABELS {
+        return;
+    }
+    let labels: Vec<(Vec2, String)> = (min.y..=max.y)
+        .flat_map(|y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+        .map(|tile| (settings.tile_center(tile), format!("{},{}", tile.x, tile.y)))
+        .collect();
+    let color = egui::Color32::from_white_alpha(120);
+    paint_labels(&mut contexts, &camera, &transform, labels.iter().map(|(p, t)| (*p, t.as_str())), color);
+}
+
+fn draw_labels(
+    mut contexts: EguiContexts,
+    labels: Res<WorldLabels>,
+    entities: Query<(&DebugLabel, &GlobalTransform)>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) {
+    let Some((_, camera, transform)) = visible_world(&cameras) else {
+        return;
+    };
+    paint_labels(&mut contexts, &camera, &transform, labels.0.iter().map(|(p, t)| (*p, t.as_str())), egui::Color32::YELLOW);
+    paint_labels(&mut contexts, &camera, &transform, entities.iter().map(|(l, t)| (t.translation().truncate(), l.0.as_str())), egui::Color32::LIGHT_BLUE);
+}
+
+fn clear_world_labels(mut labels: ResMut<WorldLabels>) {
+    labels.0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles() {
+        struct TestCase {
+            settings: GizmoSettings,
+            position: Vec2,
+            expected_tile: IVec2,
+        }
+
+        let up = GizmoSettings { tile_size: Vec2::splat(32.0), origin: Vec2::ZERO, rows_down: false };
+        // Tile centres on multiples of 32 with rows going down, like game-1's map
+        let down = GizmoSettings { tile_size: Vec2::splat(32.0), origin: Vec2::new(-16.0, 16.0), rows_down: true };
+        let cases = vec!(
+            TestCase { settings: up.clone(), position: Vec2::new(10.0, 10.0), expected_tile: IVec2::new(0, 0) },
+            TestCase { settings: up.clone(), position: Vec2::new(-1.0, 33.0), expected_tile: IVec2::new(-1, 1) },
+            TestCase { settings: down.clone(), position: Vec2::new(0.0, 0.0), expected_tile: IVec2::new(0, 0) },
+            TestCase { settings: down.clone(), position: Vec2::new(64.0, -96.0), expected_tile: IVec2::new(2, 3) },
+            TestCase { settings: down.clone(), position: Vec2::new(-20.0, 20.0), expected_tile: IVec2::new(-1, -1) },
+        );
+
+        for c in cases {
+            let tile = c.settings.tile_at(c.position);
+            assert_eq!(tile, c.expected_tile, "{}", c.position);
+            assert_eq!(c.settings.tile_at(c.settings.tile_center(tile)), tile);
+        }
+
+        assert_eq!(down.tile_center(IVec2::new(2, 3)), Vec2::new(64.0, -96.0));
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(grid_lines(-40.0, 70.0, 0.0, 32.0), vec!(-32.0, 0.0, 32.0, 64.0));
+        assert_eq!(grid_lines(-40.0, 70.0, 16.0, 32.0), vec!(-16.0, 16.0, 48.0));
+        assert_eq!(grid_lines(0.0, 100_000.0, 0.0, 32.0), Vec::<f32>::new());
+    }
+}
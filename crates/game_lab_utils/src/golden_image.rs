@@ -0,0 +1,321 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use bevy::app::{App, PluginsState};
+use bevy::asset::io::AssetSourceBuilder;
+use bevy::asset::{AssetApp, AssetPlugin, AssetServer, Assets, RenderAssetUsages};
+use bevy::color::Color;
+use bevy::image::Image;
+use bevy::log::LogPlugin;
+use bevy::math::{UVec2, Vec2};
+use bevy::prelude::{Camera, Camera2d, ClearColorConfig, DefaultPlugins, ImagePlugin, PluginGroup, ResMut, Resource, Transform, Trigger};
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy::render::renderer::{initialize_renderer, RenderInstance, WgpuWrapper};
+use bevy::render::settings::{Backends, RenderCreation, WgpuSettings};
+use bevy::render::RenderPlugin;
+use bevy::tasks::block_on;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{ExitCondition, WindowPlugin};
+use bevy::winit::WinitPlugin;
+use image::{Rgba, RgbaImage};
+use wgpu::{Instance, InstanceDescriptor, RequestAdapterOptions};
+use crate::debug_plugin::DebugState;
+use crate::internal_asset_plugin::{AssetRoots, AssetSourcesPlugin};
+use crate::loading_plugin::{LoadingProgress, LoadingTracker};
+use crate::test_harness::FRAME_TIME;
+
+// How far a channel can drift before the pixel counts as changed, enough for rounding between renderers
+pub const DEFAULT_TOLERANCE: u8 = 2;
+// Set to write every render over its golden instead of comparing, e.g. GOLDEN_UPDATE=1 cargo test
+pub const UPDATE_VAR: &str = "GOLDEN_UPDATE";
+
+const MISMATCH_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+pub struct ImageDiff {
+    pub mismatched: usize,
+    pub max_delta: u8,
+    // Changed pixels in red over a dimmed copy of the golden
+    pub image: RgbaImage,
+}
+
+pub fn compare_rgba(actual: &RgbaImage, expected: &RgbaImage, tolerance: u8) -> Result<ImageDiff, String> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(format!("Render is {:?} but the golden is {:?}", actual.dimensions(), expected.dimensions()));
+    }
+    let mut diff = ImageDiff { mismatched: 0, max_delta: 0, image: RgbaImage::new(expected.width(), expected.height()) };
+    for ((a, e), d) in actual.pixels().zip(expected.pixels()).zip(diff.image.pixels_mut()) {
+        let delta = a.0.iter().zip(e.0.iter()).map(|(a, e)| a.abs_diff(*e)).max().unwrap_or(0);
+        diff.max_delta = diff.max_delta.max(delta);
+        if delta > tolerance {
+            diff.mismatched += 1;
+            *d = MISMATCH_COLOR;
+        } else {
+            *d = Rgba([e[0] / 3, e[1] / 3, e[2] / 3, 255]);
+        }
+    }
+    Ok(diff)
+}
+
+// <crate>/tests/golden/<name>.png
+pub fn golden_path(manifest_dir: &str, name: &str) -> PathBuf {
+    PathBuf::from(manifest_dir).join("tests").join("golden").join(format!("{name}.png"))
+}
+
+fn sibling(golden: &Path, suffix: &str) -> PathBuf {
+    let stem = golden.file_stem().unwrap_or_default().to_string_lossy();
+    golden.with_file_name(format!("{stem}.{suffix}.png"))
+}
+
+fn save(path: &Path, image: &RgbaImage) {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).unwrap_or_else(|e| panic!("Couldn't create {}: {e}", parent.display()));
+    }
+    image.save(path).unwrap_or_else(|e| panic!("Couldn't write {}: {e}", path.display()));
+}
+
+// Panics if `actual` differs from the golden, leaving <name>.actual.png and <name>.diff.png beside it.
+// A missing golden is written from this render and still fails, so it gets looked at before it's committed
+pub fn assert_golden(golden: &Path, actual: &RgbaImage, tolerance: u8) {
+    let (actual_path, diff_path) = (sibling(golden, "actual"), sibling(golden, "diff"));
+    if std::env::var_os(UPDATE_VAR).is_some() {
+        save(golden, actual);
+        return;
+    }
+    if !golden.exists() {
+        save(golden, actual);
+        panic!("No golden at {}, wrote this render there to check and commit", golden.display());
+    }
+    let expected = image::open(golden)
+        .unwrap_or_else(|e| panic!("Couldn't read {}: {e}", golden.display()))
+        .to_rgba8();
+    let result = compare_rgba(actual, &expected, tolerance);
+    if let Ok(diff) = &result && diff.mismatched == 0 {
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return;
+    }
+    save(&actual_path, actual);
+    match result {
+        Ok(diff) => {
+            save(&diff_path, &diff.image);
+            panic!(
+                "{} pixels differ from {} by up to {} (tolerance {tolerance}), see {}",
+                diff.mismatched,
+                golden.display(),
+                diff.max_delta,
+                diff_path.display(),
+            );
+        }
+        Err(e) => panic!("{e}, see {}", actual_path.display()),
+    }
+}
+
+#[derive(Resource, Default)]
+struct CapturedFrame(Option<Image>);
+
+// Renders a scene offscreen into an image on a software adapter, lavapipe (mesa-vulkan-drivers) or
+// llvmpipe through GL, so a GPU on the machine doesn't change the pixels. Panics if there's neither,
+// so tests using it are marked #[ignore] and run with `cargo test --workspace -- --ignored`
+pub struct GoldenScene {
+    root: PathBuf,
+    game: String,
+    sources: Vec<(String, PathBuf)>,
+    size: UVec2,
+    camera: Vec2,
+}
+
+impl GoldenScene {
+    // Assets come from the crate's tests/fixtures folder, the same as TestApp::with_fixtures
+    pub fn with_fixtures(manifest_dir: &str, game: &str) -> Self {
+        Self {
+            root: PathBuf::from(manifest_dir).join("tests").join("fixtures"),
+            game: game.to_string(),
+            sources: vec!(),
+            size: UVec2::new(256, 256),
+            camera: Vec2::ZERO,
+        }
+    }
+
+    // Extra asset source, e.g. ("assets", asset_root()) so a fixture manifest can name assets://shaders/...
+    pub fn with_source(mut self, id: &str, path: PathBuf) -> Self {
+        self.sources.push((id.to_string(), path));
+        self
+    }
+
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = UVec2::new(width, height);
+        self
+    }
+
+    pub fn with_camera_at(mut self, position: Vec2) -> Self {
+        self.camera = position;
+        self
+    }
+
+    // `setup` adds the plugins that build the scene
+    pub fn render(&self, setup: impl FnOnce(&mut App)) -> RgbaImage {
+        let mut app = App::new();
+        app.add_plugins(AssetSourcesPlugin::new(&self.game).with_root(self.root.clone()));
+        for (id, path) in &self.sources {
+            app.register_asset_source(id.clone(), AssetSourceBuilder::platform_default(&path.to_string_lossy(), None));
+            // So AssetManifestPlugin can check files in it too
+            app.world_mut().resource_mut::<AssetRoots>().sources.insert(id.clone(), path.clone());
+        }
+        let plugins = DefaultPlugins.build()
+            .disable::<WinitPlugin>()
+            .disable::<LogPlugin>()
+            .set(WindowPlugin { primary_window: None, exit_condition: ExitCondition::DontExit, ..Default::default() })
+            .set(AssetPlugin {
+                file_path: self.root.to_string_lossy().to_string(),
+                watch_for_changes_override: Some(false),
+                ..Default::default()
+            })
+            .set(ImagePlugin::default_nearest())
+            .set(RenderPlugin {
+                render_creation: software_renderer(),
+                // Otherwise sprites are skipped until their pipeline compiles, which differs from run to run
+                synchronous_pipeline_compilation: true,
+            });
+        app.add_plugins(plugins);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+            .init_resource::<DebugState>()
+            .init_resource::<LoadingTracker>()
+            .init_resource::<CapturedFrame>();
+        setup(&mut app);
+
+        // What App::run would do before the first frame
+        while app.plugins_state() == PluginsState::Adding {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        app.finish();
+        app.cleanup();
+
+        let target = app.world_mut().resource_mut::<Assets<Image>>().add(render_target(self.size));
+        app.world_mut().spawn((
+            Camera2d,
+            Camera { target: RenderTarget::Image(target.clone()), clear_color: ClearColorConfig::Custom(Color::BLACK), ..Default::default() },
+            Transform::from_translation(self.camera.extend(0.0)),
+        ));
+
+        if !step_until(&mut app, 500, loaded) {
+            let world = app.world();
+            let progress = LoadingProgress::from_statuses(&world.resource::<LoadingTracker>().statuses(world.resource::<AssetServer>()));
+            panic!("Scene assets didn't load: {:?}", progress);
+        }
+        // Let anything waiting on the loaded assets spawn its sprites
+        for _ in 0..5 {
+            app.update();
+        }
+
+        app.world_mut().spawn(Screenshot::image(target))
+            .observe(|trigger: Trigger<ScreenshotCaptured>, mut captured: ResMut<CapturedFrame>| {
+                captured.0 = Some(trigger.event().0.clone());
+            });
+        if !step_until(&mut app, 100, |app| app.world().resource::<CapturedFrame>().0.is_some()) {
+            panic!("The screenshot never came back from the render world");
+        }
+        let frame = app.world_mut().resource_mut::<CapturedFrame>().0.take().unwrap_or_default();
+        frame.try_into_dynamic().expect("Screenshot in an unexpected format").to_rgba8()
+    }
+}
+
+// Bevy picks the best adapter it finds, this only takes a CPU one
+fn software_renderer() -> RenderCreation {
+    let instance = Instance::new(InstanceDescriptor { backends: Backends::VULKAN | Backends::GL, ..Default::default() });
+    let options = RequestAdapterOptions { force_fallback_adapter: true, ..Default::default() };
+    if block_on(instance.request_adapter(&options)).is_none() {
+        panic!("No software wgpu adapter to render goldens with, install mesa-vulkan-drivers or a GL driver with llvmpipe");
+    }
+    let (device, queue, info, adapter) = block_on(initialize_renderer(&instance, &WgpuSettings::default(), &options));
+    RenderCreation::manual(device, queue, info, adapter, RenderInstance(Arc::new(WgpuWrapper::new(instance))))
+}
+
+fn render_target(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+fn loaded(app: &App) -> bool {
+    let world = app.world();
+    let progress = LoadingProgress::from_statuses(&world.resource::<LoadingTracker>().statuses(world.resource::<AssetServer>()));
+    progress.failed.is_empty() && progress.loaded == progress.total
+}
+
+fn step_until(app: &mut App, max_frames: usize, done: impl Fn(&App) -> bool) -> bool {
+    for _ in 0..max_frames {
+        app.update();
+        if done(app) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::catch_unwind;
+    use super::*;
+
+    fn solid(color: [u8; 4]) -> RgbaImage {
+        RgbaImage::from_pixel(4, 4, Rgba(color))
+    }
+
+    #[test]
+    fn compare() {
+        struct TestCase {
+            actual: RgbaImage,
+            tolerance: u8,
+            expected_mismatched: usize,
+            expected_max_delta: u8,
+        }
+
+        let golden = solid([100, 100, 100, 255]);
+        let mut one_off = golden.clone();
+        one_off.put_pixel(1, 2, Rgba([100, 140, 100, 255]));
+        let cases = vec!(
+            TestCase { actual: golden.clone(), tolerance: 0, expected_mismatched: 0, expected_max_delta: 0 },
+            TestCase { actual: solid([102, 99, 100, 255]), tolerance: 2, expected_mismatched: 0, expected_max_delta: 2 },
+            TestCase { actual: solid([103, 100, 100, 255]), tolerance: 2, expected_mismatched: 16, expected_max_delta: 3 },
+            TestCase { actual: one_off, tolerance: 2, expected_mismatched: 1, expected_max_delta: 40 },
+        );
+
+        for c in cases {
+            let diff = compare_rgba(&c.actual, &golden, c.tolerance).unwrap();
+            assert_eq!(diff.mismatched, c.expected_mismatched);
+            assert_eq!(diff.max_delta, c.expected_max_delta);
+            let marked = diff.image.pixels().filter(|p| **p == MISMATCH_COLOR).count();
+            assert_eq!(marked, c.expected_mismatched);
+        }
+
+        assert!(compare_rgba(&RgbaImage::new(2, 2), &golden, 0).is_err());
+    }
+
+    #[test]
+    fn writes_actual_and_diff() {
+        let dir = std::env::temp_dir().join(format!("golden_test_{}", std::process::id()));
+        let golden = dir.join("scene.png");
+        let expected = solid([10, 20, 30, 255]);
+        save(&golden, &expected);
+
+        assert_golden(&golden, &expected, 0);
+        let changed = solid([200, 20, 30, 255]);
+        assert!(catch_unwind(|| assert_golden(&golden, &changed, 0)).is_err());
+        assert!(dir.join("scene.actual.png").exists());
+        assert_eq!(image::open(dir.join("scene.diff.png")).unwrap().to_rgba8().get_pixel(0, 0), &MISMATCH_COLOR);
+
+        // Passing again clears them out
+        assert_golden(&golden, &expected, 0);
+        assert!(!dir.join("scene.diff.png").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
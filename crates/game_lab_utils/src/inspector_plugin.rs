@@ -0,0 +1,367 @@
+use std::any::TypeId;
+use bevy::app::{App, Plugin};
+use bevy::asset::Assets;
+use bevy::color::palettes::css::{GOLD, ORANGE_RED};
+use bevy::ecs::reflect::{AppTypeRegistry, ReflectComponent, ReflectResource};
+use bevy::image::Image;
+use bevy::math::{Isometry2d, Vec2};
+use bevy::prelude::{DetectChangesMut, Entity, GizmoPrimitive2d, Gizmos, GlobalTransform, KeyCode, Mut, Name, Query, Rectangle, Res, Resource, Sprite, TextureAtlasLayout, With, World};
+use bevy::reflect::{PartialReflect, ReflectMut, ReflectRef, TypeRegistry};
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContext};
+use crate::debug_plugin::{debug_window, DebugKey, DebugState, Debugger};
+
+// Any component or resource shows up here once it derives Reflect, has #[reflect(Component)]
+// or #[reflect(Resource)] and is registered with app.register_type
+pub const INSPECTOR_KEY: DebugKey = DebugKey::new("Tools/Inspector").with_hotkey(KeyCode::F1);
+
+#[derive(Resource, Default)]
+pub struct InspectorState {
+    pub selected: Option<Entity>,
+    pub filter: String,
+}
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InspectorState>()
+            .add_debug_system((inspector_window, highlight_selected), INSPECTOR_KEY);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntityRow {
+    pub entity: Entity,
+    pub label: String,
+    pub components: Vec<String>,
+}
+
+impl EntityRow {
+    pub fn matches(&self, filter: &str) -> bool {
+        matches_filter(filter, &self.label) || self.components.iter().any(|c| matches_filter(filter, c))
+    }
+}
+
+pub fn matches_filter(filter: &str, text: &str) -> bool {
+    let filter = filter.trim();
+    filter.is_empty() || text.to_lowercase().contains(&filter.to_lowercase())
+}
+
+// "bevy_time::time::Time<bevy_time::real::Real>" -> "Time<Real>"
+pub fn short_name(name: &str) -> String {
+    let last_segment = |path: &str| path.rsplit("::").next().unwrap_or(path).to_string();
+    let mut short = String::new();
+    let mut start = 0;
+    for (i, c) in name.char_indices() {
+        if matches!(c, '<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | ';' | '&') {
+            short.push_str(&last_segment(&name[start..i]));
+            short.push(c);
+            start = i + 1;
+        }
+    }
+    short.push_str(&last_segment(&name[start..]));
+    short
+}
+
+// Entities read by their Name, otherwise by the first component that isn't one of Bevy's own
+pub fn entity_rows(world: &World) -> Vec<EntityRow> {
+    let mut rows: Vec<EntityRow> = world.iter_entities().map(|entity| {
+        let full_names: Vec<&str> = entity.archetype().components()
+            .filter_map(|id| world.components().get_info(id))
+            .map(|info| info.name())
+            .collect();
+        let components: Vec<String> = full_names.iter().map(|name| short_name(name)).collect();
+        let kind = full_names.iter()
+            .position(|name| !name.starts_with("bevy"))
+            .map(|i| components[i].clone());
+        let label = match (entity.get::<Name>(), kind) {
+            (Some(name), _) => format!("{} {}", name, entity.id()),
+            (None, Some(kind)) => format!("{} {}", kind, entity.id()),
+            (None, None) => entity.id().to_string(),
+        };
+        EntityRow { entity: entity.id(), label, components }
+    }).collect();
+    rows.sort_by_key(|row| row.entity);
+    rows
+}
+
+fn inspector_window(world: &mut World) {
+    let Ok(ctx) = world.query_filtered::<&EguiContext, With<PrimaryWindow>>().get_single(world).map(|c| c.get().clone()) else {
+        return;
+    };
+    let window = debug_window("Inspector", world.resource::<DebugState>());
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    world.resource_scope(|world, mut state: Mut<InspectorState>| {
+        let rows = entity_rows(world);
+        if state.selected.is_some_and(|e| world.get_entity(e).is_err()) {
+            state.selected = None;
+        }
+
+        window.show(&ctx, |ui| {
+            ui.add(egui::TextEdit::singleline(&mut state.filter).hint_text("Filter by name or component"));
+            let visible: Vec<&EntityRow> = rows.iter().filter(|row| row.matches(&state.filter)).collect();
+            ui.label(format!("{} of {} entities", visible.len(), rows.len()));
+
+            egui::ScrollArea::vertical().id_salt("inspector_entities").max_height(200.0).show(ui, |ui| {
+                for row in visible {
+                    let selected = state.selected == Some(row.entity);
+                    if ui.selectable_label(selected, &row.label).clicked() {
+                        state.selected = (!selected).then_some(row.entity);
+                    }
+                }
+            });
+
+            if let Some(entity) = state.selected {
+                ui.separator();
+                egui::ScrollArea::vertical().id_salt("inspector_components").max_height(300.0).show(ui, |ui| {
+                    components_ui(ui, world, &registry, entity);
+                });
+            }
+
+            ui.separator();
+            egui::CollapsingHeader::new("Resources").show(ui, |ui| {
+                resources_ui(ui, world, &registry, &state.filter);
+            });
+        });
+    });
+}
+
+fn components_ui(ui: &mut egui::Ui, world: &mut World, registry: &TypeRegistry, entity: Entity) {
+    let types: Vec<(String, Option<TypeId>)> = {
+        let entity = world.entity(entity);
+        entity.archetype().components()
+            .filter_map(|id| world.components().get_info(id))
+            .map(|info| (short_name(info.name()), info.type_id()))
+            .collect()
+    };
+
+    let mut not_reflected = vec!();
+    for (name, type_id) in types {
+        let Some(reflect_component) = type_id.and_then(|id| registry.get_type_data::<ReflectComponent>(id)) else {
+            not_reflected.push(name);
+            continue;
+        };
+        let mut entity_mut = world.entity_mut(entity);
+        let Some(mut value) = reflect_component.reflect_mut(&mut entity_mut) else {
+            continue;
+        };
+        // Only an edit counts as a change, otherwise every frame would trigger change detection
+        let changed = egui::CollapsingHeader::new(&name).id_salt((entity, &name)).show(ui, |ui| {
+            reflect_ui(ui, value.bypass_change_detection().as_partial_reflect_mut(), egui::Id::new((entity, &name)))
+        }).body_returned.unwrap_or(false);
+        if changed {
+            value.set_changed();
+        }
+    }
+
+    if !not_reflected.is_empty() {
+        ui.weak(format!("Not reflected: {}", not_reflected.join(", ")));
+    }
+}
+
+fn resources_ui(ui: &mut egui::Ui, world: &mut World, registry: &TypeRegistry, filter: &str) {
+    let mut resources: Vec<(&str, &ReflectResource)> = registry.iter()
+        .filter_map(|registration| Some((registration.type_info().type_path_table().short_path(), registration.data::<ReflectResource>()?)))
+        .filter(|(name, _)| matches_filter(filter, name))
+        .collect();
+    resources.sort_by_key(|(name, _)| *name);
+
+    for (name, reflect_resource) in resources {
+        let Some(mut value) = reflect_resource.reflect_mut(world) else {
+            continue;
+        };
+        let changed = egui::CollapsingHeader::new(name).id_salt(("resource", name)).show(ui, |ui| {
+            reflect_ui(ui, value.bypass_change_detection().as_partial_reflect_mut(), egui::Id::new(("resource", name)))
+        }).body_returned.unwrap_or(false);
+        if changed {
+            value.set_changed();
+        }
+    }
+}
+
+// Draws an editor for any reflected value and returns whether it was edited. Maps, sets and
+// anything opaque that isn't a number, bool or string are shown read-only
+pub fn reflect_ui(ui: &mut egui::Ui, value: &mut dyn PartialReflect, id: egui::Id) -> bool {
+    let mut changed = false;
+    match value.reflect_mut() {
+        ReflectMut::Struct(s) => {
+            for i in 0..s.field_len() {
+                let name = s.name_at(i).unwrap_or_default().to_string();
+                if let Some(field) = s.field_at_mut(i) {
+                    changed |= field_ui(ui, &name, field, id.with(i));
+                }
+            }
+        },
+        ReflectMut::TupleStruct(s) => {
+            for i in 0..s.field_len() {
+                if let Some(field) = s.field_mut(i) {
+                    changed |= field_ui(ui, &i.to_string(), field, id.with(i));
+                }
+            }
+        },
+        ReflectMut::Tuple(t) => {
+            for i in 0..t.field_len() {
+                if let Some(field) = t.field_mut(i) {
+                    changed |= field_ui(ui, &i.to_string(), field, id.with(i));
+                }
+            }
+        },
+        ReflectMut::List(l) => {
+            for i in 0..l.len() {
+                if let Some(item) = l.get_mut(i) {
+                    changed |= field_ui(ui, &format!("[{}]", i), item, id.with(i));
+                }
+            }
+        },
+        ReflectMut::Array(a) => {
+            for i in 0..a.len() {
+                if let Some(item) = a.get_mut(i) {
+                    changed |= field_ui(ui, &format!("[{}]", i), item, id.with(i));
+                }
+            }
+        },
+        ReflectMut::Enum(e) => {
+            ui.label(e.variant_name().to_string());
+            for i in 0..e.field_len() {
+                let name = e.name_at(i).map_or_else(|| i.to_string(), str::to_string);
+                if let Some(field) = e.field_at_mut(i) {
+                    changed |= field_ui(ui, &name, field, id.with(i));
+                }
+            }
+        },
+        ReflectMut::Opaque(v) => changed |= opaque_ui(ui, v),
+        _ => {
+            ui.label(format!("{:?}", value));
+        },
+    }
+    changed
+}
+
+// Plain values and small vectors sit on one line, anything bigger gets its own collapsing section
+fn field_ui(ui: &mut egui::Ui, name: &str, value: &mut dyn PartialReflect, id: egui::Id) -> bool {
+    if is_inline(value) {
+        return ui.horizontal(|ui| {
+            ui.label(name);
+            reflect_ui(ui, value, id)
+        }).inner;
+    }
+    egui::CollapsingHeader::new(name).id_salt(id).show(ui, |ui| reflect_ui(ui, value, id)).body_returned.unwrap_or(false)
+}
+
+fn is_inline(value: &dyn PartialReflect) -> bool {
+    let opaque = |v: &dyn PartialReflect| matches!(v.reflect_ref(), ReflectRef::Opaque(_));
+    match value.reflect_ref() {
+        ReflectRef::Opaque(_) => true,
+        ReflectRef::Enum(e) => e.field_len() == 0,
+        ReflectRef::Struct(s) => s.field_len() <= 4 && s.iter_fields().all(opaque),
+        ReflectRef::TupleStruct(s) => s.field_len() <= 4 && s.iter_fields().all(opaque),
+        ReflectRef::Tuple(t) => t.field_len() <= 4 && t.iter_fields().all(opaque),
+        _ => false,
+    }
+}
+
+fn opaque_ui(ui: &mut egui::Ui, value: &mut dyn PartialReflect) -> bool {
+    macro_rules! drag_value {
+        ($($t:ty),*) => {
+            $(if let Some(v) = value.try_downcast_mut::<$t>() {
+                return ui.add(egui::DragValue::new(v).speed(0.1)).changed();
+            })*
+        };
+    }
+    drag_value!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+    if let Some(v) = value.try_downcast_mut::<bool>() {
+        return ui.checkbox(v, "").changed();
+    }
+    if let Some(v) = value.try_downcast_mut::<String>() {
+        return ui.text_edit_singleline(v).changed();
+    }
+    ui.label(format!("{:?}", value));
+    false
+}
+
+// Outlines the selected entity, sized to its sprite when it has one
+fn highlight_selected(
+    mut gizmos: Gizmos,
+    state: Res<InspectorState>,
+    query: Query<(&GlobalTransform, Option<&Sprite>)>,
+    images: Res<Assets<Image>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+) {
+    let Some((transform, sprite)) = state.selected.and_then(|e| query.get(e).ok()) else {
+        return;
+    };
+    let translation = transform.translation().truncate();
+    if let Some(size) = sprite.and_then(|s| sprite_size(s, &images, &layouts)) {
+        let size = size * transform.scale().truncate();
+        gizmos.primitive_2d(&Rectangle::new(size.x, size.y), Isometry2d::from_translation(translation), GOLD);
+    }
+    gizmos.primitive_2d(&Rectangle::new(4.0, 4.0), Isometry2d::from_translation(translation), ORANGE_RED);
+}
+
+fn sprite_size(sprite: &Sprite, images: &Assets<Image>, layouts: &Assets<TextureAtlasLayout>) -> Option<Vec2> {
+    if let Some(size) = sprite.custom_size {
+        return Some(size);
+    }
+    if let Some(atlas) = &sprite.texture_atlas {
+        return layouts.get(&atlas.layout)?.textures.get(atlas.index).map(|rect| rect.size().as_vec2());
+    }
+    images.get(&sprite.image).map(|image| image.size_f32())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{Component, Transform};
+    use super::*;
+
+    #[derive(Component)]
+    struct Crop;
+
+    #[test]
+    fn short_names() {
+        let cases = vec!(
+            ("bevy_sprite::sprite::Sprite", "Sprite"),
+            ("bevy_time::time::Time<bevy_time::real::Real>", "Time<Real>"),
+            ("game_lab_utils::y_sort_plugin::YSort", "YSort"),
+            ("core::option::Option<(u32, alloc::string::String)>", "Option<(u32, String)>"),
+            ("Player", "Player"),
+        );
+
+        for (name, expected) in cases {
+            assert_eq!(short_name(name), expected);
+        }
+    }
+
+    #[test]
+    fn entity_rows_and_filter() {
+        let mut world = World::new();
+        let player = world.spawn((Name::new("Player"), Transform::default())).id();
+        let crop = world.spawn((Crop, Transform::default())).id();
+        let empty = world.spawn_empty().id();
+
+        let rows = entity_rows(&world);
+        let labels: Vec<String> = rows.iter().map(|row| row.label.clone()).collect();
+        assert_eq!(labels, vec!(format!("Player {}", player), format!("Crop {}", crop), empty.to_string()));
+
+        struct TestCase {
+            filter: &'static str,
+            expected: Vec<Entity>,
+        }
+
+        let cases = vec!(
+            TestCase { filter: "", expected: vec!(player, crop, empty) },
+            TestCase { filter: "  player ", expected: vec!(player) },
+            TestCase { filter: "transform", expected: vec!(player, crop) },
+            TestCase { filter: "crop", expected: vec!(crop) },
+            TestCase { filter: "tree", expected: vec!() },
+        );
+
+        for c in cases {
+            let matched: Vec<Entity> = rows.iter().filter(|row| row.matches(c.filter)).map(|row| row.entity).collect();
+            assert_eq!(matched, c.expected, "{}", c.filter);
+        }
+    }
+}
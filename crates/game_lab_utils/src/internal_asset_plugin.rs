@@ -1,13 +1,241 @@
-use bevy::asset::AssetPlugin;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(not(feature = "embedded_assets"))]
+use std::{env, fs};
+use bevy::app::{App, Plugin};
+#[cfg(not(feature = "embedded_assets"))]
+use bevy::asset::io::{file::FileAssetReader, AssetSourceBuilder};
+use bevy::asset::{AssetApp, AssetPlugin};
+use bevy::prelude::Resource;
+
+#[cfg(feature = "embedded_assets")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+}
+
+// Points straight at an assets folder, skipping the search
+pub const ASSET_ROOT_ENV: &str = "GAME_LAB_ASSET_ROOT";
+const ASSET_FOLDER: &str = "assets";
+
+// Art packs shared by every game, e.g. internal://sprout-lands/...
+pub const INTERNAL_SOURCE: &str = "internal";
+// A game's own data folder, e.g. game://items.json
+pub const GAME_SOURCE: &str = "game";
+
+// Override first, then up from the crate being run (cargo run and cargo test), then up from the
+// executable, which covers both target/debug and a packaged build with assets beside it
+pub fn find_asset_root(
+    override_root: Option<PathBuf>,
+    manifest_dir: Option<PathBuf>,
+    exe: Option<PathBuf>,
+    is_dir: impl Fn(&Path) -> bool,
+) -> Option<PathBuf> {
+    if override_root.is_some() {
+        return override_root;
+    }
+    let search = |start: &Path| start.ancestors()
+        .map(|dir| dir.join(ASSET_FOLDER))
+        .find(|dir| is_dir(dir));
+
+    manifest_dir.as_deref().and_then(search)
+        .or_else(|| exe.as_deref().and_then(Path::parent).and_then(search))
+}
+
+// Embedded assets are keyed by their path inside the assets folder, so the root is empty
+#[cfg(feature = "embedded_assets")]
+pub fn asset_root() -> PathBuf {
+    PathBuf::new()
+}
+
+#[cfg(not(feature = "embedded_assets"))]
+pub fn asset_root() -> PathBuf {
+    find_asset_root(
+        env::var_os(ASSET_ROOT_ENV).map(PathBuf::from),
+        env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from),
+        env::current_exe().ok(),
+        Path::is_dir,
+    ).unwrap_or_else(|| FileAssetReader::get_base_path().join(ASSET_FOLDER))
+}
+
+// Where each asset source reads from on disk, so paths can be checked before they're loaded
+#[derive(Resource, Clone, Debug)]
+pub struct AssetRoots {
+    pub root: PathBuf,
+    pub sources: HashMap<String, PathBuf>,
+}
+
+impl AssetRoots {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root, sources: HashMap::new() }
+    }
+
+    pub fn for_game(root: PathBuf, game: &str) -> Self {
+        let mut roots = Self::new(root);
+        roots.sources.insert(INTERNAL_SOURCE.to_string(), roots.root.join("internal"));
+        roots.sources.insert(GAME_SOURCE.to_string(), roots.root.join("data").join(game));
+        roots
+    }
+
+    // Unknown sources fall back to the root so the missing file still shows up in reports
+    pub fn resolve(&self, path: &str) -> PathBuf {
+        match path.split_once("://") {
+            Some((source, rest)) => self.sources.get(source).unwrap_or(&self.root).join(rest),
+            None => self.root.join(path),
+        }
+    }
+
+    #[cfg(feature = "embedded_assets")]
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let path = self.resolve(path).to_string_lossy().replace('\\', "/");
+        embedded::EMBEDDED_ASSETS.iter()
+            .find(|(p, _)| *p == path)
+            .map(|(_, bytes)| bytes.to_vec())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} is not embedded", path)))
+    }
+
+    #[cfg(not(feature = "embedded_assets"))]
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.resolve(path))
+    }
+
+    #[cfg(feature = "embedded_assets")]
+    pub fn write(&self, _path: &str, _bytes: &[u8]) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "assets are embedded in the binary"))
+    }
+
+    // For tools that edit data files in place, e.g. tuning written back from the debug window.
+    // Folders that aren't there yet are made
+    #[cfg(not(feature = "embedded_assets"))]
+    pub fn write(&self, path: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.resolve(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, bytes)
+    }
+
+    #[cfg(feature = "embedded_assets")]
+    pub fn exists(&self, path: &str) -> bool {
+        self.read(path).is_ok()
+    }
+
+    #[cfg(not(feature = "embedded_assets"))]
+    pub fn exists(&self, path: &str) -> bool {
+        self.resolve(path).is_file()
+    }
+}
 
-pub const INTERNAL_ASSET_FILE_PATH: &str = "../../assets";
 pub struct InternalAssetPlugin;
 impl InternalAssetPlugin {
     pub fn new() -> AssetPlugin {
         AssetPlugin{
-            file_path: INTERNAL_ASSET_FILE_PATH.to_string(),
+            file_path: asset_root().to_string_lossy().to_string(),
+            // Nothing on disk to watch once the assets are in the binary
+            watch_for_changes_override: cfg!(feature = "embedded_assets").then_some(false),
             ..Default::default()
         }
     }
 }
 
+// Has to be added before DefaultPlugins, Bevy builds its asset sources along with the AssetPlugin.
+// With `embedded_assets` every source, the default one included, reads from the binary instead
+pub struct AssetSourcesPlugin {
+    game: String,
+    root: Option<PathBuf>,
+}
+
+impl AssetSourcesPlugin {
+    pub fn new(game: &str) -> Self {
+        Self { game: game.to_string(), root: None }
+    }
+
+    // Reads from somewhere other than the assets folder, e.g. test fixtures. Ignored with `embedded_assets`
+    pub fn with_root(mut self, root: PathBuf) -> Self {
+        self.root = Some(root);
+        self
+    }
+}
+
+impl Plugin for AssetSourcesPlugin {
+    #[cfg(not(feature = "embedded_assets"))]
+    fn build(&self, app: &mut App) {
+        let roots = AssetRoots::for_game(self.root.clone().unwrap_or_else(asset_root), &self.game);
+        for id in [INTERNAL_SOURCE, GAME_SOURCE] {
+            let path = roots.sources[id].to_string_lossy().to_string();
+            app.register_asset_source(id, AssetSourceBuilder::platform_default(&path, None));
+        }
+        app.insert_resource(roots);
+    }
+
+    #[cfg(feature = "embedded_assets")]
+    fn build(&self, app: &mut App) {
+        use bevy::asset::io::memory::{Dir, MemoryAssetReader};
+        use bevy::asset::io::{AssetSource, AssetSourceId};
+
+        let files = Dir::default();
+        for (path, bytes) in embedded::EMBEDDED_ASSETS {
+            files.insert_asset(Path::new(path), *bytes);
+        }
+        let roots = AssetRoots::for_game(asset_root(), &self.game);
+        let reader = |dir: Dir| AssetSource::build().with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() }));
+
+        app.register_asset_source(AssetSourceId::Default, reader(files.clone()));
+        for id in [INTERNAL_SOURCE, GAME_SOURCE] {
+            let dir = files.get_dir(&roots.sources[id]).unwrap_or_default();
+            app.register_asset_source(id, reader(dir));
+        }
+        app.insert_resource(roots);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_root() {
+        struct TestCase {
+            override_root: Option<&'static str>,
+            manifest_dir: Option<&'static str>,
+            exe: Option<&'static str>,
+            expected: Option<&'static str>,
+        }
+
+        let dirs = ["/lab/assets", "/dist/assets"];
+        let cases = vec!(
+            TestCase { override_root: Some("/elsewhere"), manifest_dir: Some("/lab/projects/game-1"), exe: None, expected: Some("/elsewhere") },
+            TestCase { override_root: None, manifest_dir: Some("/lab/projects/game-1"), exe: None, expected: Some("/lab/assets") },
+            TestCase { override_root: None, manifest_dir: Some("/lab"), exe: None, expected: Some("/lab/assets") },
+            TestCase { override_root: None, manifest_dir: None, exe: Some("/lab/target/debug/game-1"), expected: Some("/lab/assets") },
+            TestCase { override_root: None, manifest_dir: None, exe: Some("/dist/game-1"), expected: Some("/dist/assets") },
+            TestCase { override_root: None, manifest_dir: Some("/tmp/other"), exe: Some("/dist/game-1"), expected: Some("/dist/assets") },
+            TestCase { override_root: None, manifest_dir: None, exe: Some("/usr/bin/game-1"), expected: None },
+        );
+
+        for c in cases {
+            let root = find_asset_root(
+                c.override_root.map(PathBuf::from),
+                c.manifest_dir.map(PathBuf::from),
+                c.exe.map(PathBuf::from),
+                |p| dirs.iter().any(|d| Path::new(d) == p),
+            );
+            assert_eq!(root, c.expected.map(PathBuf::from));
+        }
+    }
+
+    #[test]
+    fn resolve() {
+        let roots = AssetRoots::for_game(PathBuf::from("/lab/assets"), "game1");
+        let cases = vec!(
+            ("shaders/shadow.wgsl", "/lab/assets/shaders/shadow.wgsl"),
+            ("internal://fonts/debug/debug.ttf", "/lab/assets/internal/fonts/debug/debug.ttf"),
+            ("game://items.json", "/lab/assets/data/game1/items.json"),
+            ("unknown://items.json", "/lab/assets/items.json"),
+        );
+
+        for (path, expected) in cases {
+            assert_eq!(roots.resolve(path), PathBuf::from(expected));
+        }
+    }
+}
@@ -0,0 +1,550 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::{Asset, AssetEvent, AssetServer, Assets, Handle};
+use bevy::image::Image;
+use bevy::math::UVec2;
+use bevy::prelude::{Commands, Component, Entity, EventReader, In, Local, Name, Query, Res, ResMut, Resource, Single, TextureAtlas, TextureAtlasLayout, TypePath, With};
+use bevy_common_assets::json::JsonAssetPlugin;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+use crate::console_plugin::{ConsoleArgs, ConsoleResult};
+use crate::debug_plugin::{debug_window, DebugKey, DebugState, Debugger};
+use crate::loading_plugin::LoadingTracker;
+
+const INVENTORY_KEY: DebugKey = DebugKey::new("Tools/Inventory");
+// Loading label for the icon atlases named in the item definitions
+const ICON_ATLASES: &str = "item icon atlases";
+
+#[derive(Asset, TypePath, Deserialize)]
+pub struct ItemDefinitions {
+    pub atlases: HashMap<String, IconAtlas>,
+    pub items: Vec<ItemDefinition>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct IconAtlas {
+    pub image: String,
+    pub tile_size: u32,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ItemIcon {
+    pub atlas: String,
+    pub index: usize,
+}
+
+#[derive(Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum ItemCategory {
+    Tool,
+    Seed,
+    Crop,
+    Currency,
+    Material,
+}
+
+impl Display for ItemCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemCategory::Tool => write!(f, "Tool"),
+            ItemCategory::Seed => write!(f, "Seed"),
+            ItemCategory::Crop => write!(f, "Crop"),
+            ItemCategory::Currency => write!(f, "Currency"),
+            ItemCategory::Material => write!(f, "Material"),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ItemDefinition {
+    pub id: String,
+    pub name: String,
+    pub icon: ItemIcon,
+    pub stack_size: u32,
+    pub category: ItemCategory,
+    // Game specific extras, e.g. which crop a seed grows into
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+}
+
+pub struct LoadedIconAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
+#[derive(Resource, Default)]
+pub struct ItemRegistry {
+    pub items: HashMap<String, ItemDefinition>,
+    pub atlases: HashMap<String, LoadedIconAtlas>,
+}
+
+impl ItemRegistry {
+    pub fn get(&self, id: &str) -> Option<&ItemDefinition> {
+        self.items.get(id)
+    }
+
+    // Unknown items don't stack so they can't swallow anything else
+    pub fn stack_size(&self, id: &str) -> u32 {
+        self.items.get(id).map(|i| i.stack_size.max(1)).unwrap_or(1)
+    }
+
+    pub fn icon(&self, id: &str) -> Option<(Handle<Image>, TextureAtlas)> {
+        let item = self.items.get(id)?;
+        let atlas = self.atlases.get(&item.icon.atlas)?;
+        Some((atlas.image.clone(), TextureAtlas { layout: atlas.layout.clone(), index: item.icon.index }))
+    }
+
+    pub fn sorted_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.items.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    // An exact id, or part of exactly one id, so "give seed" works while there's only one kind of seed
+    pub fn find(&self, query: &str) -> Result<&str, String> {
+        if let Some((id, _)) = self.items.get_key_value(query) {
+            return Ok(id);
+        }
+        let matches: Vec<String> = self.sorted_ids().into_iter().filter(|id| id.contains(query)).collect();
+        match matches.as_slice() {
+            [id] => Ok(self.items.get_key_value(id).map(|(id, _)| id.as_str()).unwrap_or_default()),
+            [] => Err(format!("No item matches \"{}\"", query)),
+            _ => Err(format!("\"{}\" could be {}", query, matches.join(", "))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct ItemStack {
+    pub item: String,
+    pub count: u32,
+}
+
+impl ItemStack {
+    pub fn new(item: &str, count: u32) -> Self {
+        Self { item: item.to_string(), count }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Self { slots: vec![None; size] }
+    }
+
+    // Fills the first slots in order, used for starting kit before the registry has loaded
+    pub fn with_items(size: usize, items: Vec<ItemStack>) -> Self {
+        let mut inventory = Self::new(size);
+        for (slot, stack) in inventory.slots.iter_mut().zip(items) {
+            *slot = Some(stack);
+        }
+        inventory
+    }
+
+    pub fn count(&self, item: &str) -> u32 {
+        self.slots.iter().flatten().filter(|s| s.item == item).map(|s| s.count).sum()
+    }
+
+    // Tops up existing stacks first, then empty slots. Returns what didn't fit
+    pub fn add(&mut self, item: &str, mut count: u32, registry: &ItemRegistry) -> u32 {
+        let stack_size = registry.stack_size(item);
+
+        for stack in self.slots.iter_mut().flatten().filter(|s| s.item == item) {
+            let moved = count.min(stack_size.saturating_sub(stack.count));
+            stack.count += moved;
+            count -= moved;
+        }
+
+        for slot in self.slots.iter_mut().filter(|s| s.is_none()) {
+            if count == 0 {
+                break;
+            }
+            let moved = count.min(stack_size);
+            *slot = Some(ItemStack::new(item, moved));
+            count -= moved;
+        }
+        count
+    }
+
+    // Takes from the last stacks first so the front of the hotbar stays put. Returns how many were removed
+    pub fn remove(&mut self, item: &str, mut count: u32) -> u32 {
+        let requested = count;
+        for slot in self.slots.iter_mut().rev() {
+            if count == 0 {
+                break;
+            }
+            let Some(stack) = slot else {
+                continue;
+            };
+            if stack.item != item {
+                continue;
+            }
+            let taken = count.min(stack.count);
+            stack.count -= taken;
+            count -= taken;
+            if stack.count == 0 {
+                *slot = None;
+            }
+        }
+        requested - count
+    }
+
+    pub fn remove_from_slot(&mut self, slot: usize, count: u32) -> Option<ItemStack> {
+        let stack = self.slots.get_mut(slot)?.as_mut()?;
+        let taken = count.min(stack.count);
+        stack.count -= taken;
+        let removed = ItemStack::new(&stack.item, taken);
+        if stack.count == 0 {
+            self.slots[slot] = None;
+        }
+        Some(removed)
+    }
+
+    // Drops slot `from` onto slot `to`. Same items merge up to the stack size, anything else swaps
+    pub fn merge(&mut self, from: usize, to: usize, registry: &ItemRegistry) {
+        if from == to || from >= self.slots.len() || to >= self.slots.len() {
+            return;
+        }
+
+        match (self.slots[from].clone(), self.slots[to].clone()) {
+            (Some(source), Some(mut target)) if source.item == target.item => {
+                let moved = source.count.min(registry.stack_size(&target.item).saturating_sub(target.count));
+                target.count += moved;
+                self.slots[to] = Some(target);
+                self.slots[from] = if source.count > moved {
+                    Some(ItemStack::new(&source.item, source.count - moved))
+                } else {
+                    None
+                };
+            },
+            _ => self.slots.swap(from, to),
+        }
+    }
+}
+
+pub struct InventoryPlugin {
+    items_path: String,
+}
+
+impl InventoryPlugin {
+    pub fn new(items_path: &str) -> Self {
+        Self { items_path: items_path.to_string() }
+    }
+}
+
+#[derive(Resource)]
+struct ItemDefinitionsHandle(Handle<ItemDefinitions>);
+
+#[derive(Resource)]
+struct ItemDefinitionsPath(String);
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(JsonAssetPlugin::<ItemDefinitions>::new(&["items.json"]))
+            .insert_resource(ItemDefinitionsPath(self.items_path.clone()))
+            .init_resource::<ItemRegistry>()
+            .add_systems(Startup, load_item_definitions)
+            .add_systems(Update, sync_item_registry)
+            .add_debug_system(debug_inventory, INVENTORY_KEY);
+    }
+}
+
+fn load_item_definitions(
+    mut commands: Commands,
+    tracker: Option<ResMut<LoadingTracker>>,
+    asset_server: Res<AssetServer>,
+    path: Res<ItemDefinitionsPath>,
+) {
+    let handle: Handle<ItemDefinitions> = asset_server.load(path.0.clone());
+    if let Some(mut tracker) = tracker {
+        tracker.track(&path.0, handle.clone());
+        // The icon atlases are only known once the file is read, see sync_item_registry
+        tracker.defer(ICON_ATLASES);
+    }
+    commands.insert_resource(ItemDefinitionsHandle(handle));
+}
+
+fn sync_item_registry(
+    mut reader: EventReader<AssetEvent<ItemDefinitions>>,
+    mut registry: ResMut<ItemRegistry>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    definitions: Res<Assets<ItemDefinitions>>,
+    mut tracker: Option<ResMut<LoadingTracker>>,
+    handle: Res<ItemDefinitionsHandle>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in reader.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) && !event.is_modified(&handle.0) {
+            continue;
+        }
+        let Some(loaded) = definitions.get(&handle.0) else {
+            continue;
+        };
+
+        registry.items = loaded.items.iter().map(|i| (i.id.clone(), i.clone())).collect();
+        registry.atlases = loaded.atlases.iter()
+            .map(|(name, atlas)| {
+                let layout = TextureAtlasLayout::from_grid(UVec2::splat(atlas.tile_size), atlas.columns, atlas.rows, None, None);
+                let image: Handle<Image> = asset_server.load(atlas.image.clone());
+                if let Some(tracker) = &mut tracker {
+                    tracker.track(&atlas.image, image.clone());
+                }
+                (name.clone(), LoadedIconAtlas {
+                    image,
+                    layout: texture_atlas_layouts.add(layout),
+                })
+            })
+            .collect();
+        if let Some(tracker) = &mut tracker {
+            tracker.resolve(ICON_ATLASES);
+        }
+    }
+}
+
+// For console commands, e.g. app.add_console_command(ConsoleCommand::new("give", ...), give_item::<Player>)
+pub fn give_item<M: Component>(In(args): In<ConsoleArgs>, registry: Res<ItemRegistry>, mut inventory: Single<&mut Inventory, With<M>>) -> ConsoleResult {
+    let item = registry.find(args.text(0))?.to_string();
+    let count = match args.len() {
+        1 => 1,
+        _ => u32::try_from(args.int(1)).map_err(|_| format!("Can't give {} {}", args.int(1), item))?,
+    };
+    let leftover = inventory.add(&item, count, &registry);
+    match leftover {
+        0 => Ok(format!("Gave {} {}", count, item)),
+        _ => Ok(format!("Gave {} {}, {} didn't fit", count - leftover, item, leftover)),
+    }
+}
+
+#[derive(Default)]
+struct GrantState {
+    item: String,
+    count: u32,
+}
+
+fn debug_inventory(
+    mut ctx: EguiContexts,
+    mut inventories: Query<(Entity, &mut Inventory, Option<&Name>)>,
+    mut grant: Local<GrantState>,
+    registry: Res<ItemRegistry>,
+    debug_state: Res<DebugState>,
+) {
+    debug_window("Inventory", &debug_state).show(ctx.ctx_mut(), |ui| {
+        let ids = registry.sorted_ids();
+        if grant.count == 0 {
+            grant.count = 1;
+        }
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("grant_item")
+                .selected_text(grant.item.clone())
+                .show_ui(ui, |ui| {
+                    for id in ids {
+                        let label = registry.get(&id).map(|i| format!("{} ({})", i.name, i.category)).unwrap_or(id.clone());
+                        ui.selectable_value(&mut grant.item, id, label);
+                    }
+                });
+            ui.add(egui::DragValue::new(&mut grant.count).range(1..=999));
+        });
+
+        for (entity, mut inventory, name) in inventories.iter_mut() {
+            let title = name.map(|n| n.to_string()).unwrap_or(format!("{}", entity));
+            egui::CollapsingHeader::new(title).id_salt(entity).default_open(true).show(ui, |ui| {
+                if ui.button("Grant").clicked() && !grant.item.is_empty() {
+                    inventory.add(&grant.item, grant.count, &registry);
+                }
+                egui::Grid::new(("inventory_grid", entity))
+                    .num_columns(2)
+                    .spacing([40.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (i, slot) in inventory.slots.iter().enumerate() {
+                            ui.label(format!("{}", i + 1));
+                            ui.label(match slot {
+                                Some(stack) => format!("{} x{}", stack.item, stack.count),
+                                None => "-".to_string(),
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::World;
+    use crate::console_plugin::Arg;
+    use super::*;
+
+    fn registry() -> ItemRegistry {
+        let mut registry = ItemRegistry::default();
+        for (id, stack_size) in [("seed", 10), ("hoe", 1)] {
+            registry.items.insert(id.to_string(), ItemDefinition {
+                id: id.to_string(),
+                name: id.to_string(),
+                icon: ItemIcon { atlas: "items".to_string(), index: 0 },
+                stack_size,
+                category: ItemCategory::Material,
+                properties: HashMap::new(),
+            });
+        }
+        registry
+    }
+
+    #[test]
+    fn add() {
+        struct TestCase {
+            start: Vec<ItemStack>,
+            item: &'static str,
+            count: u32,
+            expected_leftover: u32,
+            expected_slots: Vec<Option<ItemStack>>,
+        }
+
+        let cases = vec!(
+            TestCase {
+                start: vec!(),
+                item: "seed",
+                count: 4,
+                expected_leftover: 0,
+                expected_slots: vec!(Some(ItemStack::new("seed", 4)), None, None),
+            },
+            TestCase {
+                start: vec!(ItemStack::new("seed", 8)),
+                item: "seed",
+                count: 5,
+                expected_leftover: 0,
+                expected_slots: vec!(Some(ItemStack::new("seed", 10)), Some(ItemStack::new("seed", 3)), None),
+            },
+            TestCase {
+                start: vec!(ItemStack::new("hoe", 1)),
+                item: "hoe",
+                count: 3,
+                expected_leftover: 1,
+                expected_slots: vec!(Some(ItemStack::new("hoe", 1)), Some(ItemStack::new("hoe", 1)), Some(ItemStack::new("hoe", 1))),
+            },
+            TestCase {
+                start: vec!(ItemStack::new("hoe", 1), ItemStack::new("seed", 10), ItemStack::new("seed", 10)),
+                item: "seed",
+                count: 1,
+                expected_leftover: 1,
+                expected_slots: vec!(Some(ItemStack::new("hoe", 1)), Some(ItemStack::new("seed", 10)), Some(ItemStack::new("seed", 10))),
+            },
+        );
+
+        let registry = registry();
+        for c in cases {
+            let mut inventory = Inventory::with_items(3, c.start);
+            assert_eq!(inventory.add(c.item, c.count, &registry), c.expected_leftover);
+            assert_eq!(inventory.slots, c.expected_slots);
+        }
+    }
+
+    #[test]
+    fn find() {
+        struct TestCase {
+            query: &'static str,
+            expected: Result<&'static str, &'static str>,
+        }
+
+        let mut registry = registry();
+        for id in ["wheat_seeds", "tomato_seeds"] {
+            let mut item = registry.items["seed"].clone();
+            item.id = id.to_string();
+            registry.items.insert(id.to_string(), item);
+        }
+        let cases = vec!(
+            TestCase { query: "seed", expected: Ok("seed") },
+            TestCase { query: "wheat", expected: Ok("wheat_seeds") },
+            TestCase { query: "_seeds", expected: Err("\"_seeds\" could be tomato_seeds, wheat_seeds") },
+            TestCase { query: "axe", expected: Err("No item matches \"axe\"") },
+        );
+
+        for c in cases {
+            assert_eq!(registry.find(c.query), c.expected.map_err(str::to_string), "{}", c.query);
+        }
+    }
+
+    #[test]
+    fn merge() {
+        struct TestCase {
+            start: Vec<Option<ItemStack>>,
+            from: usize,
+            to: usize,
+            expected_slots: Vec<Option<ItemStack>>,
+        }
+
+        let cases = vec!(
+            TestCase {
+                start: vec!(Some(ItemStack::new("seed", 4)), Some(ItemStack::new("seed", 3))),
+                from: 0,
+                to: 1,
+                expected_slots: vec!(None, Some(ItemStack::new("seed", 7))),
+            },
+            TestCase {
+                start: vec!(Some(ItemStack::new("seed", 6)), Some(ItemStack::new("seed", 7))),
+                from: 0,
+                to: 1,
+                expected_slots: vec!(Some(ItemStack::new("seed", 3)), Some(ItemStack::new("seed", 10))),
+            },
+            TestCase {
+                start: vec!(Some(ItemStack::new("hoe", 1)), Some(ItemStack::new("seed", 7))),
+                from: 0,
+                to: 1,
+                expected_slots: vec!(Some(ItemStack::new("seed", 7)), Some(ItemStack::new("hoe", 1))),
+            },
+            TestCase {
+                start: vec!(Some(ItemStack::new("hoe", 1)), None),
+                from: 0,
+                to: 1,
+                expected_slots: vec!(None, Some(ItemStack::new("hoe", 1))),
+            },
+        );
+
+        let registry = registry();
+        for c in cases {
+            let mut inventory = Inventory { slots: c.start };
+            inventory.merge(c.from, c.to, &registry);
+            assert_eq!(inventory.slots, c.expected_slots);
+        }
+    }
+
+    #[test]
+    fn give() {
+        #[derive(Component)]
+        struct Holder;
+
+        struct TestCase {
+            item: &'static str,
+            count: Option<i64>,
+            expected: Result<&'static str, &'static str>,
+            expected_count: u32,
+        }
+
+        let cases = vec!(
+            TestCase { item: "seed", count: None, expected: Ok("Gave 1 seed"), expected_count: 1 },
+            TestCase { item: "seed", count: Some(5), expected: Ok("Gave 5 seed"), expected_count: 5 },
+            // One slot, so the hoe that doesn't stack has nowhere for the rest to go
+            TestCase { item: "hoe", count: Some(3), expected: Ok("Gave 1 hoe, 2 didn't fit"), expected_count: 1 },
+            TestCase { item: "seed", count: Some(-1), expected: Err("Can't give -1 seed"), expected_count: 0 },
+            TestCase { item: "seed", count: Some(u32::MAX as i64 + 1), expected: Err("Can't give 4294967296 seed"), expected_count: 0 },
+        );
+
+        for c in cases {
+            let mut world = World::new();
+            world.insert_resource(registry());
+            let holder = world.spawn((Holder, Inventory::new(1))).id();
+            let args = ConsoleArgs(std::iter::once(Arg::Text(c.item.to_string())).chain(c.count.map(Arg::Int)).collect());
+            let result = world.run_system_once_with(args, give_item::<Holder>).unwrap();
+            assert_eq!(result, c.expected.map(str::to_string).map_err(str::to_string));
+            assert_eq!(world.get::<Inventory>(holder).unwrap().count(c.item), c.expected_count);
+        }
+    }
+}
@@ -1,4 +1,19 @@
 pub mod internal_asset_plugin;
 pub mod diagnostic_plugin;
+pub mod system_timings;
 pub mod texture_atlas_layout;
+pub mod texture_atlas_packer;
 pub mod debug_plugin;
+pub mod inspector_plugin;
+pub mod capture_plugin;
+pub mod tuning_plugin;
+pub mod gizmo_plugin;
+pub mod test_harness;
+pub mod golden_image;
+pub mod console_plugin;
+pub mod y_sort_plugin;
+pub mod inventory_plugin;
+pub mod save_plugin;
+pub mod game_state_plugin;
+pub mod loading_plugin;
+pub mod asset_manifest_plugin;
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+use bevy::app::{App, Plugin, Update};
+use bevy::asset::{AssetServer, LoadState, RecursiveDependencyLoadState, UntypedHandle};
+use bevy::color::Color;
+use bevy::prelude::{error, in_state, AlignItems, BackgroundColor, BuildChildren, ChildBuild, Commands, Component, EventWriter, FlexDirection, IntoSystemConfigs, JustifyContent, Local, Node, OnEnter, Res, ResMut, Resource, Single, StateScoped, Text, TextColor, TextFont, Val, With};
+use crate::game_state_plugin::{Fade, GameStates, TransitionRequest};
+
+const BAR_WIDTH: f32 = 300.0;
+const BAR_HEIGHT: f32 = 16.0;
+const BAR_BACKGROUND: Color = Color::srgb(0.2, 0.2, 0.2);
+const BAR_FILL: Color = Color::srgb(0.4, 0.8, 0.4);
+const FAILED_COLOR: Color = Color::srgb(1.0, 0.4, 0.4);
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum AssetStatus {
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+impl AssetStatus {
+    // An asset only counts as loaded once everything it depends on has too
+    pub fn new(load_state: &LoadState, dependencies: &RecursiveDependencyLoadState) -> Self {
+        match (load_state, dependencies) {
+            (LoadState::Failed(e), _) => AssetStatus::Failed(e.to_string()),
+            (_, RecursiveDependencyLoadState::Failed(e)) => AssetStatus::Failed(e.to_string()),
+            (LoadState::Loaded, RecursiveDependencyLoadState::Loaded) => AssetStatus::Loaded,
+            _ => AssetStatus::Loading,
+        }
+    }
+}
+
+// Handles that have to be in before the game can start, anything that loads assets can add to it
+#[derive(Resource, Default)]
+pub struct LoadingTracker {
+    handles: Vec<(String, UntypedHandle)>,
+    deferred: HashSet<String>,
+}
+
+impl LoadingTracker {
+    pub fn track(&mut self, label: &str, handle: impl Into<UntypedHandle>) {
+        self.handles.push((label.to_string(), handle.into()));
+    }
+
+    // For a file that names more assets to load once it's in, e.g. item definitions and their icons.
+    // It counts as loading until resolve is called with the same label after those are tracked
+    pub fn defer(&mut self, label: &str) {
+        self.deferred.insert(label.to_string());
+    }
+
+    pub fn resolve(&mut self, label: &str) {
+        self.deferred.remove(label);
+    }
+
+    pub fn statuses(&self, asset_server: &AssetServer) -> Vec<(String, AssetStatus)> {
+        let deferred = self.deferred.iter().map(|label| (label.clone(), AssetStatus::Loading));
+        self.handles.iter()
+            .map(|(label, handle)| {
+                let status = match asset_server.get_load_states(handle.id()) {
+                    Some((load_state, _, dependencies)) => AssetStatus::new(&load_state, &dependencies),
+                    None => AssetStatus::Loading,
+                };
+                (label.clone(), status)
+            })
+            .chain(deferred)
+            .collect()
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct LoadingProgress {
+    pub loaded: usize,
+    pub total: usize,
+    pub failed: Vec<(String, String)>,
+}
+
+impl LoadingProgress {
+    pub fn from_statuses(statuses: &[(String, AssetStatus)]) -> Self {
+        let mut progress = LoadingProgress { total: statuses.len(), ..Default::default() };
+        for (label, status) in statuses {
+            match status {
+                AssetStatus::Loaded => progress.loaded += 1,
+                AssetStatus::Failed(e) => progress.failed.push((label.clone(), e.clone())),
+                AssetStatus::Loading => {},
+            }
+        }
+        progress
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 { 1.0 } else { self.loaded as f32 / self.total as f32 }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.failed.is_empty() && self.loaded == self.total
+    }
+}
+
+#[derive(Component)]
+struct LoadingBarFill;
+
+#[derive(Component)]
+struct LoadingStatusText;
+
+// Shows a progress bar while in `loading` and moves on to `next` once every tracked handle is in
+pub struct LoadingPlugin<S: GameStates> {
+    loading: S,
+    next: S,
+}
+
+impl<S: GameStates> LoadingPlugin<S> {
+    pub fn new(loading: S, next: S) -> Self {
+        Self { loading, next }
+    }
+}
+
+#[derive(Resource)]
+struct LoadingStates<S: GameStates> {
+    loading: S,
+    next: S,
+    // Set once the transition out has been asked for, until the loading state is entered again
+    done: bool,
+}
+
+impl<S: GameStates> Plugin for LoadingPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingTracker>()
+            .init_resource::<LoadingProgress>()
+            .insert_resource(LoadingStates { loading: self.loading, next: self.next, done: false })
+            .add_systems(OnEnter(self.loading), spawn_loading_screen::<S>)
+            .add_systems(Update, (update_loading_progress::<S>, update_loading_screen).chain().run_if(in_state(self.loading)));
+    }
+}
+
+fn spawn_loading_screen<S: GameStates>(mut commands: Commands, mut states: ResMut<LoadingStates<S>>) {
+    states.done = false;
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(8.0),
+            ..Default::default()
+        },
+        StateScoped(states.loading),
+    )).with_children(|parent| {
+        parent.spawn((Text::new("Loading..."), TextFont { font_size: 42.0, ..Default::default() }));
+        parent.spawn((
+            Node { width: Val::Px(BAR_WIDTH), height: Val::Px(BAR_HEIGHT), ..Default::default() },
+            BackgroundColor(BAR_BACKGROUND),
+        )).with_children(|bar| {
+            bar.spawn((
+                LoadingBarFill,
+                Node { width: Val::Percent(0.0), height: Val::Percent(100.0), ..Default::default() },
+                BackgroundColor(BAR_FILL),
+            ));
+        });
+        parent.spawn((
+            LoadingStatusText,
+            Text::new(""),
+            TextFont { font_size: 16.0, ..Default::default() },
+            TextColor(FAILED_COLOR),
+        ));
+    });
+}
+
+fn update_loading_progress<S: GameStates>(
+    mut progress: ResMut<LoadingProgress>,
+    mut writer: EventWriter<TransitionRequest<S>>,
+    mut reported: Local<HashSet<String>>,
+    tracker: Res<LoadingTracker>,
+    mut states: ResMut<LoadingStates<S>>,
+    fade: Res<Fade<S>>,
+    asset_server: Res<AssetServer>,
+) {
+    *progress = LoadingProgress::from_statuses(&tracker.statuses(&asset_server));
+
+    // Failures never finish, so log them once and stay on the loading screen
+    for (label, e) in &progress.failed {
+        if reported.insert(label.clone()) {
+            error!("Failed to load {}: {}", label, e);
+        }
+    }
+    // A request made while the fade into loading is still going would be dropped
+    if progress.is_ready() && !states.done && !fade.is_fading() {
+        writer.send(TransitionRequest(states.next));
+        states.done = true;
+    }
+}
+
+fn update_loading_screen(
+    progress: Res<LoadingProgress>,
+    mut fill: Single<&mut Node, With<LoadingBarFill>>,
+    mut status: Single<&mut Text, With<LoadingStatusText>>,
+) {
+    fill.width = Val::Percent(progress.fraction() * 100.0);
+    status.0 = progress.failed.iter()
+        .map(|(label, e)| format!("Failed to load {}: {}", label, e))
+        .collect::<Vec<String>>()
+        .join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+    use bevy::MinimalPlugins;
+    use super::*;
+
+    #[test]
+    fn progress_from_statuses() {
+        struct TestCase {
+            statuses: Vec<AssetStatus>,
+            expected_fraction: f32,
+            expected_ready: bool,
+        }
+
+        let cases = vec!(
+            TestCase { statuses: vec!(), expected_fraction: 1.0, expected_ready: true },
+            TestCase { statuses: vec!(AssetStatus::Loading, AssetStatus::Loaded), expected_fraction: 0.5, expected_ready: false },
+            TestCase { statuses: vec!(AssetStatus::Loaded, AssetStatus::Loaded), expected_fraction: 1.0, expected_ready: true },
+            TestCase { statuses: vec!(AssetStatus::Loaded, AssetStatus::Failed("missing".to_string())), expected_fraction: 0.5, expected_ready: false },
+        );
+
+        for c in cases {
+            let statuses: Vec<(String, AssetStatus)> = c.statuses.into_iter().enumerate()
+                .map(|(i, s)| (format!("asset {}", i), s))
+                .collect();
+            let progress = LoadingProgress::from_statuses(&statuses);
+            assert_eq!(progress.fraction(), c.expected_fraction);
+            assert_eq!(progress.is_ready(), c.expected_ready);
+        }
+    }
+
+    #[test]
+    fn deferred_until_resolved() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        let asset_server = app.world().resource::<AssetServer>();
+
+        let mut tracker = LoadingTracker::default();
+        tracker.defer("items.json");
+        assert!(!LoadingProgress::from_statuses(&tracker.statuses(asset_server)).is_ready());
+        tracker.resolve("items.json");
+        assert!(LoadingProgress::from_statuses(&tracker.statuses(asset_server)).is_ready());
+    }
+}
@@ -0,0 +1,343 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use bevy::app::{App, Plugin, PostStartup, Update};
+use bevy::prelude::{error, info, Event, EventReader, EventWriter, IntoSystemConfigs, IntoSystemSetConfigs, Res, ResMut, Resource, SystemSet};
+use bevy::time::{Time, Timer, TimerMode};
+use bevy_egui::{egui, EguiContexts};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::debug_plugin::{debug_window, DebugKey, DebugState, Debugger};
+
+// Slot 0 is kept for autosaves, the rest are for the player
+pub const AUTOSAVE_SLOT: u32 = 0;
+const DEFAULT_SLOTS: u32 = 3;
+const SAVE_DIR_ENV: &str = "GAME_LAB_SAVE_DIR";
+const DEFAULT_SAVE_DIR: &str = "saves";
+const SAVES_KEY: DebugKey = DebugKey::new("Tools/Saves");
+
+// Upgrades a save's sections from version n to n + 1
+pub type Migration = fn(&mut Map<String, Value>);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SaveFile {
+    pub version: u32,
+    // Seconds since the unix epoch
+    pub saved_at: u64,
+    pub sections: Map<String, Value>,
+}
+
+impl SaveFile {
+    pub fn new(version: u32) -> Self {
+        let saved_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        Self { version, saved_at, sections: Map::new() }
+    }
+
+    pub fn write<T: Serialize>(&mut self, key: &str, section: &T) {
+        match serde_json::to_value(section) {
+            Ok(value) => { self.sections.insert(key.to_string(), value); },
+            Err(e) => error!("Failed to save section {}: {}", key, e),
+        }
+    }
+
+    pub fn read<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.sections.get(key)?;
+        serde_json::from_value(value.clone())
+            .inspect_err(|e| error!("Failed to load section {}: {}", key, e))
+            .ok()
+    }
+
+    // Runs every migration between the file's version and the current one, in order
+    pub fn migrate(&mut self, current: u32, migrations: &[(u32, Migration)]) -> Result<(), String> {
+        if self.version > current {
+            return Err(format!("Save version {} is newer than the game's {}", self.version, current));
+        }
+        while self.version < current {
+            let Some((_, migration)) = migrations.iter().find(|(from, _)| *from == self.version) else {
+                return Err(format!("No migration from save version {}", self.version));
+            };
+            migration(&mut self.sections);
+            self.version += 1;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SaveRequest(pub u32);
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct LoadRequest(pub u32);
+
+// Sent once every Apply system has had a look at the loaded file
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SaveLoaded(pub u32);
+
+// Game systems go in Collect (gated by `saving`) and Apply (gated by `loading`)
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SaveSystems {
+    Begin,
+    Collect,
+    Write,
+    Read,
+    Apply,
+    Finish,
+}
+
+#[derive(Resource, Default)]
+pub struct SaveState {
+    pub saving: Option<(u32, SaveFile)>,
+    pub loading: Option<(u32, SaveFile)>,
+}
+
+impl SaveState {
+    pub fn write<T: Serialize>(&mut self, key: &str, section: &T) {
+        if let Some((_, file)) = &mut self.saving {
+            file.write(key, section);
+        }
+    }
+
+    pub fn read<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.loading.as_ref().and_then(|(_, file)| file.read(key))
+    }
+}
+
+pub fn saving(state: Res<SaveState>) -> bool {
+    state.saving.is_some()
+}
+
+pub fn loading(state: Res<SaveState>) -> bool {
+    state.loading.is_some()
+}
+
+#[derive(Resource)]
+pub struct SaveSettings {
+    pub directory: PathBuf,
+    pub version: u32,
+    pub slots: u32,
+    pub migrations: Vec<(u32, Migration)>,
+    pub autoload: Option<u32>,
+    pub autosave: Option<Timer>,
+}
+
+impl SaveSettings {
+    pub fn slot_path(&self, slot: u32) -> PathBuf {
+        self.directory.join(format!("slot_{}.json", slot))
+    }
+
+    pub fn read_slot(&self, slot: u32) -> Result<SaveFile, String> {
+        let path = self.slot_path(slot);
+        let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut file: SaveFile = serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        file.migrate(self.version, &self.migrations)?;
+        Ok(file)
+    }
+
+    pub fn write_slot(&self, slot: u32, file: &SaveFile) -> Result<(), String> {
+        fs::create_dir_all(&self.directory).map_err(|e| e.to_string())?;
+        let text = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+        // Write then rename so a crash mid save doesn't eat the old file
+        let path = self.slot_path(slot);
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, text).map_err(|e| e.to_string())?;
+        fs::rename(&temp, &path).map_err(|e| e.to_string())
+    }
+}
+
+pub struct SavePlugin {
+    game: String,
+    version: u32,
+    slots: u32,
+    migrations: Vec<(u32, Migration)>,
+    autoload: Option<u32>,
+    autosave_interval: Option<Duration>,
+}
+
+impl SavePlugin {
+    pub fn new(game: &str, version: u32) -> Self {
+        Self {
+            game: game.to_string(),
+            version,
+            slots: DEFAULT_SLOTS,
+            migrations: Vec::new(),
+            autoload: None,
+            autosave_interval: None,
+        }
+    }
+
+    pub fn with_migration(mut self, from_version: u32, migration: Migration) -> Self {
+        self.migrations.push((from_version, migration));
+        self
+    }
+
+    // Loads the slot once Startup has spawned everything, if it exists
+    pub fn with_autoload(mut self, slot: u32) -> Self {
+        self.autoload = Some(slot);
+        self
+    }
+
+    pub fn with_autosave_interval(mut self, interval: Duration) -> Self {
+        self.autosave_interval = Some(interval);
+        self
+    }
+}
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        let root = std::env::var(SAVE_DIR_ENV).map(PathBuf::from).unwrap_or(PathBuf::from(DEFAULT_SAVE_DIR));
+        app.add_event::<SaveRequest>()
+            .add_event::<LoadRequest>()
+            .add_event::<SaveLoaded>()
+            .init_resource::<SaveState>()
+            .insert_resource(SaveSettings {
+                directory: root.join(&self.game),
+                version: self.version,
+                slots: self.slots,
+                migrations: self.migrations.clone(),
+                autoload: self.autoload,
+                autosave: self.autosave_interval.map(|i| Timer::new(i, TimerMode::Repeating)),
+            })
+            .configure_sets(Update, (
+                SaveSystems::Begin,
+                SaveSystems::Collect,
+                SaveSystems::Write,
+                SaveSystems::Read,
+                SaveSystems::Apply,
+                SaveSystems::Finish,
+            ).chain())
+            .add_systems(PostStartup, autoload)
+            .add_systems(Update, (
+                autosave_timer.before(SaveSystems::Begin),
+                begin_save.in_set(SaveSystems::Begin),
+                write_save.in_set(SaveSystems::Write),
+                read_save.in_set(SaveSystems::Read),
+                finish_load.in_set(SaveSystems::Finish),
+            ))
+            .add_debug_system(debug_saves, SAVES_KEY);
+    }
+}
+
+fn autoload(settings: Res<SaveSettings>, mut writer: EventWriter<LoadRequest>) {
+    if let Some(slot) = settings.autoload && settings.slot_path(slot).exists() {
+        writer.send(LoadRequest(slot));
+    }
+}
+
+fn autosave_timer(time: Res<Time>, mut settings: ResMut<SaveSettings>, mut writer: EventWriter<SaveRequest>) {
+    if let Some(timer) = &mut settings.autosave && timer.tick(time.delta()).just_finished() {
+        writer.send(SaveRequest(AUTOSAVE_SLOT));
+    }
+}
+
+fn begin_save(mut reader: EventReader<SaveRequest>, mut state: ResMut<SaveState>, settings: Res<SaveSettings>) {
+    // Several requests in a frame all end up in the last slot asked for
+    if let Some(request) = reader.read().last() {
+        state.saving = Some((request.0, SaveFile::new(settings.version)));
+    }
+}
+
+fn write_save(mut state: ResMut<SaveState>, settings: Res<SaveSettings>) {
+    let Some((slot, file)) = state.saving.take() else {
+        return;
+    };
+    match settings.write_slot(slot, &file) {
+        Ok(_) => info!("Saved slot {} to {}", slot, settings.slot_path(slot).display()),
+        Err(e) => error!("Failed to save slot {}: {}", slot, e),
+    }
+}
+
+fn read_save(mut reader: EventReader<LoadRequest>, mut state: ResMut<SaveState>, settings: Res<SaveSettings>) {
+    let Some(request) = reader.read().last() else {
+        return;
+    };
+    match settings.read_slot(request.0) {
+        Ok(file) => state.loading = Some((request.0, file)),
+        Err(e) => error!("Failed to load slot {}: {}", request.0, e),
+    }
+}
+
+fn finish_load(mut state: ResMut<SaveState>, mut writer: EventWriter<SaveLoaded>) {
+    if let Some((slot, _)) = state.loading.take() {
+        info!("Loaded slot {}", slot);
+        writer.send(SaveLoaded(slot));
+    }
+}
+
+fn debug_saves(
+    mut ctx: EguiContexts,
+    mut save_writer: EventWriter<SaveRequest>,
+    mut load_writer: EventWriter<LoadRequest>,
+    settings: Res<SaveSettings>,
+    debug_state: Res<DebugState>,
+) {
+    debug_window("Saves", &debug_state).show(ctx.ctx_mut(), |ui| {
+        ui.label(format!("{}", settings.directory.display()));
+        egui::Grid::new("saves_grid")
+            .num_columns(3)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for slot in AUTOSAVE_SLOT..=settings.slots {
+                    let name = if slot == AUTOSAVE_SLOT { "Autosave".to_string() } else { format!("Slot {}", slot) };
+                    let exists = settings.slot_path(slot).exists();
+                    ui.label(if exists { name } else { format!("{} (empty)", name) });
+                    if ui.button("Save").clicked() {
+                        save_writer.send(SaveRequest(slot));
+                    }
+                    if ui.add_enabled(exists, egui::Button::new("Load")).clicked() {
+                        load_writer.send(LoadRequest(slot));
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate() {
+        struct TestCase {
+            version: u32,
+            current: u32,
+            expected: Result<Value, ()>,
+        }
+
+        // v0 stored a bare number, v1 wrapped it, v2 renamed the key
+        fn wrap(sections: &mut Map<String, Value>) {
+            let gold = sections.remove("gold").unwrap_or(Value::from(0));
+            sections.insert("purse".to_string(), serde_json::json!({ "gold": gold }));
+        }
+        fn rename(sections: &mut Map<String, Value>) {
+            if let Some(purse) = sections.remove("purse") {
+                sections.insert("wallet".to_string(), purse);
+            }
+        }
+        let migrations: Vec<(u32, Migration)> = vec!((0, wrap), (1, rename));
+
+        let cases = vec!(
+            TestCase { version: 0, current: 2, expected: Ok(serde_json::json!({ "wallet": { "gold": 5 } })) },
+            TestCase { version: 0, current: 1, expected: Ok(serde_json::json!({ "purse": { "gold": 5 } })) },
+            TestCase { version: 2, current: 2, expected: Ok(serde_json::json!({ "gold": 5 })) },
+            TestCase { version: 3, current: 2, expected: Err(()) },
+            TestCase { version: 0, current: 3, expected: Err(()) },
+        );
+
+        for c in cases {
+            let mut file = SaveFile::new(c.version);
+            file.sections.insert("gold".to_string(), Value::from(5));
+            let result = file.migrate(c.current, &migrations);
+            match c.expected {
+                Ok(expected) => {
+                    assert!(result.is_ok());
+                    assert_eq!(file.version, c.current);
+                    assert_eq!(Value::Object(file.sections), expected);
+                },
+                Err(_) => assert!(result.is_err()),
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use bevy::app::App;
+use bevy::log::BoxedLayer;
+use bevy::log::tracing_subscriber::layer::Context;
+use bevy::log::tracing_subscriber::registry::LookupSpan;
+use bevy::log::tracing_subscriber::Layer;
+use bevy::prelude::{ResMut, Resource};
+use bevy::utils::tracing::field::{Field, Visit};
+use bevy::utils::tracing::span::{Attributes, Id};
+use bevy::utils::tracing::Subscriber;
+use crate::inspector_plugin::short_name;
+
+// How much of each new frame goes into the running average
+const SMOOTHING: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SpanKind {
+    System,
+    Schedule,
+}
+
+impl SpanKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpanKind::System => "system",
+            SpanKind::Schedule => "schedule",
+        }
+    }
+}
+
+type SpanTotals = Arc<Mutex<HashMap<(SpanKind, String), Duration>>>;
+
+// Time spent in Bevy's "system" and "schedule" spans, in milliseconds per frame. Systems running in
+// parallel each count in full. Bevy only opens these spans when built with bevy/trace, which the
+// `system_timings` feature turns on, e.g. cargo run --features game_lab_utils/system_timings
+#[derive(Resource, Clone, Default)]
+pub struct SystemTimings {
+    totals: SpanTotals,
+    pub averages: BTreeMap<(SpanKind, String), f64>,
+}
+
+impl SystemTimings {
+    // Called once a frame, anything that didn't run this frame fades towards zero
+    pub fn collect(&mut self) {
+        let totals: HashMap<(SpanKind, String), Duration> = std::mem::take(&mut *self.totals.lock().unwrap());
+        for (key, average) in self.averages.iter_mut() {
+            let ms = totals.get(key).map_or(0.0, |d| d.as_secs_f64() * 1000.0);
+            *average += (ms - *average) * SMOOTHING;
+        }
+        for (key, duration) in totals {
+            self.averages.entry(key).or_insert(duration.as_secs_f64() * 1000.0);
+        }
+    }
+
+    // Slowest first
+    pub fn slowest(&self, kind: SpanKind, count: usize) -> Vec<(&str, f64)> {
+        let mut spans: Vec<(&str, f64)> = self.averages.iter()
+            .filter(|((k, _), _)| *k == kind)
+            .map(|((_, name), ms)| (name.as_str(), *ms))
+            .collect();
+        spans.sort_by(|a, b| b.1.total_cmp(&a.1));
+        spans.truncate(count);
+        spans
+    }
+}
+
+// Goes in LogPlugin::custom_layer, which runs while the app is built and lets the layer share its totals
+pub fn system_timing_layer(app: &mut App) -> Option<BoxedLayer> {
+    let timings = SystemTimings::default();
+    let layer = SpanTimingLayer { totals: timings.totals.clone() };
+    app.insert_resource(timings);
+    Some(Box::new(layer))
+}
+
+pub(crate) fn collect_system_timings(timings: Option<ResMut<SystemTimings>>) {
+    if let Some(mut timings) = timings {
+        timings.collect();
+    }
+}
+
+struct SpanTimingLayer {
+    totals: SpanTotals,
+}
+
+struct SpanTiming {
+    key: (SpanKind, String),
+    entered: Option<Instant>,
+}
+
+#[derive(Default)]
+struct NameVisitor(Option<String>);
+
+impl Visit for NameVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "name" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "name" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanTimingLayer {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let kind = match attrs.metadata().name() {
+            "system" => SpanKind::System,
+            "schedule" => SpanKind::Schedule,
+            _ => return,
+        };
+        let mut name = NameVisitor::default();
+        attrs.record(&mut name);
+        if let (Some(span), Some(name)) = (ctx.span(id), name.0) {
+            span.extensions_mut().insert(SpanTiming { key: (kind, short_name(&name)), entered: None });
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+            timing.entered = Some(Instant::now());
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>()
+            && let Some(entered) = timing.entered.take() {
+            *self.totals.lock().unwrap().entry(timing.key.clone()).or_default() += entered.elapsed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect() {
+        let mut timings = SystemTimings::default();
+        let add = |timings: &SystemTimings, kind: SpanKind, name: &str, ms: u64| {
+            *timings.totals.lock().unwrap().entry((kind, name.to_string())).or_default() += Duration::from_millis(ms);
+        };
+
+        add(&timings, SpanKind::System, "move_player", 2);
+        add(&timings, SpanKind::System, "move_player", 2);
+        add(&timings, SpanKind::System, "y_sort", 1);
+        add(&timings, SpanKind::Schedule, "Update", 6);
+        timings.collect();
+        assert_eq!(timings.slowest(SpanKind::System, 5), vec!(("move_player", 4.0), ("y_sort", 1.0)));
+        assert_eq!(timings.slowest(SpanKind::Schedule, 5), vec!(("Update", 6.0)));
+
+        // Nothing ran, the averages fade instead of dropping straight to zero
+        timings.collect();
+        let slowest = timings.slowest(SpanKind::System, 1);
+        assert_eq!(slowest.len(), 1);
+        assert!((slowest[0].1 - 3.6).abs() < 1e-9);
+    }
+}
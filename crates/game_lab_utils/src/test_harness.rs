@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use bevy::app::{App, Plugins};
+use bevy::asset::{AssetApp, AssetPlugin};
+use bevy::ecs::query::{QueryFilter, ROQueryItem, ReadOnlyQueryData};
+use bevy::image::{CompressedImageFormats, ImageLoader};
+use bevy::input::keyboard::{Key, KeyboardInput, NativeKey};
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::prelude::{Component, Entity, Event, HierarchyPlugin, ImagePlugin, KeyCode, MinimalPlugins, MouseButton, NextState, Resource, Shader, TextureAtlasLayout, TransformPlugin, World};
+use bevy::state::app::StatesPlugin;
+use bevy::state::state::FreelyMutableState;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{PrimaryWindow, Window};
+use crate::debug_plugin::DebugState;
+use crate::internal_asset_plugin::{asset_root, AssetSourcesPlugin};
+
+// Every frame is this long, so timers behave the same however fast the tests run
+pub const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+
+// An App without a window or renderer for driving game plugins from tests. Input goes in as the
+// same events a window would send, and each step runs one frame of FRAME_TIME.
+// Anything that needs the render world, e.g. materials or a camera's viewport, won't work here
+pub struct TestApp {
+    pub app: App,
+}
+
+impl TestApp {
+    // Reads from the real assets folder, with game:// pointing at this game's data
+    pub fn new(game: &str) -> Self {
+        Self::with_asset_root(asset_root(), game)
+    }
+
+    // A crate's tests/fixtures folder laid out like assets, e.g. with_fixtures(env!("CARGO_MANIFEST_DIR"), "game1")
+    pub fn with_fixtures(manifest_dir: &str, game: &str) -> Self {
+        Self::with_asset_root(PathBuf::from(manifest_dir).join("tests").join("fixtures"), game)
+    }
+
+    pub fn with_asset_root(root: PathBuf, game: &str) -> Self {
+        let mut app = App::new();
+        app.add_plugins(AssetSourcesPlugin::new(game).with_root(root.clone()))
+            .add_plugins(MinimalPlugins)
+            .add_plugins(AssetPlugin {
+                file_path: root.to_string_lossy().to_string(),
+                watch_for_changes_override: Some(false),
+                ..Default::default()
+            })
+            .add_plugins((ImagePlugin::default_nearest(), TransformPlugin, HierarchyPlugin, InputPlugin, StatesPlugin))
+            .init_asset::<TextureAtlasLayout>()
+            .init_asset::<Shader>()
+            // ImagePlugin only adds its loader once there's a renderer, without it images never finish loading
+            .register_asset_loader(ImageLoader::new(CompressedImageFormats::NONE))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+            // Debug systems check this, left disabled
+            .init_resource::<DebugState>();
+        // Nothing is opened for it, it's there for systems that read the window's size or cursor
+        app.world_mut().spawn((Window::default(), PrimaryWindow));
+        Self { app }
+    }
+
+    pub fn add_plugins<M>(&mut self, plugins: impl Plugins<M>) -> &mut Self {
+        self.app.add_plugins(plugins);
+        self
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn step(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.app.update();
+        }
+        self
+    }
+
+    // Steps until `done` holds, giving assets a moment to load on the IO threads between frames.
+    // Returns false if it still didn't after `max_frames`
+    pub fn step_until(&mut self, max_frames: usize, done: impl Fn(&World) -> bool) -> bool {
+        for _ in 0..max_frames {
+            self.app.update();
+            if done(self.app.world()) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        false
+    }
+
+    // Takes effect on the next step, like any other state change
+    pub fn set_state<S: FreelyMutableState>(&mut self, state: S) -> &mut Self {
+        self.app.world_mut().resource_mut::<NextState<S>>().set(state);
+        self
+    }
+
+    pub fn send_event<E: Event>(&mut self, event: E) -> &mut Self {
+        self.app.world_mut().send_event(event);
+        self
+    }
+
+    pub fn press(&mut self, key: KeyCode) -> &mut Self {
+        self.send_key(key, ButtonState::Pressed)
+    }
+
+    pub fn release(&mut self, key: KeyCode) -> &mut Self {
+        self.send_key(key, ButtonState::Released)
+    }
+
+    // Down for one frame then up
+    pub fn tap(&mut self, key: KeyCode) -> &mut Self {
+        self.press(key).step(1).release(key)
+    }
+
+    pub fn press_mouse(&mut self, button: MouseButton) -> &mut Self {
+        self.send_event(MouseButtonInput { button, state: ButtonState::Pressed, window: Entity::PLACEHOLDER })
+    }
+
+    pub fn release_mouse(&mut self, button: MouseButton) -> &mut Self {
+        self.send_event(MouseButtonInput { button, state: ButtonState::Released, window: Entity::PLACEHOLDER })
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, state: ButtonState) -> &mut Self {
+        self.send_event(KeyboardInput {
+            key_code,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        })
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world().resource::<R>()
+    }
+
+    // Panics unless exactly one entity has C
+    pub fn single<C: Component>(&mut self) -> &C {
+        let mut query = self.app.world_mut().query::<&C>();
+        query.single(self.app.world())
+    }
+
+    // e.g. app.query_single::<&Transform, With<Player>>()
+    pub fn query_single<D: ReadOnlyQueryData, F: QueryFilter>(&mut self) -> ROQueryItem<'_, D> {
+        let mut query = self.app.world_mut().query_filtered::<D, F>();
+        query.single(self.app.world())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::Update;
+    use bevy::input::ButtonInput;
+    use bevy::prelude::{Res, ResMut, Time};
+    use crate::inventory_plugin::{InventoryPlugin, ItemRegistry};
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Held(Vec<bool>);
+
+    #[test]
+    fn input_and_time() {
+        let mut app = TestApp::with_fixtures(env!("CARGO_MANIFEST_DIR"), "test");
+        app.app.init_resource::<Held>()
+            .add_systems(Update, |keys: Res<ButtonInput<KeyCode>>, mut held: ResMut<Held>| held.0.push(keys.pressed(KeyCode::KeyW)));
+
+        app.step(1).press(KeyCode::KeyW).step(2).release(KeyCode::KeyW).step(1);
+        assert_eq!(app.resource::<Held>().0, vec!(false, true, true, false));
+
+        app.tap(KeyCode::KeyW).step(1);
+        assert_eq!(app.resource::<Held>().0[4..], [true, false]);
+        assert_eq!(app.resource::<Time>().delta(), FRAME_TIME);
+    }
+
+    #[test]
+    fn fixtures() {
+        let mut app = TestApp::with_fixtures(env!("CARGO_MANIFEST_DIR"), "test");
+        app.add_plugins(InventoryPlugin::new("game://items.json"));
+
+        assert!(app.step_until(500, |world| !world.resource::<ItemRegistry>().items.is_empty()));
+        assert_eq!(app.resource::<ItemRegistry>().stack_size("seed"), 10);
+        assert!(app.resource::<ItemRegistry>().icon("seed").is_some());
+    }
+}
@@ -1,13 +1,249 @@
-use bevy::math::UVec2;
+use std::fmt::{Display, Formatter};
+use bevy::math::{URect, UVec2};
 use bevy::sprite::TextureAtlasLayout;
 
+// Sheets with `padding` between tiles and half of it around the edge, i.e. each tile carries its own border
 pub fn texture_atlas_layout_with_padding(size: UVec2, columns: u32, rows: u32, padding: u32) -> TextureAtlasLayout {
-    let mut x = TextureAtlasLayout::from_grid(
-        size,
-        columns,
-        rows,
-        Some(UVec2::splat(padding)),
-        Some(UVec2::splat(padding / 2)));
-    x.size = (size + UVec2::splat(padding)) * (UVec2::new(columns, rows));
-    x
-}
\ No newline at end of file
+    let grid = AtlasGrid::new(size, columns, rows)
+        .with_spacing(UVec2::splat(padding))
+        .with_margin(UVec2::splat(padding / 2));
+    grid.layout(grid.required_size() + UVec2::splat(padding - padding / 2))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AtlasGridError {
+    TooSmall { required: UVec2, actual: UVec2 },
+    // The image has room for more whole tiles than the grid uses, usually a wrong tile size
+    UnusedTiles { columns: u32, rows: u32 },
+}
+
+impl Display for AtlasGridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtlasGridError::TooSmall { required, actual } =>
+                write!(f, "grid needs {}x{} but the image is {}x{}", required.x, required.y, actual.x, actual.y),
+            AtlasGridError::UnusedTiles { columns, rows } =>
+                write!(f, "image fits {} columns and {} rows, more than the grid uses", columns, rows),
+        }
+    }
+}
+
+// A uniform grid of tiles. Tiles count along rows unless `column_major` is set, and `count` can stop
+// short of the last row or column for sheets that don't fill it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasGrid {
+    pub tile_size: UVec2,
+    pub columns: u32,
+    pub rows: u32,
+    // Gap between neighbouring tiles
+    pub spacing: UVec2,
+    // Gap between the image edge and the first tile
+    pub margin: UVec2,
+    pub count: Option<usize>,
+    pub column_major: bool,
+}
+
+impl AtlasGrid {
+    pub fn new(tile_size: UVec2, columns: u32, rows: u32) -> Self {
+        Self { tile_size, columns, rows, spacing: UVec2::ZERO, margin: UVec2::ZERO, count: None, column_major: false }
+    }
+
+    // The most columns and rows that fit, the same as from_grid would make
+    pub fn from_image_size(image_size: UVec2, tile_size: UVec2, spacing: UVec2, margin: UVec2) -> Self {
+        let fits = (image_size.saturating_sub(margin) + spacing) / (tile_size + spacing);
+        Self { spacing, margin, ..Self::new(tile_size, fits.x, fits.y) }
+    }
+
+    pub fn with_spacing(mut self, spacing: UVec2) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn with_margin(mut self, margin: UVec2) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn column_major(mut self) -> Self {
+        self.column_major = true;
+        self
+    }
+
+    pub fn tile_count(&self) -> usize {
+        let full = (self.columns * self.rows) as usize;
+        self.count.map_or(full, |c| c.min(full))
+    }
+
+    pub fn tile_rect(&self, index: usize) -> URect {
+        let index = index as u32;
+        let cell = if self.column_major {
+            UVec2::new(index / self.rows, index % self.rows)
+        } else {
+            UVec2::new(index % self.columns, index / self.columns)
+        };
+        let min = self.margin + cell * (self.tile_size + self.spacing);
+        URect::from_corners(min, min + self.tile_size)
+    }
+
+    // Smallest image the grid fits in, margin on the leading edges only
+    pub fn required_size(&self) -> UVec2 {
+        let tiles = UVec2::new(self.columns, self.rows);
+        self.margin + tiles * self.tile_size + tiles.saturating_sub(UVec2::ONE) * self.spacing
+    }
+
+    pub fn validate(&self, image_size: UVec2) -> Result<(), AtlasGridError> {
+        let required = self.required_size();
+        if required.x > image_size.x || required.y > image_size.y {
+            return Err(AtlasGridError::TooSmall { required, actual: image_size });
+        }
+        let fits = Self::from_image_size(image_size, self.tile_size, self.spacing, self.margin);
+        if fits.columns > self.columns || fits.rows > self.rows {
+            return Err(AtlasGridError::UnusedTiles { columns: fits.columns, rows: fits.rows });
+        }
+        Ok(())
+    }
+
+    // `image_size` has to be the real size of the image, sprites are cut out relative to it
+    pub fn layout(&self, image_size: UVec2) -> TextureAtlasLayout {
+        let mut layout = TextureAtlasLayout::new_empty(image_size);
+        for i in 0..self.tile_count() {
+            layout.add_texture(self.tile_rect(i));
+        }
+        layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheets() {
+        struct TestCase {
+            name: &'static str,
+            grid: AtlasGrid,
+            image_size: UVec2,
+        }
+
+        // Every grid sheet the games load, at its real size
+        let cases = vec!(
+            TestCase { name: "dungeon tiles", grid: AtlasGrid::new(UVec2::splat(32), 12, 10), image_size: UVec2::new(384, 320) },
+            TestCase { name: "coins", grid: AtlasGrid::new(UVec2::splat(32), 1, 1), image_size: UVec2::new(32, 32) },
+            TestCase { name: "grass", grid: AtlasGrid::new(UVec2::splat(16), 39, 7), image_size: UVec2::new(624, 112) },
+            TestCase { name: "water", grid: AtlasGrid::new(UVec2::splat(16), 4, 1), image_size: UVec2::new(64, 16) },
+            TestCase { name: "player idle", grid: AtlasGrid::new(UVec2::splat(80), 4, 4), image_size: UVec2::new(320, 320) },
+            TestCase { name: "player walk", grid: AtlasGrid::new(UVec2::splat(80), 8, 4), image_size: UVec2::new(640, 320) },
+            TestCase { name: "player run", grid: AtlasGrid::new(UVec2::splat(80), 8, 4), image_size: UVec2::new(640, 320) },
+            TestCase { name: "tilled dirt", grid: AtlasGrid::new(UVec2::splat(16), 11, 7), image_size: UVec2::new(176, 112) },
+            TestCase { name: "plants", grid: AtlasGrid::new(UVec2::splat(16), 6, 2), image_size: UVec2::new(96, 32) },
+            TestCase { name: "tools", grid: AtlasGrid::new(UVec2::splat(16), 3, 3), image_size: UVec2::new(48, 48) },
+        );
+
+        for c in cases {
+            assert_eq!(c.grid.validate(c.image_size), Ok(()), "{}", c.name);
+            assert_eq!(AtlasGrid::from_image_size(c.image_size, c.grid.tile_size, UVec2::ZERO, UVec2::ZERO), c.grid, "{}", c.name);
+
+            // Same cuts as Bevy's own grid builder
+            let layout = c.grid.layout(c.image_size);
+            let expected = TextureAtlasLayout::from_grid(c.grid.tile_size, c.grid.columns, c.grid.rows, None, None);
+            assert_eq!(layout.size, expected.size, "{}", c.name);
+            assert_eq!(layout.textures, expected.textures, "{}", c.name);
+        }
+    }
+
+    #[test]
+    fn tile_rect() {
+        struct TestCase {
+            grid: AtlasGrid,
+            index: usize,
+            expected: URect,
+        }
+
+        let tile = UVec2::splat(16);
+        let cases = vec!(
+            TestCase { grid: AtlasGrid::new(tile, 4, 2), index: 5, expected: URect::new(16, 16, 32, 32) },
+            TestCase { grid: AtlasGrid::new(tile, 4, 2).column_major(), index: 5, expected: URect::new(32, 16, 48, 32) },
+            TestCase { grid: AtlasGrid::new(tile, 4, 2).with_spacing(UVec2::new(2, 4)), index: 5, expected: URect::new(18, 20, 34, 36) },
+            TestCase { grid: AtlasGrid::new(tile, 4, 2).with_margin(UVec2::new(1, 3)), index: 0, expected: URect::new(1, 3, 17, 19) },
+        );
+
+        for c in cases {
+            assert_eq!(c.grid.tile_rect(c.index), c.expected);
+        }
+    }
+
+    #[test]
+    fn partial_last_row() {
+        let grid = AtlasGrid::new(UVec2::splat(16), 4, 3).with_count(10);
+        let layout = grid.layout(UVec2::new(64, 48));
+        assert_eq!(layout.textures.len(), 10);
+        assert_eq!(layout.textures[9], URect::new(16, 32, 32, 48));
+        assert_eq!(grid.validate(UVec2::new(64, 48)), Ok(()));
+    }
+
+    #[test]
+    fn validate() {
+        struct TestCase {
+            grid: AtlasGrid,
+            image_size: UVec2,
+            expected: Result<(), AtlasGridError>,
+        }
+
+        let tile = UVec2::splat(16);
+        let cases = vec!(
+            TestCase { grid: AtlasGrid::new(tile, 4, 1), image_size: UVec2::new(64, 16), expected: Ok(()) },
+            // Leftover pixels that can't hold a whole tile are fine
+            TestCase { grid: AtlasGrid::new(tile, 4, 1), image_size: UVec2::new(70, 20), expected: Ok(()) },
+            TestCase {
+                grid: AtlasGrid::new(tile, 4, 1),
+                image_size: UVec2::new(48, 16),
+                expected: Err(AtlasGridError::TooSmall { required: UVec2::new(64, 16), actual: UVec2::new(48, 16) }),
+            },
+            TestCase {
+                grid: AtlasGrid::new(tile, 4, 1),
+                image_size: UVec2::new(64, 32),
+                expected: Err(AtlasGridError::UnusedTiles { columns: 4, rows: 2 }),
+            },
+            TestCase {
+                grid: AtlasGrid::new(tile, 3, 3).with_spacing(UVec2::splat(2)).with_margin(UVec2::splat(1)),
+                image_size: UVec2::new(53, 53),
+                expected: Ok(()),
+            },
+            TestCase {
+                grid: AtlasGrid::new(tile, 3, 3).with_spacing(UVec2::splat(2)).with_margin(UVec2::splat(1)),
+                image_size: UVec2::new(52, 53),
+                expected: Err(AtlasGridError::TooSmall { required: UVec2::new(53, 53), actual: UVec2::new(52, 53) }),
+            },
+        );
+
+        for c in cases {
+            assert_eq!(c.grid.validate(c.image_size), c.expected);
+        }
+    }
+
+    #[test]
+    fn with_padding() {
+        struct TestCase {
+            padding: u32,
+            expected_size: UVec2,
+            expected_first: URect,
+        }
+
+        let cases = vec!(
+            TestCase { padding: 0, expected_size: UVec2::new(64, 32), expected_first: URect::new(0, 0, 16, 16) },
+            TestCase { padding: 2, expected_size: UVec2::new(72, 36), expected_first: URect::new(1, 1, 17, 17) },
+            TestCase { padding: 3, expected_size: UVec2::new(76, 38), expected_first: URect::new(1, 1, 17, 17) },
+        );
+
+        for c in cases {
+            let layout = texture_atlas_layout_with_padding(UVec2::splat(16), 4, 2, c.padding);
+            assert_eq!(layout.size, c.expected_size, "padding {}", c.padding);
+            assert_eq!(layout.textures[0], c.expected_first, "padding {}", c.padding);
+        }
+    }
+}
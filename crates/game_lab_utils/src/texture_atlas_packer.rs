@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::{AssetEvent, AssetServer, Assets, Handle, LoadedFolder, RenderAssetUsages};
+use bevy::image::Image;
+use bevy::math::{URect, UVec2};
+use bevy::prelude::{error, EventReader, Res, ResMut, Resource, TextureAtlas, TextureAtlasLayout};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use crate::loading_plugin::LoadingTracker;
+
+const PIXEL_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PackSettings {
+    // Transparent gap between sprites
+    pub padding: u32,
+    // Edge pixels repeated outwards, so filtering at a sprite's border never reaches its neighbour
+    pub extrude: u32,
+    // Rows wrap before they'd go past this
+    pub max_width: u32,
+}
+
+impl Default for PackSettings {
+    fn default() -> Self {
+        Self { padding: 2, extrude: 1, max_width: 1024 }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PackError {
+    TooWide(String, u32),
+    UnsupportedFormat(String),
+    Empty(String),
+}
+
+impl Display for PackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackError::TooWide(name, width) => write!(f, "{} is {}px wide, too wide for the atlas", name, width),
+            PackError::UnsupportedFormat(name) => write!(f, "{} can't be converted to RGBA", name),
+            PackError::Empty(name) => write!(f, "{} has no pixels", name),
+        }
+    }
+}
+
+// Shelf packing, tallest first. Rects come back in input order and don't include the extrusion
+pub fn pack_rects(sizes: &[UVec2], settings: PackSettings) -> Result<(UVec2, Vec<URect>), usize> {
+    let border = settings.extrude * 2;
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|a, b| sizes[*b].y.cmp(&sizes[*a].y).then(sizes[*b].x.cmp(&sizes[*a].x)));
+
+    let mut rects = vec![URect::default(); sizes.len()];
+    let mut cursor = UVec2::splat(settings.padding);
+    let mut shelf_height = 0;
+    let mut width = 0;
+    for i in order {
+        let cell = sizes[i] + UVec2::splat(border);
+        if cell.x + settings.padding * 2 > settings.max_width {
+            return Err(i);
+        }
+        if cursor.x + cell.x + settings.padding > settings.max_width {
+            cursor = UVec2::new(settings.padding, cursor.y + shelf_height + settings.padding);
+            shelf_height = 0;
+        }
+        let min = cursor + UVec2::splat(settings.extrude);
+        rects[i] = URect::from_corners(min, min + sizes[i]);
+        cursor.x += cell.x + settings.padding;
+        shelf_height = shelf_height.max(cell.y);
+        width = width.max(cursor.x);
+    }
+    Ok((UVec2::new(width, cursor.y + shelf_height + settings.padding), rects))
+}
+
+pub struct PackedAtlas {
+    pub image: Image,
+    pub layout: TextureAtlasLayout,
+    pub names: HashMap<String, usize>,
+}
+
+// Atlas indices follow the order of `sprites`
+pub fn pack_images(sprites: &[(String, &Image)], settings: PackSettings) -> Result<PackedAtlas, PackError> {
+    let mut pixels = vec!();
+    for (name, image) in sprites {
+        let converted = if image.texture_descriptor.format == TextureFormat::Rgba8UnormSrgb {
+            Some((*image).clone())
+        } else {
+            image.convert(TextureFormat::Rgba8UnormSrgb)
+        };
+        let converted = converted.ok_or_else(|| PackError::UnsupportedFormat(name.clone()))?;
+        // blit clamps into the sprite, which needs at least one pixel to clamp to
+        if converted.size().min_element() == 0 {
+            return Err(PackError::Empty(name.clone()));
+        }
+        pixels.push(converted);
+    }
+    let sizes: Vec<UVec2> = pixels.iter().map(|p| p.size()).collect();
+    let (size, rects) = pack_rects(&sizes, settings)
+        .map_err(|i| PackError::TooWide(sprites[i].0.clone(), sizes[i].x))?;
+
+    let mut data = vec![0; size.x as usize * size.y as usize * PIXEL_SIZE];
+    let mut layout = TextureAtlasLayout::new_empty(size);
+    let mut names = HashMap::new();
+    for (i, (name, _)) in sprites.iter().enumerate() {
+        blit(&mut data, size.x, &pixels[i], rects[i], settings.extrude);
+        names.insert(name.clone(), layout.add_texture(rects[i]));
+    }
+
+    let image = Image::new(
+        Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    Ok(PackedAtlas { image, layout, names })
+}
+
+// Copies the sprite into `rect` and clamps its edges out into the extrusion around it
+fn blit(data: &mut [u8], atlas_width: u32, sprite: &Image, rect: URect, extrude: u32) {
+    let size = sprite.size().as_ivec2();
+    let extrude = extrude as i32;
+    for y in -extrude..size.y + extrude {
+        for x in -extrude..size.x + extrude {
+            let src = (y.clamp(0, size.y - 1) * size.x + x.clamp(0, size.x - 1)) as usize * PIXEL_SIZE;
+            let dst_x = (rect.min.x as i32 + x) as usize;
+            let dst_y = (rect.min.y as i32 + y) as usize;
+            let dst = (dst_y * atlas_width as usize + dst_x) * PIXEL_SIZE;
+            data[dst..dst + PIXEL_SIZE].copy_from_slice(&sprite.data[src..src + PIXEL_SIZE]);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PackedAtlasHandles {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub names: HashMap<String, usize>,
+}
+
+impl PackedAtlasHandles {
+    pub fn texture_atlas(&self, name: &str) -> Option<TextureAtlas> {
+        self.names.get(name).map(|index| TextureAtlas { layout: self.layout.clone(), index: *index })
+    }
+}
+
+// Atlases are only here once their folder has loaded and been packed
+#[derive(Resource, Default)]
+pub struct PackedAtlases {
+    folders: Vec<(String, Handle<LoadedFolder>)>,
+    atlases: HashMap<String, PackedAtlasHandles>,
+}
+
+impl PackedAtlases {
+    pub fn get(&self, key: &str) -> Option<&PackedAtlasHandles> {
+        self.atlases.get(key)
+    }
+}
+
+#[derive(Resource)]
+struct PackerFolders {
+    folders: Vec<(String, String)>,
+    settings: PackSettings,
+}
+
+// Packs folders of loose sprites into one atlas each. Sprites are named by their path in the folder
+// without the extension, e.g. "coin" or "tools/hoe", so the same file name can be in two subfolders
+#[derive(Default)]
+pub struct TextureAtlasPackerPlugin {
+    folders: Vec<(String, String)>,
+    settings: PackSettings,
+}
+
+impl TextureAtlasPackerPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_folder(mut self, key: &str, path: &str) -> Self {
+        self.folders.push((key.to_string(), path.to_string()));
+        self
+    }
+
+    pub fn with_settings(mut self, settings: PackSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+impl Plugin for TextureAtlasPackerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PackedAtlases>()
+            .insert_resource(PackerFolders { folders: self.folders.clone(), settings: self.settings })
+            .add_systems(Startup, load_folders)
+            .add_systems(Update, pack_folders);
+    }
+}
+
+fn load_folders(
+    mut packed: ResMut<PackedAtlases>,
+    mut tracker: Option<ResMut<LoadingTracker>>,
+    folders: Res<PackerFolders>,
+    asset_server: Res<AssetServer>,
+) {
+    for (key, path) in &folders.folders {
+        let handle = asset_server.load_folder(path.clone());
+        if let Some(tracker) = tracker.as_mut() {
+            tracker.track(key, handle.clone());
+        }
+        packed.folders.push((key.clone(), handle));
+    }
+}
+
+fn pack_folders(
+    mut reader: EventReader<AssetEvent<LoadedFolder>>,
+    mut packed: ResMut<PackedAtlases>,
+    mut images: ResMut<Assets<Image>>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    loaded_folders: Res<Assets<LoadedFolder>>,
+    folders: Res<PackerFolders>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in reader.read() {
+        let Some((key, handle)) = packed.folders.iter()
+            .find(|(_, handle)| event.is_loaded_with_dependencies(handle))
+            .cloned() else {
+            continue;
+        };
+        let Some(folder) = loaded_folders.get(&handle) else {
+            continue;
+        };
+
+        let Some(folder_path) = asset_server.get_path(handle.id()) else {
+            continue;
+        };
+        // Sorted by name so indices don't move around between runs
+        let mut sprites: Vec<(String, &Image)> = folder.handles.iter()
+            .filter_map(|h| {
+                let name = sprite_name(folder_path.path(), asset_server.get_path(h.id())?.path())?;
+                Some((name, images.get(h.id().try_typed::<Image>().ok()?)?))
+            })
+            .collect();
+        sprites.sort_by(|a, b| a.0.cmp(&b.0));
+
+        match pack_images(&sprites, folders.settings) {
+            Ok(atlas) => {
+                let handles = PackedAtlasHandles {
+                    image: images.add(atlas.image),
+                    layout: texture_atlas_layouts.add(atlas.layout),
+                    names: atlas.names,
+                };
+                packed.atlases.insert(key, handles);
+            },
+            Err(e) => error!("Couldn't pack {}: {}", key, e),
+        }
+    }
+}
+
+// "tools/hoe" for <folder>/tools/hoe.png, with forward slashes on every platform
+fn sprite_name(folder: &Path, file: &Path) -> Option<String> {
+    let relative = file.strip_prefix(folder).ok()?.with_extension("");
+    let parts: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TestApp;
+
+    #[test]
+    fn pack_rects() {
+        struct TestCase {
+            sizes: Vec<UVec2>,
+            settings: PackSettings,
+            expected_size: UVec2,
+            expected_rects: Vec<URect>,
+        }
+
+        let cases = vec!(
+            TestCase {
+                sizes: vec!(UVec2::new(16, 16)),
+                settings: PackSettings { padding: 0, extrude: 0, max_width: 64 },
+                expected_size: UVec2::new(16, 16),
+                expected_rects: vec!(URect::new(0, 0, 16, 16)),
+            },
+            TestCase {
+                sizes: vec!(UVec2::new(16, 16), UVec2::new(16, 32)),
+                settings: PackSettings { padding: 2, extrude: 1, max_width: 64 },
+                expected_size: UVec2::new(42, 38),
+                expected_rects: vec!(URect::new(23, 3, 39, 19), URect::new(3, 3, 19, 35)),
+            },
+            TestCase {
+                sizes: vec!(UVec2::new(16, 16), UVec2::new(16, 16), UVec2::new(16, 16)),
+                settings: PackSettings { padding: 2, extrude: 1, max_width: 44 },
+                expected_size: UVec2::new(42, 42),
+                expected_rects: vec!(URect::new(3, 3, 19, 19), URect::new(23, 3, 39, 19), URect::new(3, 23, 19, 39)),
+            },
+        );
+
+        for c in cases {
+            let (size, rects) = super::pack_rects(&c.sizes, c.settings).unwrap();
+            assert_eq!(size, c.expected_size);
+            assert_eq!(rects, c.expected_rects);
+        }
+    }
+
+    #[test]
+    fn pack_images_extrudes_edges() {
+        let red = [255, 0, 0, 255];
+        let sprite = Image::new_fill(
+            Extent3d { width: 2, height: 2, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            &red,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        let settings = PackSettings { padding: 1, extrude: 1, max_width: 64 };
+        let atlas = pack_images(&[("red".to_string(), &sprite)], settings).unwrap();
+
+        assert_eq!(atlas.names.get("red"), Some(&0));
+        assert_eq!(atlas.layout.textures[0], URect::new(2, 2, 4, 4));
+        let pixel = |x: usize, y: usize| {
+            let i = (y * atlas.image.width() as usize + x) * PIXEL_SIZE;
+            atlas.image.data[i..i + PIXEL_SIZE].to_vec()
+        };
+        // Padding stays clear, extrusion repeats the edge
+        assert_eq!(pixel(0, 0), vec!(0, 0, 0, 0));
+        assert_eq!(pixel(1, 1), red.to_vec());
+        assert_eq!(pixel(4, 4), red.to_vec());
+        assert_eq!(pixel(5, 5), vec!(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn empty_image() {
+        let sprite = Image::new(
+            Extent3d { width: 0, height: 4, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            vec!(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        let result = pack_images(&[("blank".to_string(), &sprite)], PackSettings::default());
+        assert_eq!(result.err(), Some(PackError::Empty("blank".to_string())));
+    }
+
+    #[test]
+    fn too_wide() {
+        let settings = PackSettings { padding: 2, extrude: 1, max_width: 16 };
+        assert_eq!(super::pack_rects(&[UVec2::new(8, 8), UVec2::new(16, 8)], settings), Err(1));
+    }
+
+    #[test]
+    fn sprite_names() {
+        let cases = vec!(
+            ("icons", "icons/coin.png", Some("coin")),
+            ("icons", "icons/tools/hoe.png", Some("tools/hoe")),
+            ("icons", "icons/seeds/wheat.png", Some("seeds/wheat")),
+            ("icons", "other/coin.png", None),
+        );
+        for (folder, file, expected) in cases {
+            assert_eq!(sprite_name(Path::new(folder), Path::new(file)).as_deref(), expected, "{}", file);
+        }
+    }
+
+    #[test]
+    fn packs_folder() {
+        let mut app = TestApp::with_fixtures(env!("CARGO_MANIFEST_DIR"), "test");
+        app.add_plugins(TextureAtlasPackerPlugin::new().with_folder("icons", "game://icons"));
+        assert!(app.step_until(500, |world| world.resource::<PackedAtlases>().get("icons").is_some()));
+
+        // wheat is in both seeds and crops, each keeps its own sprite
+        let atlas = app.resource::<PackedAtlases>().get("icons").unwrap().clone();
+        let mut names: Vec<_> = atlas.names.keys().cloned().collect();
+        names.sort();
+        assert_eq!(names, vec!("coin", "crops/wheat", "seeds/wheat", "tools/hoe"));
+
+        let layouts = app.resource::<Assets<TextureAtlasLayout>>();
+        let layout = layouts.get(&atlas.layout).unwrap();
+        for (name, size) in [("coin", UVec2::new(8, 8)), ("tools/hoe", UVec2::new(12, 8)), ("seeds/wheat", UVec2::splat(6)), ("crops/wheat", UVec2::splat(10))] {
+            let index = atlas.texture_atlas(name).unwrap().index;
+            assert_eq!(layout.textures[index].size(), size, "{}", name);
+        }
+        assert!(atlas.texture_atlas("wheat").is_none());
+    }
+}
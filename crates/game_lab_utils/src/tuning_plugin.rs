@@ -0,0 +1,207 @@
+use std::marker::PhantomData;
+use bevy::app::{App, Plugin, PreUpdate, Startup};
+use bevy::asset::{Asset, AssetEvent, AssetLoadFailedEvent, AssetServer, Assets, Handle};
+use bevy::prelude::{error, info, DetectChangesMut, EventReader, Res, ResMut, Resource};
+use bevy::reflect::{GetTypeRegistration, Reflect};
+use bevy_common_assets::ron::RonAssetPlugin;
+use bevy_egui::{egui, EguiContexts};
+use ron::ser::PrettyConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::debug_plugin::{debug_window, DebugKey, DebugState, Debugger};
+use crate::inspector_plugin::reflect_ui;
+use crate::internal_asset_plugin::AssetRoots;
+use crate::loading_plugin::LoadingTracker;
+
+const TUNING_KEY: DebugKey = DebugKey::new("Tools/Tuning");
+
+// Gameplay values kept in a .tuning.ron file and reloaded as it changes. Derive Asset, Resource,
+// Reflect with #[reflect(Resource)], Serialize, Deserialize, Clone, PartialEq and Default, where the
+// defaults are what the game runs with until the file loads. Read it as Res<T> and check
+// is_changed() for anything, like a timer, that holds on to a value
+pub trait Tuning: Asset + Resource + Reflect + GetTypeRegistration + Serialize + DeserializeOwned + Clone + PartialEq + Default {}
+
+impl<T: Asset + Resource + Reflect + GetTypeRegistration + Serialize + DeserializeOwned + Clone + PartialEq + Default> Tuning for T {}
+
+#[derive(Resource)]
+pub struct TuningFile<T: Tuning> {
+    pub path: String,
+    handle: Option<Handle<T>>,
+    // What the file held when it was last read or written
+    saved: Option<T>,
+    error: Option<String>,
+}
+
+impl<T: Tuning> TuningFile<T> {
+    // Changed in the inspector or tuning window since the file was read
+    pub fn is_edited(&self, current: &T) -> bool {
+        self.saved.as_ref().is_some_and(|saved| saved != current)
+    }
+
+    // The file watcher picks this up and reloads it, which leaves the values as they are
+    pub fn write(&mut self, roots: &AssetRoots, value: &T) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(value, PrettyConfig::default()).map_err(|e| e.to_string())?;
+        roots.write(&self.path, text.as_bytes()).map_err(|e| format!("Could not write {}: {}", self.path, e))?;
+        self.saved = Some(value.clone());
+        self.error = None;
+        Ok(())
+    }
+}
+
+pub struct TuningPlugin<T: Tuning> {
+    path: String,
+    marker: PhantomData<T>,
+}
+
+impl<T: Tuning> TuningPlugin<T> {
+    // e.g. TuningPlugin::<Tuning>::new("game://game.tuning.ron")
+    pub fn new(path: &str) -> Self {
+        Self { path: path.to_string(), marker: PhantomData }
+    }
+}
+
+impl<T: Tuning> Plugin for TuningPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<T>::new(&["tuning.ron"]))
+            .register_type::<T>()
+            .init_resource::<T>()
+            .insert_resource(TuningFile::<T> { path: self.path.clone(), handle: None, saved: None, error: None })
+            .add_systems(Startup, load_tuning::<T>)
+            // Before Update, so a reload is seen by every system in the same frame
+            .add_systems(PreUpdate, apply_tuning::<T>)
+            .add_debug_system(tuning_window::<T>, TUNING_KEY);
+    }
+}
+
+fn load_tuning<T: Tuning>(mut file: ResMut<TuningFile<T>>, tracker: Option<ResMut<LoadingTracker>>, asset_server: Res<AssetServer>) {
+    let handle: Handle<T> = asset_server.load(file.path.clone());
+    if let Some(mut tracker) = tracker {
+        tracker.track(&file.path, handle.clone());
+    }
+    file.handle = Some(handle);
+}
+
+fn apply_tuning<T: Tuning>(
+    mut reader: EventReader<AssetEvent<T>>,
+    mut failures: EventReader<AssetLoadFailedEvent<T>>,
+    mut file: ResMut<TuningFile<T>>,
+    mut tuning: ResMut<T>,
+    assets: Res<Assets<T>>,
+) {
+    // A bad edit keeps the last values that loaded
+    for failure in failures.read() {
+        error!("Could not load tuning {}: {}", failure.path, failure.error);
+        file.error = Some(failure.error.to_string());
+    }
+    let Some(handle) = file.handle.clone() else {
+        return;
+    };
+    for event in reader.read() {
+        if !event.is_loaded_with_dependencies(&handle) && !event.is_modified(&handle) {
+            continue;
+        }
+        let Some(loaded) = assets.get(&handle) else {
+            continue;
+        };
+        if file.saved.is_some() {
+            info!("Reloaded tuning {}", file.path);
+        }
+        *tuning = loaded.clone();
+        file.saved = Some(loaded.clone());
+        file.error = None;
+    }
+}
+
+fn tuning_window<T: Tuning>(
+    mut contexts: EguiContexts,
+    mut tuning: ResMut<T>,
+    mut file: ResMut<TuningFile<T>>,
+    roots: Option<Res<AssetRoots>>,
+    debug_state: Res<DebugState>,
+) {
+    let name = T::short_type_path();
+    debug_window(&format!("Tuning: {}", name), &debug_state).show(contexts.ctx_mut(), |ui| {
+        ui.weak(&file.path);
+        let edited = file.is_edited(&tuning);
+        match &file.error {
+            Some(e) => ui.colored_label(egui::Color32::LIGHT_RED, e),
+            None if file.saved.is_none() => ui.label("Not loaded yet, these are the defaults"),
+            None if edited => ui.colored_label(egui::Color32::YELLOW, "Edited, not written to the file"),
+            None => ui.label("Matches the file"),
+        };
+        ui.separator();
+
+        // Same as the inspector, only an edit counts as a change
+        if reflect_ui(ui, tuning.bypass_change_detection().as_partial_reflect_mut(), egui::Id::new(("tuning", name))) {
+            tuning.set_changed();
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.add_enabled(edited, egui::Button::new("Write to file")).clicked() {
+                let result = match &roots {
+                    Some(roots) => file.write(roots, &tuning),
+                    None => Err("No asset roots to find the file with".to_string()),
+                };
+                if let Err(e) = result {
+                    error!("{}", e);
+                    file.error = Some(e);
+                }
+            }
+            if ui.add_enabled(edited, egui::Button::new("Revert")).clicked() && let Some(saved) = file.saved.clone() {
+                *tuning = saved;
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use serde::Deserialize;
+    use bevy::prelude::ReflectResource;
+    use crate::test_harness::TestApp;
+    use super::*;
+
+    #[derive(Asset, Resource, Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+    #[reflect(Resource)]
+    #[serde(default)]
+    struct TestTuning {
+        speed: f32,
+        step_ms: u64,
+    }
+
+    impl Default for TestTuning {
+        fn default() -> Self {
+            Self { speed: 1.0, step_ms: 100 }
+        }
+    }
+
+    #[test]
+    fn loads_and_writes_back() {
+        let root = std::env::temp_dir().join(format!("tuning_test_{}", std::process::id()));
+        let file_path = root.join("data").join("test").join("test.tuning.ron");
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(&file_path, "(speed: 2.5)").unwrap();
+
+        let mut app = TestApp::with_asset_root(root.clone(), "test");
+        app.add_plugins(TuningPlugin::<TestTuning>::new("game://test.tuning.ron"));
+        assert_eq!(app.resource::<TestTuning>(), &TestTuning::default());
+        assert!(app.step_until(500, |world| world.resource::<TestTuning>().speed == 2.5));
+        // Anything the file leaves out keeps its default
+        assert_eq!(app.resource::<TestTuning>().step_ms, 100);
+        assert!(!app.resource::<TuningFile<TestTuning>>().is_edited(app.resource::<TestTuning>()));
+
+        app.world_mut().resource_mut::<TestTuning>().step_ms = 50;
+        let edited = app.resource::<TestTuning>().clone();
+        assert!(app.resource::<TuningFile<TestTuning>>().is_edited(&edited));
+
+        let roots = app.resource::<AssetRoots>().clone();
+        app.world_mut().resource_mut::<TuningFile<TestTuning>>().write(&roots, &edited).unwrap();
+        assert!(!app.resource::<TuningFile<TestTuning>>().is_edited(&edited));
+        let written: TestTuning = ron::from_str(&fs::read_to_string(&file_path).unwrap()).unwrap();
+        assert_eq!(written, TestTuning { speed: 2.5, step_ms: 50 });
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
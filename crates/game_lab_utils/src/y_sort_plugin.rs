@@ -0,0 +1,160 @@
+use bevy::app::{App, Plugin, PostUpdate};
+use bevy::prelude::{Changed, Component, IntoSystemConfigs, Or, Query, Res, Resource, Transform, TransformSystem};
+
+// Sorting layers are bands of Z, everything inside a band is ordered by world Y
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum SortingLayer {
+    Background,
+    Ground,
+    Shadows,
+    #[default]
+    World,
+    Foreground,
+    Overlay,
+}
+
+impl SortingLayer {
+    pub fn index(&self) -> f32 {
+        *self as u8 as f32
+    }
+}
+
+#[derive(Component, Copy, Clone, Debug, Default)]
+pub struct YSort {
+    pub layer: SortingLayer,
+    // Moves the sort point from the entity origin, e.g. down to a sprite's feet
+    pub offset: f32,
+}
+
+impl YSort {
+    pub fn new(layer: SortingLayer) -> Self {
+        Self { layer, offset: 0.0 }
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+#[derive(Resource, Copy, Clone, Debug)]
+pub struct YSortSettings {
+    // Z range given to each sorting layer
+    pub layer_depth: f32,
+    // World Y range mapped into a layer, anything outside is clamped to its edge
+    pub min_y: f32,
+    pub max_y: f32,
+}
+
+impl Default for YSortSettings {
+    fn default() -> Self {
+        Self { layer_depth: 100.0, min_y: -5000.0, max_y: 5000.0 }
+    }
+}
+
+impl YSortSettings {
+    pub fn z_for(&self, y: f32, sort: &YSort) -> f32 {
+        let range = (self.max_y - self.min_y).max(f32::EPSILON);
+        // Lower on screen means closer to the viewer, so it gets the higher Z
+        let depth = ((self.max_y - (y + sort.offset)) / range).clamp(0.0, 1.0);
+        sort.layer.index() * self.layer_depth + depth * (self.layer_depth - 1.0)
+    }
+}
+
+#[derive(Default)]
+pub struct YSortPlugin {
+    settings: YSortSettings,
+}
+
+impl YSortPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_y_range(mut self, min_y: f32, max_y: f32) -> Self {
+        self.settings.min_y = min_y;
+        self.settings.max_y = max_y;
+        self
+    }
+}
+
+impl Plugin for YSortPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings)
+            .add_systems(PostUpdate, y_sort.before(TransformSystem::TransformPropagate));
+    }
+}
+
+type YSortChanged = Or<(Changed<Transform>, Changed<YSort>)>;
+
+pub fn y_sort(settings: Res<YSortSettings>, mut query: Query<(&YSort, &mut Transform), YSortChanged>) {
+    for (sort, mut transform) in query.iter_mut() {
+        let z = settings.z_for(transform.translation.y, sort);
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Y 0..99 over a depth of 100 puts a World sprite at y on z 399 - y
+    const SETTINGS: YSortSettings = YSortSettings { layer_depth: 100.0, min_y: 0.0, max_y: 99.0 };
+    const LAYERS: [SortingLayer; 6] = [
+        SortingLayer::Background,
+        SortingLayer::Ground,
+        SortingLayer::Shadows,
+        SortingLayer::World,
+        SortingLayer::Foreground,
+        SortingLayer::Overlay,
+    ];
+
+    #[test]
+    fn z_for() {
+        struct TestCase {
+            y: f32,
+            sort: YSort,
+            expected_z: f32,
+        }
+
+        let cases = vec!(
+            TestCase { y: 0.0, sort: YSort::new(SortingLayer::World), expected_z: 399.0 },
+            TestCase { y: 49.0, sort: YSort::new(SortingLayer::World), expected_z: 350.0 },
+            TestCase { y: 99.0, sort: YSort::new(SortingLayer::World), expected_z: 300.0 },
+            // Sorting from the feet, 10 below the origin, puts it in front of one standing on the origin's row
+            TestCase { y: 49.0, sort: YSort::new(SortingLayer::World).with_offset(-10.0), expected_z: 360.0 },
+            TestCase { y: 49.0, sort: YSort::new(SortingLayer::World).with_offset(10.0), expected_z: 340.0 },
+            // Outside min_y..max_y sticks to the edge of the band
+            TestCase { y: -500.0, sort: YSort::new(SortingLayer::World), expected_z: 399.0 },
+            TestCase { y: 500.0, sort: YSort::new(SortingLayer::World), expected_z: 300.0 },
+            TestCase { y: 95.0, sort: YSort::new(SortingLayer::World).with_offset(10.0), expected_z: 300.0 },
+            TestCase { y: -500.0, sort: YSort::new(SortingLayer::Background), expected_z: 99.0 },
+            TestCase { y: 500.0, sort: YSort::new(SortingLayer::Overlay), expected_z: 500.0 },
+        );
+
+        for c in cases {
+            let z = SETTINGS.z_for(c.y, &c.sort);
+            assert!((z - c.expected_z).abs() < 0.001, "{:?} at {} is {} not {}", c.sort, c.y, z, c.expected_z);
+        }
+    }
+
+    #[test]
+    fn layers_keep_to_their_band() {
+        for settings in [SETTINGS, YSortSettings::default()] {
+            for (i, layer) in LAYERS.iter().enumerate() {
+                let band = i as f32 * settings.layer_depth..(i + 1) as f32 * settings.layer_depth;
+                for y in [-1.0e6, settings.min_y, 0.5 * settings.max_y, settings.max_y, 1.0e6] {
+                    let z = settings.z_for(y, &YSort::new(*layer));
+                    assert!(band.contains(&z), "{:?} at {} is {}, outside {:?}", layer, y, z, band);
+                }
+            }
+        }
+
+        // The highest a World sprite can go is still under the lowest Overlay one
+        let world = SETTINGS.z_for(-1.0e6, &YSort::new(SortingLayer::World));
+        let overlay = SETTINGS.z_for(1.0e6, &YSort::new(SortingLayer::Overlay));
+        assert!(world < overlay);
+    }
+}
@@ -1,13 +1,16 @@
 use crate::map_plugin::{MapMeta, Tile};
 use crate::player_plugin::Player;
+use crate::state::GameState;
 use crate::utils::{bfs, get_ray_vec, vec_to_nearest};
 use bevy::app::App;
-use bevy::asset::AssetServer;
 use bevy::math::{Rect, Vec2, Vec3, vec2};
 use bevy::prelude::{
-    Camera, Color, Commands, Component, Event, EventReader, EventWriter, GlobalTransform, Plugin,
-    Query, Res, Single, Sprite, Startup, Transform, Update, Window, With,
+    Camera, Color, Commands, Component, Condition, Event, EventReader, EventWriter,
+    GlobalTransform, IntoSystemConfigs, Plugin, Query, Res, Single, Sprite, Startup, Transform,
+    Update, Window, With, in_state,
 };
+use game_lab_utils::asset_manifest_plugin::ImageAssets;
+use game_lab_utils::y_sort_plugin::{SortingLayer, YSort};
 
 #[derive(Component)]
 struct Cursor;
@@ -21,7 +24,12 @@ impl Plugin for CursorPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<HighlightEvent>()
             .add_systems(Startup, setup_cursor)
-            .add_systems(Update, (update_mouse_box, highlight_tiles));
+            // The editor snaps to tiles with the same cursor
+            .add_systems(
+                Update,
+                update_mouse_box.run_if(in_state(GameState::Playing).or(in_state(GameState::Editing))),
+            )
+            .add_systems(Update, highlight_tiles.run_if(in_state(GameState::Playing)));
     }
 }
 
@@ -31,18 +39,24 @@ impl CursorPlugin {
     }
 }
 
-fn setup_cursor(mut commands: Commands, mut window: Single<&mut Window>, asset_server: Res<AssetServer>) {
+fn setup_cursor(
+    mut commands: Commands,
+    mut window: Single<&mut Window>,
+    images: Res<ImageAssets>,
+) {
     window.cursor_options.visible = false;
+    let image = images.image("cursor");
 
     commands.spawn((
         Cursor,
         Sprite {
-            image: asset_server.load("internal/sprout-lands/ui/icons/select.png"),
+            image,
             custom_size: Some(Vec2::new(32.0, 32.0)),
             rect: Some(Rect::new(0.0, 0.0, 32.0, 32.0)),
             ..Default::default()
         },
         Transform::from_xyz(0.0, 0.0, 0.0),
+        YSort::new(SortingLayer::Overlay),
     ));
 }
 
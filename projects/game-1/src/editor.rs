@@ -0,0 +1,799 @@
+use crate::levels::{Level, level_file};
+use crate::map_plugin::{FLOOR_TILE, LevelChangeEvent, MapMeta, MapResources};
+use crate::state::GameState;
+use crate::utils::{bfs, get_ray_vec, vec_to_nearest};
+use bevy::app::{App, Plugin, Update};
+use bevy::color::palettes::css::WHITE;
+use bevy::input::ButtonInput;
+use bevy::math::{Isometry2d, Vec2};
+use bevy::prelude::{
+    Assets, Camera, EventWriter, Gizmos, GlobalTransform, IntoSystemConfigs, KeyCode, MouseButton,
+    NextState, Res, ResMut, Resource, Single, State, TextureAtlasLayout, Window, error, in_state,
+};
+use bevy_egui::{EguiContexts, egui};
+use game_lab_utils::debug_plugin::{DebugKey, DebugState, Debugger, debug_window};
+use game_lab_utils::internal_asset_plugin::AssetRoots;
+use ron::ser::PrettyConfig;
+use std::collections::VecDeque;
+
+// Turning this on opens the editor over the level being played, turning it off goes back to it
+pub const EDITOR_KEY: DebugKey = DebugKey::new("Tools/Level Editor").with_hotkey(KeyCode::F5);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tool {
+    Paint,
+    Fill,
+    Rectangle,
+    Spawn,
+    Coin,
+}
+
+impl Tool {
+    const ALL: [Tool; 5] = [
+        Tool::Paint,
+        Tool::Fill,
+        Tool::Rectangle,
+        Tool::Spawn,
+        Tool::Coin,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Tool::Paint => "Paint",
+            Tool::Fill => "Fill",
+            Tool::Rectangle => "Rectangle",
+            Tool::Spawn => "Spawn",
+            Tool::Coin => "Coin",
+        }
+    }
+}
+
+// Snapshots of the level from before each edit
+#[derive(Default)]
+struct History {
+    undo: Vec<Level>,
+    redo: Vec<Level>,
+}
+
+impl History {
+    fn push(&mut self, before: Level) {
+        self.undo.push(before);
+        self.redo.clear();
+    }
+
+    fn undo(&mut self, level: &mut Level) -> bool {
+        let Some(before) = self.undo.pop() else {
+            return false;
+        };
+        self.redo.push(std::mem::replace(level, before));
+        true
+    }
+
+    fn redo(&mut self, level: &mut Level) -> bool {
+        let Some(after) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push(std::mem::replace(level, after));
+        true
+    }
+}
+
+#[derive(Resource)]
+pub struct LevelEditor {
+    level: Level,
+    // What was being played, put back when the editor is closed without a playtest
+    played: Level,
+    // The coins picked up on it, they'd be collected again if the map came back with all of them
+    collected: Vec<(i32, i32)>,
+    history: History,
+    tool: Tool,
+    tile: usize,
+    name: String,
+    status: Option<Result<String, String>>,
+    // The level from before the click or drag under way, so all of it is one undo
+    stroke: Option<Level>,
+    // Where the drag started and where it is now
+    drag: Option<((i32, i32), (i32, i32))>,
+}
+
+impl Default for LevelEditor {
+    fn default() -> Self {
+        Self {
+            level: Level::from_tiles(vec![]),
+            played: Level::from_tiles(vec![]),
+            collected: vec![],
+            history: History::default(),
+            tool: Tool::Paint,
+            tile: FLOOR_TILE,
+            name: "custom".to_string(),
+            status: None,
+            stroke: None,
+            drag: None,
+        }
+    }
+}
+
+impl LevelEditor {
+    fn start(&mut self, level: Level) {
+        self.played = level.clone();
+        self.level = level;
+        self.history = History::default();
+        self.stroke = None;
+        self.drag = None;
+    }
+
+    // Coming back to the level that was left keeps the edits and history, anything else starts over
+    fn enter(&mut self, level: Level) {
+        if level != self.played {
+            self.start(level);
+        }
+    }
+
+    // A level that can't be finished isn't written out, it would be played as one of the game's own
+    fn save(&self, roots: &AssetRoots) -> Result<String, String> {
+        if let Some(problem) = problems(&self.level).first() {
+            return Err(format!("Not saved: {}", problem));
+        }
+        let path = level_file(self.name.trim());
+        let text = ron::ser::to_string_pretty(&self.level, PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        roots
+            .write(&path, text.as_bytes())
+            .map_err(|e| format!("Could not write {}: {}", path, e))?;
+        Ok(format!("Saved {}", path))
+    }
+
+    // Goes on the history like an edit, so it can be undone
+    fn open(&mut self, roots: &AssetRoots) -> Result<String, String> {
+        let path = level_file(self.name.trim());
+        let level = Level::read(roots, self.name.trim())?;
+        if !level.same_size(&self.level) {
+            return Err(format!("{} isn't the size of the map", path));
+        }
+        let before = std::mem::replace(&mut self.level, level);
+        if before != self.level {
+            self.history.push(before);
+        }
+        Ok(format!("Opened {}", path))
+    }
+}
+
+pub struct EditorPlugin {}
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelEditor>()
+            .add_systems(Update, sync_editor_mode)
+            .add_systems(
+                Update,
+                (edit_level, show_edited_level, draw_editor_overlay)
+                    .chain()
+                    .run_if(in_state(GameState::Editing)),
+            )
+            .add_debug_system(editor_window, EDITOR_KEY);
+    }
+}
+
+impl EditorPlugin {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+// Coins only go on floor, so painting anything else over one takes it away
+fn paint(level: &mut Level, at: (i32, i32), tile: usize) -> bool {
+    if level.tile(at).is_none_or(|current| current == tile) {
+        return false;
+    }
+    level.tiles[at.1 as usize][at.0 as usize] = tile;
+    level.coins.retain(|coin| tile == FLOOR_TILE || *coin != at);
+    true
+}
+
+// Everything joined to `at` by its sides with the same tile
+fn fill(level: &mut Level, at: (i32, i32), tile: usize) -> bool {
+    let Some(target) = level.tile(at).filter(|target| *target != tile) else {
+        return false;
+    };
+    let mut queue = VecDeque::from([at]);
+    while let Some(next) = queue.pop_front() {
+        if level.tile(next) != Some(target) {
+            continue;
+        }
+        paint(level, next, tile);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            queue.push_back((next.0 + dx, next.1 + dy));
+        }
+    }
+    true
+}
+
+// Corners can be given either way round
+fn fill_rect(level: &mut Level, from: (i32, i32), to: (i32, i32), tile: usize) -> bool {
+    let mut changed = false;
+    for y in from.1.min(to.1)..=from.1.max(to.1) {
+        for x in from.0.min(to.0)..=from.0.max(to.0) {
+            changed |= paint(level, (x, y), tile);
+        }
+    }
+    changed
+}
+
+fn toggle_coin(level: &mut Level, at: (i32, i32)) -> bool {
+    if !level.is_floor(at) {
+        return false;
+    }
+    match level.coins.iter().position(|coin| *coin == at) {
+        Some(i) => {
+            level.coins.remove(i);
+        }
+        None => level.coins.push(at),
+    }
+    true
+}
+
+// What a click does, rectangles wait for the drag to end
+fn apply_tool(level: &mut Level, tool: Tool, at: (i32, i32), tile: usize) -> bool {
+    match tool {
+        Tool::Paint => paint(level, at, tile),
+        Tool::Fill => fill(level, at, tile),
+        Tool::Rectangle => false,
+        Tool::Spawn if level.is_floor(at) && level.spawn != at => {
+            level.spawn = at;
+            true
+        }
+        Tool::Spawn => false,
+        Tool::Coin => toggle_coin(level, at),
+    }
+}
+
+// Why the level couldn't be finished if it were played
+fn problems(level: &Level) -> Vec<&'static str> {
+    if !level.is_floor(level.spawn) {
+        return vec!["The spawn isn't on a floor tile"];
+    }
+    let mut problems = vec![];
+    if level.coins.is_empty() {
+        problems.push("There are no coins to collect");
+    }
+    let mask: Vec<Vec<usize>> = level
+        .tiles
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tile| if *tile == FLOOR_TILE { 0 } else { 1 })
+                .collect()
+        })
+        .collect();
+    if level
+        .coins
+        .iter()
+        .any(|coin| bfs(&mask, level.spawn, *coin).is_empty())
+    {
+        problems.push("Some coins can't be reached from the spawn");
+    }
+    problems
+}
+
+// Follows the debug toggle, only play can be left for the editor
+fn sync_editor_mode(
+    debug_state: Res<DebugState>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut editor: ResMut<LevelEditor>,
+    mut map_meta: ResMut<MapMeta>,
+    mut writer: EventWriter<LevelChangeEvent>,
+    mut window: Single<&mut Window>,
+) {
+    let open = debug_state.enabled && debug_state.keys.get(EDITOR_KEY.path()) == Some(&true);
+    match (state.get(), open) {
+        (GameState::Playing, true) => {
+            // Sent so the coins already picked up come back
+            editor.enter(map_meta.level());
+            editor.collected = std::mem::take(&mut map_meta.collected);
+            writer.send(LevelChangeEvent);
+            next_state.set(GameState::Editing);
+            window.cursor_options.visible = true;
+        }
+        (GameState::Editing, false) => {
+            map_meta.set_level(editor.played.clone());
+            map_meta.collected = std::mem::take(&mut editor.collected);
+            writer.send(LevelChangeEvent);
+            next_state.set(GameState::Playing);
+            window.cursor_options.visible = false;
+        }
+        _ => {}
+    }
+}
+
+fn edit_level(
+    editor: ResMut<LevelEditor>,
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    map_meta: Res<MapMeta>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    window: Single<&Window>,
+) {
+    let editor = editor.into_inner();
+    let ctx = contexts.ctx_mut();
+    let index =
+        map_meta.translate_transform_to_index(vec_to_nearest(get_ray_vec(camera, window), 32.0));
+    let hovered = (index != -1).then(|| map_meta.translate_index_to_coords(index));
+
+    if !ctx.wants_keyboard_input()
+        && keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        if keys.just_pressed(KeyCode::KeyY) || (shift && keys.just_pressed(KeyCode::KeyZ)) {
+            editor.history.redo(&mut editor.level);
+        } else if keys.just_pressed(KeyCode::KeyZ) {
+            editor.history.undo(&mut editor.level);
+        }
+    }
+
+    if mouse.just_pressed(MouseButton::Left)
+        && !ctx.is_pointer_over_area()
+        && let Some(at) = hovered
+    {
+        editor.stroke = Some(editor.level.clone());
+        editor.drag = Some((at, at));
+        apply_tool(&mut editor.level, editor.tool, at, editor.tile);
+    }
+    if mouse.pressed(MouseButton::Left)
+        && let Some((start, _)) = editor.drag
+        && let Some(at) = hovered
+    {
+        editor.drag = Some((start, at));
+        if editor.tool == Tool::Paint {
+            paint(&mut editor.level, at, editor.tile);
+        }
+    }
+    if mouse.just_released(MouseButton::Left)
+        && let Some(before) = editor.stroke.take()
+    {
+        if let Some((start, end)) = editor.drag.take()
+            && editor.tool == Tool::Rectangle
+        {
+            fill_rect(&mut editor.level, start, end, editor.tile);
+        }
+        if before != editor.level {
+            editor.history.push(before);
+        }
+    }
+}
+
+// The map is the level as it's edited, so tiles, coins and the player all show as they'd play
+fn show_edited_level(
+    editor: Res<LevelEditor>,
+    mut map_meta: ResMut<MapMeta>,
+    mut writer: EventWriter<LevelChangeEvent>,
+) {
+    if map_meta.level() != editor.level {
+        map_meta.set_level(editor.level.clone());
+        writer.send(LevelChangeEvent);
+    }
+}
+
+// The tiles a rectangle covers once it's let go
+fn draw_editor_overlay(mut gizmos: Gizmos, editor: Res<LevelEditor>, map_meta: Res<MapMeta>) {
+    if editor.tool != Tool::Rectangle {
+        return;
+    }
+    if let Some((start, end)) = editor.drag {
+        let a = map_meta.translate_coords_to_transform(start);
+        let b = map_meta.translate_coords_to_transform(end);
+        gizmos.rect_2d(
+            Isometry2d::from_translation((a + b) / 2.0),
+            (a - b).abs() + Vec2::splat(32.0),
+            WHITE,
+        );
+    }
+}
+
+fn editor_window(
+    mut contexts: EguiContexts,
+    editor: ResMut<LevelEditor>,
+    mut debug_state: ResMut<DebugState>,
+    map_resources: Res<MapResources>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    roots: Option<Res<AssetRoots>>,
+) {
+    let editor = editor.into_inner();
+    let texture = contexts.add_image(map_resources.tile_map_handle.clone());
+    let mut playtest = false;
+    debug_window("Level Editor", &debug_state).show(contexts.ctx_mut(), |ui| {
+        ui.horizontal_wrapped(|ui| {
+            for tool in Tool::ALL {
+                ui.selectable_value(&mut editor.tool, tool, tool.label());
+            }
+        });
+        if let Some(layout) = layouts.get(&map_resources.atlas_handle) {
+            tile_palette(ui, editor, texture, layout);
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!editor.history.undo.is_empty(), egui::Button::new("Undo"))
+                .clicked()
+            {
+                editor.history.undo(&mut editor.level);
+            }
+            if ui
+                .add_enabled(!editor.history.redo.is_empty(), egui::Button::new("Redo"))
+                .clicked()
+            {
+                editor.history.redo(&mut editor.level);
+            }
+        });
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut editor.name);
+        });
+        ui.weak(level_file(editor.name.trim()));
+        ui.horizontal(|ui| {
+            let Some(roots) = &roots else {
+                ui.label("No asset roots to save to");
+                return;
+            };
+            let named = !editor.name.trim().is_empty();
+            let status = if ui.add_enabled(named, egui::Button::new("Save")).clicked() {
+                Some(editor.save(roots))
+            } else if ui.add_enabled(named, egui::Button::new("Open")).clicked() {
+                Some(editor.open(roots))
+            } else {
+                None
+            };
+            if let Some(status) = status {
+                if let Err(e) = &status {
+                    error!("{}", e);
+                }
+                editor.status = Some(status);
+            }
+        });
+        match &editor.status {
+            Some(Ok(status)) => {
+                ui.label(status);
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::LIGHT_RED, e);
+            }
+            None => {}
+        }
+        ui.separator();
+
+        let problems = problems(&editor.level);
+        for problem in &problems {
+            ui.colored_label(egui::Color32::YELLOW, *problem);
+        }
+        playtest = ui
+            .add_enabled(problems.is_empty(), egui::Button::new("Playtest"))
+            .clicked();
+    });
+
+    // Closing the editor goes back to what was being played, which is now the edit
+    if playtest {
+        editor.played = editor.level.clone();
+        editor.collected.clear();
+        debug_state
+            .keys
+            .insert(EDITOR_KEY.path().to_string(), false);
+    }
+}
+
+// Every tile in the map's sheet, picking one goes back to painting
+fn tile_palette(
+    ui: &mut egui::Ui,
+    editor: &mut LevelEditor,
+    texture: egui::TextureId,
+    layout: &TextureAtlasLayout,
+) {
+    let size = layout.size.as_vec2();
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing = egui::vec2(2.0, 2.0);
+        for (index, rect) in layout.textures.iter().enumerate() {
+            let min = rect.min.as_vec2() / size;
+            let max = rect.max.as_vec2() / size;
+            let image = egui::Image::new(egui::load::SizedTexture::new(texture, [24.0, 24.0])).uv(
+                egui::Rect::from_min_max(egui::pos2(min.x, min.y), egui::pos2(max.x, max.y)),
+            );
+            let button = ui.add(egui::ImageButton::new(image).selected(editor.tile == index));
+            if button.on_hover_text(index.to_string()).clicked() {
+                editor.tile = index;
+                if matches!(editor.tool, Tool::Spawn | Tool::Coin) {
+                    editor.tool = Tool::Paint;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::level_to_map;
+    use crate::map_plugin::MapGenerator;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::window::Window;
+
+    #[test]
+    fn tools() {
+        struct TestCase {
+            tool: Tool,
+            from: (i32, i32),
+            to: (i32, i32),
+            tile: usize,
+            changed_tiles: usize,
+            coins: usize,
+            spawn: (i32, i32),
+            problems: usize,
+        }
+
+        // Level 1 is a ring of walls around 13x13 floor, with a coin on all of it
+        let cases = vec![
+            TestCase {
+                tool: Tool::Paint,
+                from: (3, 3),
+                to: (3, 3),
+                tile: 4,
+                changed_tiles: 1,
+                coins: 168,
+                spawn: (1, 1),
+                problems: 0,
+            },
+            TestCase {
+                tool: Tool::Paint,
+                from: (3, 3),
+                to: (3, 3),
+                tile: FLOOR_TILE,
+                changed_tiles: 0,
+                coins: 169,
+                spawn: (1, 1),
+                problems: 0,
+            },
+            TestCase {
+                tool: Tool::Paint,
+                from: (-1, 3),
+                to: (-1, 3),
+                tile: 4,
+                changed_tiles: 0,
+                coins: 169,
+                spawn: (1, 1),
+                problems: 0,
+            },
+            TestCase {
+                tool: Tool::Fill,
+                from: (3, 3),
+                to: (3, 3),
+                tile: 4,
+                changed_tiles: 169,
+                coins: 0,
+                spawn: (1, 1),
+                problems: 1,
+            },
+            TestCase {
+                tool: Tool::Fill,
+                from: (0, 0),
+                to: (0, 0),
+                tile: 4,
+                changed_tiles: 1,
+                coins: 169,
+                spawn: (1, 1),
+                problems: 0,
+            },
+            TestCase {
+                tool: Tool::Rectangle,
+                from: (2, 2),
+                to: (4, 5),
+                tile: 4,
+                changed_tiles: 12,
+                coins: 157,
+                spawn: (1, 1),
+                problems: 0,
+            },
+            TestCase {
+                tool: Tool::Rectangle,
+                from: (4, 5),
+                to: (2, 2),
+                tile: 4,
+                changed_tiles: 12,
+                coins: 157,
+                spawn: (1, 1),
+                problems: 0,
+            },
+            // A wall across the map cuts the spawn off from the coins below it
+            TestCase {
+                tool: Tool::Rectangle,
+                from: (0, 2),
+                to: (14, 2),
+                tile: 4,
+                changed_tiles: 15,
+                coins: 156,
+                spawn: (1, 1),
+                problems: 1,
+            },
+            TestCase {
+                tool: Tool::Spawn,
+                from: (5, 5),
+                to: (5, 5),
+                tile: 4,
+                changed_tiles: 0,
+                coins: 169,
+                spawn: (5, 5),
+                problems: 0,
+            },
+            TestCase {
+                tool: Tool::Spawn,
+                from: (0, 0),
+                to: (0, 0),
+                tile: 4,
+                changed_tiles: 0,
+                coins: 169,
+                spawn: (1, 1),
+                problems: 0,
+            },
+            TestCase {
+                tool: Tool::Coin,
+                from: (5, 5),
+                to: (5, 5),
+                tile: 4,
+                changed_tiles: 0,
+                coins: 168,
+                spawn: (1, 1),
+                problems: 0,
+            },
+            TestCase {
+                tool: Tool::Coin,
+                from: (0, 0),
+                to: (0, 0),
+                tile: 4,
+                changed_tiles: 0,
+                coins: 169,
+                spawn: (1, 1),
+                problems: 0,
+            },
+        ];
+
+        for case in cases {
+            let original = Level::from_tiles(level_to_map(1));
+            let mut level = original.clone();
+            let changed = match case.tool {
+                Tool::Rectangle => fill_rect(&mut level, case.from, case.to, case.tile),
+                tool => apply_tool(&mut level, tool, case.from, case.tile),
+            };
+            let changed_tiles = level
+                .tiles
+                .iter()
+                .flatten()
+                .zip(original.tiles.iter().flatten())
+                .filter(|(a, b)| a != b)
+                .count();
+
+            assert_eq!(
+                changed,
+                level != original,
+                "{:?} at {:?}",
+                case.tool,
+                case.from
+            );
+            assert_eq!(
+                changed_tiles, case.changed_tiles,
+                "{:?} at {:?}",
+                case.tool, case.from
+            );
+            assert_eq!(
+                level.coins.len(),
+                case.coins,
+                "{:?} at {:?}",
+                case.tool,
+                case.from
+            );
+            assert_eq!(
+                level.spawn, case.spawn,
+                "{:?} at {:?}",
+                case.tool, case.from
+            );
+            assert_eq!(
+                problems(&level).len(),
+                case.problems,
+                "{:?} at {:?}",
+                case.tool,
+                case.from
+            );
+        }
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let original = Level::from_tiles(level_to_map(1));
+        let mut level = original.clone();
+        let mut history = History::default();
+
+        history.push(level.clone());
+        paint(&mut level, (3, 3), 4);
+        let painted = level.clone();
+        history.push(level.clone());
+        fill(&mut level, (5, 5), 4);
+
+        assert!(history.undo(&mut level));
+        assert_eq!(level, painted);
+        assert!(history.undo(&mut level));
+        assert_eq!(level, original);
+        assert!(!history.undo(&mut level));
+        assert!(history.redo(&mut level));
+        assert_eq!(level, painted);
+
+        // A new edit drops what could have been redone
+        history.push(level.clone());
+        toggle_coin(&mut level, (5, 5));
+        assert!(!history.redo(&mut level));
+        assert!(history.undo(&mut level));
+        assert_eq!(level, painted);
+    }
+
+    #[test]
+    fn reopening_keeps_edits() {
+        let mut editor = LevelEditor::default();
+        editor.enter(Level::from_tiles(level_to_map(1)));
+        editor.history.push(editor.level.clone());
+        paint(&mut editor.level, (3, 3), 4);
+        let edited = editor.level.clone();
+
+        // Closed without a playtest and opened again on the same level
+        editor.enter(Level::from_tiles(level_to_map(1)));
+        assert_eq!(editor.level, edited);
+        assert!(editor.history.undo(&mut editor.level));
+
+        // The game moved on to another level
+        editor.enter(Level::from_tiles(level_to_map(2)));
+        assert_eq!(editor.level, Level::from_tiles(level_to_map(2)));
+        assert!(!editor.history.undo(&mut editor.level));
+    }
+
+    #[test]
+    fn save_needs_a_playable_level() {
+        let root = std::env::temp_dir().join(format!("editor_test_{}", std::process::id()));
+        let roots = AssetRoots::for_game(root.clone(), "game1");
+        let mut editor = LevelEditor::default();
+        editor.start(Level::from_tiles(level_to_map(1)));
+
+        assert!(editor.save(&roots).is_ok());
+        assert!(roots.exists(&level_file("custom")));
+
+        editor.name = "empty".to_string();
+        editor.level.coins.clear();
+        assert!(editor.save(&roots).is_err());
+        assert!(!roots.exists(&level_file("empty")));
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn closing_keeps_collected_coins() {
+        let mut app = App::new();
+        app.add_plugins(MapGenerator::new(level_to_map(1)))
+            .init_resource::<LevelEditor>()
+            .init_resource::<DebugState>()
+            .init_resource::<NextState<GameState>>();
+        app.world_mut().spawn(Window::default());
+        let collected = vec![(1, 1), (2, 1)];
+        app.world_mut().resource_mut::<MapMeta>().collected = collected.clone();
+
+        // Opened, so every coin is back to be edited
+        app.insert_resource(State::new(GameState::Playing));
+        let mut debug_state = app.world_mut().resource_mut::<DebugState>();
+        debug_state.enabled = true;
+        debug_state.keys.insert(EDITOR_KEY.path().to_string(), true);
+        app.world_mut().run_system_once(sync_editor_mode).unwrap();
+        assert!(app.world().resource::<MapMeta>().collected.is_empty());
+
+        // Closed without a playtest, the ones picked up stay gone
+        app.insert_resource(State::new(GameState::Editing));
+        app.world_mut()
+            .resource_mut::<DebugState>()
+            .keys
+            .insert(EDITOR_KEY.path().to_string(), false);
+        app.world_mut().run_system_once(sync_editor_mode).unwrap();
+        assert_eq!(app.world().resource::<MapMeta>().collected, collected);
+    }
+}
@@ -1,20 +1,41 @@
-use crate::levels::level_to_map;
-use crate::map_plugin::{LevelChangeEvent, MapMeta, TileCreationEvent};
-use crate::player_plugin::PlayerPositionUpdated;
+use crate::levels::load_level;
+use crate::map_plugin::{
+    FLOOR_TILE, LevelChangeEvent, MapMeta, TileCreationEvent, generate_sprites,
+};
+use crate::player_plugin::{Player, PlayerPositionUpdated};
+use crate::state::GameState;
 use bevy::app::{App, Startup, Update};
-use bevy::asset::AssetServer;
 use bevy::math::{Rect, Vec2, vec3};
 use bevy::prelude::{
-    Camera2d, Commands, Component, Entity, EventReader, EventWriter, Plugin, Query, Res, ResMut,
-    Resource, Sprite, Text, TextFont, Transform, With, default,
+    Camera2d, Commands, Component, Entity, EventReader, EventWriter, In, IntoSystemConfigs,
+    NextState, OnEnter, Plugin, Query, Res, ResMut, Resource, Single, Sprite, Text, TextFont,
+    Transform, With, default, in_state,
+};
+use bevy::time::{Time, Timer, TimerMode};
+use game_lab_utils::asset_manifest_plugin::ImageAssets;
+use game_lab_utils::console_plugin::{
+    ArgKind, ConsoleApp, ConsoleArgs, ConsoleCommand, ConsoleResult,
 };
+use game_lab_utils::game_state_plugin::{TransitionRequest, spawn_banner};
+use game_lab_utils::internal_asset_plugin::AssetRoots;
+use game_lab_utils::inventory_plugin::{Inventory, ItemRegistry};
+use game_lab_utils::save_plugin::{AUTOSAVE_SLOT, SaveRequest};
+use game_lab_utils::y_sort_plugin::{SortingLayer, YSort};
+use std::time::Duration;
+
+const LEVEL_BANNER_TIME: Duration = Duration::from_millis(1200);
+pub const LEVEL_COUNT: i32 = 3;
 
 #[derive(Resource, Default)]
 pub struct Game {
     coins: i32,
-    level: i32,
+    pub level: i32,
 }
 
+// How long the level banner shows before play carries on
+#[derive(Resource)]
+struct LevelCompleteTimer(Timer);
+
 #[derive(Component)]
 struct Coin {
     index: i32,
@@ -28,13 +49,34 @@ pub struct GamePlugin {}
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_world)
-            .add_systems(Update, (clear_coins, create_coins))
-            .add_systems(Update, (update_level, text_update_system, coin_collected));
+            .add_systems(
+                Update,
+                (
+                    (clear_coins, create_coins).chain().after(generate_sprites),
+                    text_update_system,
+                ),
+            )
+            .add_systems(
+                Update,
+                (coin_collected, remember_collected_coins).run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::LevelComplete), update_level)
+            .add_systems(Update, finish_level.run_if(in_state(GameState::LevelComplete)))
+            .add_console_command(
+                ConsoleCommand::new("level", "Jumps to a level").arg("level", ArgKind::Int),
+                jump_to_level,
+            );
     }
 }
 
-fn setup_world(mut commands: Commands, map_meta: Res<MapMeta>) {
+fn setup_world(
+    mut commands: Commands,
+    mut map_meta: ResMut<MapMeta>,
+    roots: Option<Res<AssetRoots>>,
+) {
     commands.insert_resource(Game { level: 1, coins: 0 });
+    // The map plugin starts on the built in level 1, the tiles are only drawn once Update runs
+    map_meta.set_level(load_level(roots.as_deref(), 1));
 
     let pos = map_meta.get_center_point();
     commands.spawn((Camera2d, Transform::from_xyz(pos.x, pos.y, 0.0)));
@@ -51,12 +93,13 @@ fn setup_world(mut commands: Commands, map_meta: Res<MapMeta>) {
 fn clear_coins(
     mut commands: Commands,
     mut game: ResMut<Game>,
-    reader: EventReader<LevelChangeEvent>,
+    mut reader: EventReader<LevelChangeEvent>,
     query: Query<Entity, With<Coin>>,
 ) {
     if reader.is_empty() {
         return;
     }
+    reader.clear();
     for entity in query.iter() {
         commands.entity(entity).despawn();
     }
@@ -67,11 +110,14 @@ fn create_coins(
     mut reader: EventReader<TileCreationEvent>,
     mut commands: Commands,
     mut game: ResMut<Game>,
-    asset_server: Res<AssetServer>,
+    images: Res<ImageAssets>,
+    map_meta: Res<MapMeta>,
 ) {
-    let coin_handle = asset_server.load("internal/dungeon-stuff/objects/coins.png");
+    let coin_handle = images.image("coins");
     for event in reader.read() {
-        if event.0 != 47 {
+        // A hand edited level file could put one in a wall
+        let coords = map_meta.translate_index_to_coords(event.2);
+        if event.0 != FLOOR_TILE || !map_meta.coins.contains(&coords) || map_meta.collected.contains(&coords) {
             continue;
         }
         let pos = event.1;
@@ -84,31 +130,70 @@ fn create_coins(
                 ..Default::default()
             },
             Transform::from_translation(vec3(pos.x, pos.y, 1.0)),
+            YSort::new(SortingLayer::World),
         ));
         game.coins += 1;
     }
 }
 
+// Runs behind the fade so the map swap is never seen
 fn update_level(
+    mut commands: Commands,
     mut game: ResMut<Game>,
     mut map_meta: ResMut<MapMeta>,
     mut writer: EventWriter<LevelChangeEvent>,
+    mut save_writer: EventWriter<SaveRequest>,
+    roots: Option<Res<AssetRoots>>,
 ) {
-    if game.coins == 0 {
-        game.level += 1;
-        map_meta.level_data = level_to_map(game.level);
-        writer.send(LevelChangeEvent);
-    }
-    if game.level == 4 {
+    game.level += 1;
+    if game.level > LEVEL_COUNT {
         game.level = 1;
-        map_meta.level_data = level_to_map(game.level);
-        writer.send(LevelChangeEvent);
+    }
+    map_meta.set_level(load_level(roots.as_deref(), game.level));
+    writer.send(LevelChangeEvent);
+    save_writer.send(SaveRequest(AUTOSAVE_SLOT));
+
+    spawn_banner(&mut commands, GameState::LevelComplete, &format!("Level {}", game.level));
+    commands.insert_resource(LevelCompleteTimer(Timer::new(LEVEL_BANNER_TIME, TimerMode::Once)));
+}
+
+// Skips the banner, the map plugin rebuilds tiles and coins from the event like a finished level
+fn jump_to_level(
+    In(args): In<ConsoleArgs>,
+    mut game: ResMut<Game>,
+    mut map_meta: ResMut<MapMeta>,
+    mut writer: EventWriter<LevelChangeEvent>,
+    roots: Option<Res<AssetRoots>>,
+) -> ConsoleResult {
+    let level = args.int(0) as i32;
+    if !(1..=LEVEL_COUNT).contains(&level) {
+        return Err(format!("Levels go from 1 to {}", LEVEL_COUNT));
+    }
+    game.level = level;
+    map_meta.set_level(load_level(roots.as_deref(), level));
+    writer.send(LevelChangeEvent);
+    Ok(format!("Level {}", level))
+}
+
+fn finish_level(
+    mut timer: ResMut<LevelCompleteTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+) {
+    if timer.0.tick(time.delta()).just_finished() {
+        next_state.set(GameState::Playing);
     }
 }
 
-fn text_update_system(mut query: Query<&mut Text, With<CoinsText>>, game: Res<Game>) {
+const COIN_ITEM: &str = "coin";
+
+fn text_update_system(
+    mut query: Query<&mut Text, With<CoinsText>>,
+    game: Res<Game>,
+    inventory: Single<&Inventory, With<Player>>,
+) {
     for mut span in &mut query {
-        **span = format!("Coins: {:?}", game.coins);
+        **span = format!("Coins: {:?} Purse: {}", game.coins, inventory.count(COIN_ITEM));
     }
 }
 
@@ -116,14 +201,33 @@ fn coin_collected(
     mut reader: EventReader<PlayerPositionUpdated>,
     mut commands: Commands,
     mut game: ResMut<Game>,
+    mut inventory: Single<&mut Inventory, With<Player>>,
     query: Query<(Entity, &Coin)>,
+    registry: Res<ItemRegistry>,
+    mut writer: EventWriter<TransitionRequest<GameState>>,
 ) {
     for event in reader.read() {
         for (entity, coin) in query.iter() {
             if coin.index == event.0 {
                 commands.entity(entity).despawn();
                 game.coins -= 1;
+                inventory.add(COIN_ITEM, 1, &registry);
+                if game.coins == 0 {
+                    writer.send(TransitionRequest(GameState::LevelComplete));
+                }
             }
         }
     }
 }
+
+fn remember_collected_coins(
+    mut reader: EventReader<PlayerPositionUpdated>,
+    mut map_meta: ResMut<MapMeta>,
+) {
+    for event in reader.read() {
+        let coords = map_meta.translate_index_to_coords(event.0);
+        if map_meta.coins.contains(&coords) && !map_meta.collected.contains(&coords) {
+            map_meta.collected.push(coords);
+        }
+    }
+}
@@ -1,3 +1,92 @@
+use crate::map_plugin::FLOOR_TILE;
+use bevy::log::warn;
+use game_lab_utils::internal_asset_plugin::AssetRoots;
+use serde::{Deserialize, Serialize};
+
+// A map with where the player starts and where the coins are, saved by the editor as
+// game://levels/<name>.level.ron. Positions are (column, row) from the top left
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Level {
+    pub tiles: Vec<Vec<usize>>,
+    pub spawn: (i32, i32),
+    pub coins: Vec<(i32, i32)>,
+}
+
+impl Level {
+    // How the built in levels play, from the top left corner with a coin on every floor tile
+    pub fn from_tiles(tiles: Vec<Vec<usize>>) -> Self {
+        let coins = tiles
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, tile)| **tile == FLOOR_TILE)
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect();
+        Self {
+            tiles,
+            spawn: (1, 1),
+            coins,
+        }
+    }
+
+    pub fn tile(&self, at: (i32, i32)) -> Option<usize> {
+        self.tiles.get(at.1 as usize)?.get(at.0 as usize).copied()
+    }
+
+    pub fn is_floor(&self, at: (i32, i32)) -> bool {
+        self.tile(at) == Some(FLOOR_TILE)
+    }
+
+    // Every map is the same size, see MapGenerator
+    pub fn same_size(&self, other: &Level) -> bool {
+        self.tiles.len() == other.tiles.len()
+            && self
+                .tiles
+                .iter()
+                .zip(&other.tiles)
+                .all(|(a, b)| a.len() == b.len())
+    }
+
+    pub fn read(roots: &AssetRoots, name: &str) -> Result<Level, String> {
+        let path = level_file(name);
+        roots
+            .read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| ron::de::from_bytes(&bytes).map_err(|e| e.to_string()))
+            .map_err(|e| format!("Could not read {}: {}", path, e))
+    }
+}
+
+pub fn level_file(name: &str) -> String {
+    format!("game://levels/{}.level.ron", name)
+}
+
+// A level saved from the editor under its number replaces the built in one
+pub fn load_level(roots: Option<&AssetRoots>, number: i32) -> Level {
+    let built_in = Level::from_tiles(level_to_map(number));
+    let name = number.to_string();
+    let Some(roots) = roots.filter(|roots| roots.exists(&level_file(&name))) else {
+        return built_in;
+    };
+    match Level::read(roots, &name) {
+        Ok(level) if level.same_size(&built_in) => level,
+        Ok(_) => {
+            warn!(
+                "{} isn't the size of the map, playing the built in level",
+                level_file(&name)
+            );
+            built_in
+        }
+        Err(e) => {
+            warn!("{}, playing the built in level", e);
+            built_in
+        }
+    }
+}
+
 pub fn level_to_map(level: i32) -> Vec<Vec<usize>> {
     match level {
         1 => level_1(),
@@ -1,29 +1,75 @@
 mod cursor;
+mod editor;
 mod game;
 mod levels;
 mod map_plugin;
 mod player_plugin;
+mod save;
+mod state;
+mod tuning;
 mod utils;
 
 use crate::cursor::CursorPlugin;
+use crate::editor::EditorPlugin;
 use crate::game::GamePlugin;
 use crate::levels::level_to_map;
 use crate::map_plugin::MapGenerator;
 use crate::player_plugin::PlayerPlugin;
+use crate::save::GameSavePlugin;
+use crate::state::StatePlugin;
+use crate::tuning::Tuning;
 use bevy::DefaultPlugins;
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
-use game_lab_utils::internal_asset_plugin::InternalAssetPlugin;
+use game_lab_utils::asset_manifest_plugin::AssetManifestPlugin;
+use game_lab_utils::capture_plugin::CaptureSettings;
+use game_lab_utils::console_plugin::ConsolePlugin;
+use game_lab_utils::debug_plugin::DebugPlugin;
+use game_lab_utils::gizmo_plugin::GizmoPlugin;
+use game_lab_utils::internal_asset_plugin::{AssetSourcesPlugin, InternalAssetPlugin};
+use game_lab_utils::inventory_plugin::InventoryPlugin;
+use game_lab_utils::system_timings::system_timing_layer;
+use game_lab_utils::tuning_plugin::TuningPlugin;
+use game_lab_utils::y_sort_plugin::YSortPlugin;
 
 fn main() {
     App::new()
+        .add_plugins(AssetSourcesPlugin::new("game1"))
         .add_plugins(
             DefaultPlugins
                 .set(InternalAssetPlugin::new())
-                .set(ImagePlugin::default_nearest()),
+                .set(ImagePlugin::default_nearest())
+                .set(LogPlugin {
+                    custom_layer: system_timing_layer,
+                    ..default()
+                }),
         )
+        .add_plugins(
+            DebugPlugin::new(false)
+                .with_state_file("saves/game-1/debug.json")
+                .with_capture(CaptureSettings {
+                    directory: "captures/game-1".into(),
+                    ..default()
+                }),
+        )
+        // Tiles are centred on multiples of 32 with rows going down, see MapMeta
+        .add_plugins(
+            GizmoPlugin::new()
+                .with_tile_size(32.0)
+                .with_origin(Vec2::new(-16.0, 16.0))
+                .with_rows_down(),
+        )
+        .add_plugins(ConsolePlugin::new().with_startup_script("game://console.txt"))
+        .add_plugins(AssetManifestPlugin::new("game://assets.json"))
+        .add_plugins(YSortPlugin::new())
+        .add_plugins(StatePlugin {})
+        .add_plugins(TuningPlugin::<Tuning>::new("game://game.tuning.ron"))
+        .add_plugins(InventoryPlugin::new("game://items.json"))
         .add_plugins(PlayerPlugin::new())
         .add_plugins(MapGenerator::new(level_to_map(1)))
         .add_plugins(CursorPlugin::new())
+        .add_plugins(EditorPlugin::new())
         .add_plugins(GamePlugin {})
+        .add_plugins(GameSavePlugin {})
         .run();
 }
@@ -1,18 +1,28 @@
+use crate::levels::Level;
 use bevy::app::{App, Plugin};
-use bevy::asset::{AssetServer, Assets, Handle};
+use bevy::asset::Handle;
 use bevy::image::Image;
 use bevy::math::{IVec2, Vec2, vec3};
 use bevy::prelude::{
-    Commands, Component, Entity, Event, EventReader, EventWriter, Query, Res, ResMut, Resource,
-    Sprite, Startup, TextureAtlas, TextureAtlasLayout, Transform, UVec2, Update,
+    Commands, Component, Entity, Event, EventReader, EventWriter, Query, Reflect, ReflectComponent,
+    ReflectResource, Res, ResMut, Resource, Sprite, Startup, TextureAtlas, TextureAtlasLayout,
+    Transform, Update,
 };
+use game_lab_utils::asset_manifest_plugin::ImageAssets;
+use game_lab_utils::gizmo_plugin::DebugCollider;
+use game_lab_utils::y_sort_plugin::{SortingLayer, YSort};
+
+const TILE_ATLAS: &str = "dungeon_tiles";
+// The only tile the player can walk on
+pub const FLOOR_TILE: usize = 47;
 
 #[derive(Resource)]
 pub struct MapResources {
-    atlas_handle: Handle<TextureAtlasLayout>,
-    tile_map_handle: Handle<Image>,
+    pub atlas_handle: Handle<TextureAtlasLayout>,
+    pub tile_map_handle: Handle<Image>,
 }
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Tile {
     pub sprite_index: IVec2,
     pub position: Vec2,
@@ -24,18 +34,34 @@ pub struct LevelChangeEvent;
 #[derive(Event)]
 pub struct TileCreationEvent(pub usize, pub Vec2, pub i32);
 
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct MapMeta {
     size: (i32, i32),
     total_count: i32,
     sprite_size: i32,
-    atlas_path: String,
-    columns: u32,
-    rows: u32,
     pub level_data: Vec<Vec<usize>>,
     pub level_mask: Vec<Vec<usize>>,
+    pub spawn: (i32, i32),
+    pub coins: Vec<(i32, i32)>,
+    // Coins already picked up on this level, they aren't spawned again when the map is rebuilt
+    pub collected: Vec<(i32, i32)>,
 }
 impl MapMeta {
+    // Send a LevelChangeEvent after for the tiles, coins and player to follow
+    pub fn set_level(&mut self, level: Level) {
+        self.level_data = level.tiles;
+        self.spawn = level.spawn;
+        self.coins = level.coins;
+        self.collected.clear();
+    }
+    pub fn level(&self) -> Level {
+        Level {
+            tiles: self.level_data.clone(),
+            spawn: self.spawn,
+            coins: self.coins.clone(),
+        }
+    }
     pub fn translate_index_to_coords(&self, i: i32) -> (i32, i32) {
         let x: i32 = i % self.size.0;
         let y: i32 = i / self.size.1;
@@ -80,25 +106,25 @@ impl MapMeta {
 
 pub struct MapGenerator {
     size: (i32, i32),
-    atlas_path: String,
-    columns: u32,
-    rows: u32,
-    level_data: Vec<Vec<usize>>,
+    level: Level,
 }
 impl Plugin for MapGenerator {
     fn build(&self, app: &mut App) {
-        let map_meta = MapMeta {
+        let mut map_meta = MapMeta {
             size: self.size,
             sprite_size: 32,
-            atlas_path: self.atlas_path.clone(),
             total_count: self.size.0 * self.size.1,
-            columns: self.columns,
-            rows: self.rows,
-            level_data: self.level_data.clone(),
+            level_data: vec![],
             level_mask: vec![vec![]],
+            spawn: (0, 0),
+            coins: vec![],
+            collected: vec![],
         };
+        map_meta.set_level(self.level.clone());
 
-        app.add_event::<LevelChangeEvent>()
+        app.register_type::<MapMeta>()
+            .register_type::<Tile>()
+            .add_event::<LevelChangeEvent>()
             .add_event::<TileCreationEvent>()
             .insert_resource(map_meta)
             .add_systems(Startup, (load_assets, generate_tiles))
@@ -110,30 +136,16 @@ impl MapGenerator {
     pub fn new(level_data: Vec<Vec<usize>>) -> Self {
         MapGenerator {
             size: (15, 15),
-            atlas_path: String::from("internal/dungeon-stuff/tiles/dungeon-tiles.png"),
-            columns: 12,
-            rows: 10,
-            level_data,
+            level: Level::from_tiles(level_data),
         }
     }
 }
 
-fn load_assets(
-    mut commands: Commands,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    asset_server: Res<AssetServer>,
-    map_meta: Res<MapMeta>,
-) {
-    let atlas = TextureAtlasLayout::from_grid(
-        UVec2::splat(map_meta.sprite_size as u32),
-        map_meta.columns,
-        map_meta.rows,
-        None,
-        None,
-    );
+fn load_assets(mut commands: Commands, images: Res<ImageAssets>) {
+    let tiles = images.atlas(TILE_ATLAS);
     commands.insert_resource(MapResources {
-        atlas_handle: texture_atlas_layouts.add(atlas),
-        tile_map_handle: asset_server.load(map_meta.atlas_path.clone()),
+        atlas_handle: tiles.layout.clone(),
+        tile_map_handle: tiles.image.clone(),
     })
 }
 
@@ -145,11 +157,14 @@ fn generate_tiles(
     for i in 0..map_meta.total_count {
         let (x, y) = map_meta.translate_index_to_coords(i);
         let transform = map_meta.translate_coords_to_transform((x, y));
-        commands.spawn(Tile {
-            sprite_index: IVec2::new(x, y),
-            position: transform,
-            index: i,
-        });
+        commands.spawn((
+            Tile {
+                sprite_index: IVec2::new(x, y),
+                position: transform,
+                index: i,
+            },
+            YSort::new(SortingLayer::Ground),
+        ));
     }
 
     writer.send(LevelChangeEvent);
@@ -160,21 +175,23 @@ fn generate_layer_mask(mut map_meta: ResMut<MapMeta>, reader: EventReader<LevelC
         return;
     }
     map_meta.level_mask = map_meta.level_data.iter()
-        .map(|i| i.iter().map(|i| if *i == 47 { 0 } else { 1 }).collect())
+        .map(|i| i.iter().map(|i| if *i == FLOOR_TILE { 0 } else { 1 }).collect())
         .collect();
 }
 
-fn generate_sprites(
+// Reads the event off so a level change sends its TileCreationEvents once
+pub fn generate_sprites(
     mut commands: Commands,
     mut ev_create_coin: EventWriter<TileCreationEvent>,
     query: Query<(Entity, &Tile)>,
     map_meta: Res<MapMeta>,
     map_resources: Res<MapResources>,
-    reader: EventReader<LevelChangeEvent>,
+    mut reader: EventReader<LevelChangeEvent>,
 ) {
     if reader.is_empty() {
         return;
     }
+    reader.clear();
 
     for (ent, tile) in query.iter() {
         let tile_index =
@@ -190,6 +207,38 @@ fn generate_sprites(
             ),
             Transform::from_translation(vec3(tile.position.x, tile.position.y, 0.0)),
         ));
+        if tile_index == FLOOR_TILE {
+            commands.entity(ent).remove::<DebugCollider>();
+        } else {
+            commands.entity(ent).insert(DebugCollider::new(Vec2::splat(map_meta.sprite_size as f32)));
+        }
         ev_create_coin.send(TileCreationEvent(tile_index, tile.position, tile.index));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::level_to_map;
+    use game_lab_utils::asset_manifest_plugin::AssetManifestPlugin;
+    use game_lab_utils::golden_image::{DEFAULT_TOLERANCE, GoldenScene, assert_golden, golden_path};
+
+    // Every cell of the fixture tile sheet is its own colour, so a wrong atlas index or a tile
+    // off its position shows up as changed pixels
+    #[test]
+    #[ignore = "needs a software renderer, run with cargo test -- --ignored"]
+    fn level_1_matches_golden() {
+        // Tiles are centred on multiples of 32, so the 15x15 map spans -16..464 across
+        let scene = GoldenScene::with_fixtures(env!("CARGO_MANIFEST_DIR"), "game1")
+            .with_size(480, 480)
+            .with_camera_at(Vec2::new(224.0, -224.0));
+        let frame = scene.render(|app| {
+            app.add_plugins((
+                AssetManifestPlugin::new("game://assets.json"),
+                MapGenerator::new(level_to_map(1)),
+            ));
+        });
+
+        assert_golden(&golden_path(env!("CARGO_MANIFEST_DIR"), "level_1"), &frame, DEFAULT_TOLERANCE);
+    }
+}
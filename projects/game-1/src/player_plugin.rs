@@ -1,17 +1,29 @@
 use crate::map_plugin::{LevelChangeEvent, MapMeta};
-use crate::utils::{bfs, get_ray_vec, vec_to_nearest};
+use crate::state::GameState;
+use crate::tuning::Tuning;
+use crate::utils::{bfs, cursor_world_pos, vec_to_nearest};
 use bevy::app::{App, Plugin, Startup};
-use bevy::asset::AssetServer;
+use bevy::color::palettes::css::YELLOW;
 use bevy::math::{Rect, Vec2, vec3};
 use bevy::prelude::{
-    ButtonInput, Camera, Commands, Component, Event, EventReader, EventWriter, GlobalTransform,
-    KeyCode, Query, Res, ResMut, Resource, Single, Sprite, Transform, Update, Window,
+    ButtonInput, Camera, Commands, Component, DetectChanges, Event, EventReader, EventWriter,
+    Gizmos, GlobalTransform, IntoSystemConfigs, KeyCode, Query, Reflect, ReflectComponent, Res,
+    ResMut, Resource, Single, Sprite, Transform, Update, Window, With, in_state,
 };
 use bevy::time::{Time, Timer, TimerMode};
+use game_lab_utils::asset_manifest_plugin::ImageAssets;
+use game_lab_utils::console_plugin::{ArgKind, ConsoleApp, ConsoleCommand};
+use game_lab_utils::debug_plugin::Debugger;
+use game_lab_utils::gizmo_plugin::{PATHS_KEY, draw_path};
+use game_lab_utils::inventory_plugin::{Inventory, give_item};
+use game_lab_utils::y_sort_plugin::{SortingLayer, YSort};
 use std::collections::VecDeque;
 use std::time::Duration;
 
-#[derive(Component)]
+const PLAYER_INVENTORY_SIZE: usize = 8;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Player {
     pub is_moving: bool,
     pub index: i32,
@@ -23,6 +35,10 @@ struct MovePlayer(pub Vec2);
 #[derive(Event)]
 pub struct PlayerPositionUpdated(pub i32);
 
+// The tile under the cursor that Space sends the player to
+#[derive(Resource, Default)]
+pub struct PlayerTarget(pub Vec2);
+
 #[derive(Resource)]
 pub struct PlayerMovement {
     movement: VecDeque<(i32, i32)>,
@@ -33,16 +49,34 @@ pub struct PlayerPlugin {}
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<MovePlayer>()
+        app.register_type::<Player>()
+            .add_event::<MovePlayer>()
             .add_event::<PlayerPositionUpdated>()
             .insert_resource(PlayerMovement {
                 movement: VecDeque::new(),
                 timer: Timer::new(Duration::from_millis(100), TimerMode::Repeating),
             })
+            .init_resource::<PlayerTarget>()
             .add_systems(Startup, setup_player)
             .add_systems(
                 Update,
-                (move_player, create_directions_for_player, transform_player, update_player_position,),
+                (
+                    aim_player,
+                    move_player,
+                    create_directions_for_player,
+                    transform_player,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, update_player_position)
+            .add_systems(Update, set_player_step.before(transform_player))
+            .add_debug_system(debug_player_path, PATHS_KEY)
+            .add_console_command(
+                ConsoleCommand::new("give", "Adds items to the player's inventory")
+                    .arg("item", ArgKind::Text)
+                    .optional_arg("count", ArgKind::Int),
+                give_item::<Player>,
             );
     }
 }
@@ -53,30 +87,48 @@ impl PlayerPlugin {
     }
 }
 
-fn setup_player(mut commands: Commands, asset_server: Res<AssetServer>, map_meta: Res<MapMeta>) {
-    let transform = map_meta.translate_index_to_transform(16);
+fn setup_player(
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    map_meta: Res<MapMeta>,
+) {
+    let image = images.image("archer_idle");
+    let index = map_meta.translate_coords_to_index(map_meta.spawn);
+    let transform = map_meta.translate_index_to_transform(index);
     commands.spawn((
         Player {
             is_moving: false,
-            index: 16,
+            index,
         },
         Sprite {
-            image:  asset_server.load("internal/dungeon-stuff/characters/archer/archer-idle-front.png"),
+            image,
             custom_size: Some(Vec2::splat(32.0)),
             rect: Some(Rect::new(0.0, 0.0, 24.0, 24.0)),
             ..Default::default()
         },
         Transform::from_xyz(transform.x, transform.y, 10.0),
+        // Sort just in front of a coin on the same tile
+        YSort::new(SortingLayer::World).with_offset(-1.0),
+        Inventory::new(PLAYER_INVENTORY_SIZE),
     ));
 }
 
+fn aim_player(
+    mut target: ResMut<PlayerTarget>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    window: Single<&Window>,
+) {
+    if let Some(pos) = cursor_world_pos(camera, window) {
+        target.0 = vec_to_nearest(pos, 32.0);
+    }
+}
+
 fn move_player(
     mut player: Single<&mut Player>,
     mut writer: EventWriter<MovePlayer>,
     player_movement: Res<PlayerMovement>,
     keys: Res<ButtonInput<KeyCode>>,
-    camera: Single<(&Camera, &GlobalTransform)>,
-    window: Single<&Window>,
+    target: Res<PlayerTarget>,
 ) {
     let is_moving = player.is_moving;
     if is_moving {
@@ -87,8 +139,7 @@ fn move_player(
     }
     if keys.just_pressed(KeyCode::Space) && !is_moving {
         player.is_moving = true;
-        let vec = vec_to_nearest(get_ray_vec(camera, window), 32.0);
-        writer.send(MovePlayer(vec));
+        writer.send(MovePlayer(target.0));
     }
 }
 
@@ -133,12 +184,97 @@ fn transform_player(
     }
 }
 
-fn update_player_position(reader: EventReader<LevelChangeEvent>, mut player: Query<(&mut Transform, &mut Player)>, map_meta: Res<MapMeta>) {
+fn set_player_step(tuning: Res<Tuning>, mut player_movement: ResMut<PlayerMovement>) {
+    if tuning.is_changed() {
+        player_movement
+            .timer
+            .set_duration(Duration::from_millis(tuning.player_step_ms));
+    }
+}
+
+// What's left of the path bfs found, from where the player is now
+fn debug_player_path(
+    mut gizmos: Gizmos,
+    player: Single<&Transform, With<Player>>,
+    player_movement: Res<PlayerMovement>,
+    map_meta: Res<MapMeta>,
+) {
+    if player_movement.movement.is_empty() {
+        return;
+    }
+    let points: Vec<Vec2> = std::iter::once(player.translation.truncate())
+        .chain(player_movement.movement.iter().map(|m| map_meta.translate_coords_to_transform(*m)))
+        .collect();
+    draw_path(&mut gizmos, &points, YELLOW.into());
+}
+
+// A path from the old map is dropped, it may walk through walls on the new one. Reads the event
+// off so a path started the frame after isn't dropped too
+fn update_player_position(mut reader: EventReader<LevelChangeEvent>, mut player: Query<(&mut Transform, &mut Player)>, mut player_movement: ResMut<PlayerMovement>, map_meta: Res<MapMeta>) {
     if reader.is_empty() {
         return;
     }
+    reader.clear();
     let (mut player_transform, mut player) = player.single_mut();
-    let transform = map_meta.translate_index_to_transform(16);
+    let index = map_meta.translate_coords_to_index(map_meta.spawn);
+    let transform = map_meta.translate_index_to_transform(index);
     player_transform.translation = vec3(transform.x, transform.y, 11.0);
-    player.index = 16;
-}
\ No newline at end of file
+    player.index = index;
+    player_movement.movement.clear();
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GamePlugin;
+    use crate::levels::level_to_map;
+    use crate::map_plugin::MapGenerator;
+    use crate::state::StatePlugin;
+    use game_lab_utils::asset_manifest_plugin::AssetManifestPlugin;
+    use game_lab_utils::inventory_plugin::{InventoryPlugin, ItemRegistry};
+    use game_lab_utils::test_harness::TestApp;
+    use game_lab_utils::tuning_plugin::TuningPlugin;
+
+    #[test]
+    fn walks_bfs_path_and_collects_coins() {
+        let mut app = TestApp::with_fixtures(env!("CARGO_MANIFEST_DIR"), "game1");
+        app.add_plugins((
+            StatePlugin {},
+            AssetManifestPlugin::new("game://assets.json"),
+            InventoryPlugin::new("game://items.json"),
+            TuningPlugin::<Tuning>::new("game://game.tuning.ron"),
+            PlayerPlugin::new(),
+            MapGenerator::new(level_to_map(1)),
+            GamePlugin {},
+        ));
+        app.set_state(GameState::Playing);
+        assert!(app.step_until(500, |world| !world.resource::<ItemRegistry>().items.is_empty()));
+
+        // Headless the camera has no viewport to aim through, so the target is set where the cursor would put it
+        let start = app.single::<Player>().index;
+        let map_meta = app.resource::<MapMeta>();
+        let start = map_meta.translate_index_to_coords(start);
+        let path: Vec<i32> = bfs(&map_meta.level_mask, start, (4, 3))
+            .into_iter()
+            .map(|coords| map_meta.translate_coords_to_index(coords))
+            .collect();
+        let target = map_meta.translate_coords_to_transform((4, 3));
+        app.world_mut().insert_resource(PlayerTarget(target));
+        app.tap(KeyCode::Space);
+        assert!(app.single::<Player>().is_moving);
+
+        let mut visited = vec![app.single::<Player>().index];
+        for _ in 0..120 {
+            app.step(1);
+            let index = app.single::<Player>().index;
+            if visited.last() != Some(&index) {
+                visited.push(index);
+            }
+        }
+
+        assert_eq!(path.len(), 6);
+        assert_eq!(visited, path);
+        assert!(!app.single::<Player>().is_moving);
+        // The tile it started on counts too
+        assert_eq!(app.single::<Inventory>().count("coin"), path.len() as u32);
+    }
+}
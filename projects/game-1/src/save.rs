@@ -0,0 +1,146 @@
+use crate::game::{Game, LEVEL_COUNT};
+use crate::levels::load_level;
+use crate::map_plugin::{LevelChangeEvent, MapMeta};
+use crate::player_plugin::Player;
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{EventWriter, IntoSystemConfigs, Res, ResMut, Single, With, warn};
+use game_lab_utils::internal_asset_plugin::AssetRoots;
+use game_lab_utils::inventory_plugin::Inventory;
+use game_lab_utils::save_plugin::{
+    AUTOSAVE_SLOT, SavePlugin, SaveState, SaveSystems, loading, saving,
+};
+use serde::{Deserialize, Serialize};
+
+// Bump when a section changes shape and add a migration from the old version
+const SAVE_VERSION: u32 = 1;
+
+const PROGRESS_SECTION: &str = "progress";
+
+pub struct GameSavePlugin {}
+
+impl Plugin for GameSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(SavePlugin::new("game-1", SAVE_VERSION).with_autoload(AUTOSAVE_SLOT))
+            .add_systems(Update, save_progress.in_set(SaveSystems::Collect).run_if(saving))
+            .add_systems(Update, load_progress.in_set(SaveSystems::Apply).run_if(loading));
+    }
+}
+
+// Levels are short so progress is the level you're on, not where you are in it
+#[derive(Serialize, Deserialize)]
+struct ProgressSave {
+    level: i32,
+    inventory: Inventory,
+}
+
+fn save_progress(
+    mut state: ResMut<SaveState>,
+    game: Res<Game>,
+    inventory: Single<&Inventory, With<Player>>,
+) {
+    state.write(
+        PROGRESS_SECTION,
+        &ProgressSave {
+            level: game.level,
+            inventory: inventory.clone(),
+        },
+    );
+}
+
+// Same path as finishing a level, the map plugin rebuilds tiles and coins from the event
+fn load_progress(
+    state: Res<SaveState>,
+    mut game: ResMut<Game>,
+    mut map_meta: ResMut<MapMeta>,
+    mut inventory: Single<&mut Inventory, With<Player>>,
+    mut writer: EventWriter<LevelChangeEvent>,
+    roots: Option<Res<AssetRoots>>,
+) {
+    let Some(save) = state.read::<ProgressSave>(PROGRESS_SECTION) else {
+        return;
+    };
+    // A save from an older build or edited by hand can name a level that isn't there
+    if (1..=LEVEL_COUNT).contains(&save.level) {
+        game.level = save.level;
+    } else {
+        warn!(
+            "Saved level {} doesn't exist, levels go from 1 to {}, staying on level {}",
+            save.level, LEVEL_COUNT, game.level
+        );
+    }
+    map_meta.set_level(load_level(roots.as_deref(), game.level));
+    **inventory = save.inventory;
+    writer.send(LevelChangeEvent);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::level_to_map;
+    use crate::map_plugin::MapGenerator;
+    use bevy::ecs::system::RunSystemOnce;
+    use game_lab_utils::save_plugin::SaveFile;
+
+    #[test]
+    fn load_progress_level() {
+        struct TestCase {
+            saved: i32,
+            expected: i32,
+        }
+
+        let cases = vec![
+            TestCase {
+                saved: 2,
+                expected: 2,
+            },
+            TestCase {
+                saved: 99,
+                expected: 1,
+            },
+            TestCase {
+                saved: 0,
+                expected: 1,
+            },
+        ];
+
+        for c in cases {
+            let mut app = App::new();
+            app.add_plugins(MapGenerator::new(level_to_map(1)));
+            let mut game = Game::default();
+            game.level = 1;
+            app.insert_resource(game);
+            app.world_mut().spawn((
+                Player {
+                    is_moving: false,
+                    index: 0,
+                },
+                Inventory::new(8),
+            ));
+            let mut file = SaveFile::new(SAVE_VERSION);
+            file.write(
+                PROGRESS_SECTION,
+                &ProgressSave {
+                    level: c.saved,
+                    inventory: Inventory::new(8),
+                },
+            );
+            app.insert_resource(SaveState {
+                saving: None,
+                loading: Some((0, file)),
+            });
+
+            app.world_mut().run_system_once(load_progress).unwrap();
+
+            assert_eq!(
+                app.world().resource::<Game>().level,
+                c.expected,
+                "{}",
+                c.saved
+            );
+            assert_eq!(
+                app.world().resource::<MapMeta>().level(),
+                load_level(None, c.expected)
+            );
+        }
+    }
+}
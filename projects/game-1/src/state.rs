@@ -0,0 +1,32 @@
+use bevy::app::{App, Plugin};
+use bevy::prelude::States;
+use game_lab_utils::game_state_plugin::{GameStatePlugin, GameStates};
+use game_lab_utils::loading_plugin::LoadingPlugin;
+
+#[derive(States, Default, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    Loading,
+    Playing,
+    Paused,
+    LevelComplete,
+    // The level editor is open, play stops until it closes
+    Editing,
+}
+
+impl GameStates for GameState {
+    const MAIN_MENU: Self = GameState::MainMenu;
+    const LOADING: Self = GameState::Loading;
+    const PLAYING: Self = GameState::Playing;
+    const PAUSED: Self = GameState::Paused;
+}
+
+pub struct StatePlugin {}
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(GameStatePlugin::new(GameState::MainMenu))
+            .add_plugins(LoadingPlugin::new(GameState::Loading, GameState::Playing));
+    }
+}
@@ -0,0 +1,17 @@
+use bevy::prelude::{Asset, Reflect, ReflectResource, Resource};
+use serde::{Deserialize, Serialize};
+
+// Loaded from game://game.tuning.ron, these defaults are only used until it's in
+#[derive(Asset, Resource, Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Tuning {
+    // How long the player takes per tile of a path
+    pub player_step_ms: u64,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self { player_step_ms: 100 }
+    }
+}
@@ -41,15 +41,19 @@ pub fn bfs(grid: &Vec<Vec<usize>>, start: (i32, i32), end: (i32, i32)) -> Vec<(i
 }
 
 pub fn get_ray_vec(camera: Single<(&Camera, &GlobalTransform)>, window: Single<&Window>) -> Vec2 {
+    cursor_world_pos(camera, window).unwrap_or(Vec2::ZERO)
+}
+
+// None while the cursor is outside the window or the camera has no viewport yet
+pub fn cursor_world_pos(
+    camera: Single<(&Camera, &GlobalTransform)>,
+    window: Single<&Window>,
+) -> Option<Vec2> {
     let (camera, camera_transform) = *camera;
-    if let Some(pos) = window
+    window
         .cursor_position()
         .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
         .map(|ray| ray.origin.truncate())
-    {
-        return pos;
-    }
-    Vec2::ZERO
 }
 
 pub fn vec_to_nearest(pos: Vec2, size: f32) -> Vec2 {
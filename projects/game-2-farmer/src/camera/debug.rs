@@ -0,0 +1,90 @@
+use std::time::Duration;
+use bevy::color::palettes::basic::{PURPLE, RED, YELLOW};
+use bevy::math::curve::EaseFunction;
+use bevy::math::{Isometry2d, Vec2};
+use bevy::prelude::{Camera2d, EventWriter, GizmoPrimitive2d, Gizmos, OrthographicProjection, Rectangle, Res, Single, Transform, With};
+use bevy_egui::{egui, EguiContexts};
+use game_lab_utils::debug_plugin::{debug_window, DebugState};
+use game_lab_utils::gizmo_plugin::draw_path;
+use crate::camera::effects::{CameraEffects, CameraPanEvent, CameraShakeEvent, CameraZoomEvent};
+use crate::player::player::Player;
+use crate::tuning::Tuning;
+
+pub fn debug_camera(mut gizmos: Gizmos, camera: Single<&CameraEffects, With<Camera2d>>, tuning: Res<Tuning>) {
+    gizmos.primitive_2d(
+        &Rectangle::new(tuning.camera.zone, tuning.camera.zone),
+        Isometry2d::from_translation(camera.anchor),
+        PURPLE,
+    );
+}
+
+pub fn debug_camera_effects(mut gizmos: Gizmos, camera: Single<(&CameraEffects, &Transform), With<Camera2d>>) {
+    let (effects, transform) = camera.into_inner();
+    let translation = transform.translation.truncate();
+
+    if effects.trauma > 0.0 {
+        gizmos.circle_2d(Isometry2d::from_translation(effects.anchor), effects.trauma * 20.0, RED);
+        gizmos.line_2d(effects.anchor, translation, RED);
+    }
+
+    if let Some(pan) = &effects.pan {
+        draw_path(&mut gizmos, &pan.points, YELLOW.into());
+        gizmos.circle_2d(Isometry2d::from_translation(effects.pan_position), 8.0, RED);
+    }
+}
+
+pub fn debug_camera_window(
+    mut ctx: EguiContexts,
+    mut shake_writer: EventWriter<CameraShakeEvent>,
+    mut zoom_writer: EventWriter<CameraZoomEvent>,
+    mut pan_writer: EventWriter<CameraPanEvent>,
+    camera: Single<(&CameraEffects, &OrthographicProjection)>,
+    player: Single<&Transform, With<Player>>,
+    debug_state: Res<DebugState>,
+) {
+    let (effects, projection) = camera.into_inner();
+    debug_window("Camera", &debug_state).show(ctx.ctx_mut(), |ui| {
+        egui::Grid::new("camera_grid")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .striped(true)
+            .max_col_width(150.0)
+            .show(ui, |ui| {
+                ui.label("Anchor:");
+                ui.label(format!("{:.1}, {:.1}", effects.anchor.x, effects.anchor.y));
+                ui.end_row();
+
+                ui.label("Trauma:");
+                ui.label(format!("{:.2}", effects.trauma));
+                ui.end_row();
+
+                ui.label("Zoom:");
+                ui.label(format!("{:.2}", projection.scale));
+                ui.end_row();
+
+                ui.label("Panning:");
+                ui.label(format!("{:?}", effects.is_panning()));
+                ui.end_row();
+            });
+
+        ui.horizontal(|ui| {
+            if ui.button("Shake").clicked() {
+                shake_writer.send(CameraShakeEvent(0.5));
+            }
+            if ui.button("Zoom in").clicked() {
+                zoom_writer.send(CameraZoomEvent { scale: 0.5, duration: Duration::from_millis(600), ease: EaseFunction::CubicInOut });
+            }
+            if ui.button("Zoom reset").clicked() {
+                zoom_writer.send(CameraZoomEvent { scale: 1.0, duration: Duration::from_millis(600), ease: EaseFunction::CubicInOut });
+            }
+            if ui.button("Pan").clicked() {
+                let start = player.translation.truncate();
+                pan_writer.send(CameraPanEvent {
+                    points: vec!(start, start + Vec2::new(300.0, 0.0), start + Vec2::new(300.0, -300.0), start),
+                    duration: Duration::from_secs(4),
+                    ease: EaseFunction::SineInOut,
+                });
+            }
+        });
+    });
+}
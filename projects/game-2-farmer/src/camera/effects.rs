@@ -0,0 +1,216 @@
+use std::time::Duration;
+use bevy::math::curve::{Curve, EaseFunction, EasingCurve};
+use bevy::math::{Quat, Vec2, Vec3};
+use bevy::prelude::{Camera2d, Component, Event, EventReader, OrthographicProjection, Res, Single, Time, Timer, TimerMode, Transform, With};
+
+const MAX_SHAKE_OFFSET: f32 = 12.0;
+const MAX_SHAKE_ROLL: f32 = 0.04;
+const SHAKE_FREQUENCY: f32 = 18.0;
+const TRAUMA_DECAY: f32 = 1.4;
+
+// Adds trauma to the camera, shake strength is trauma squared so small hits stay subtle
+#[derive(Event)]
+pub struct CameraShakeEvent(pub f32);
+
+#[derive(Event)]
+pub struct CameraZoomEvent {
+    pub scale: f32,
+    pub duration: Duration,
+    pub ease: EaseFunction,
+}
+
+// Scripted pan along `points`, follow resumes from the last point once it finishes
+#[derive(Event)]
+pub struct CameraPanEvent {
+    pub points: Vec<Vec2>,
+    pub duration: Duration,
+    pub ease: EaseFunction,
+}
+
+pub struct CameraZoom {
+    pub from: f32,
+    pub to: f32,
+    pub ease: EaseFunction,
+    pub timer: Timer,
+}
+
+pub struct CameraPan {
+    pub points: Vec<Vec2>,
+    pub ease: EaseFunction,
+    pub timer: Timer,
+}
+
+#[derive(Component)]
+pub struct CameraEffects {
+    pub anchor: Vec2,
+    pub trauma: f32,
+    pub shake_offset: Vec2,
+    pub shake_roll: f32,
+    pub zoom: Option<CameraZoom>,
+    pub pan: Option<CameraPan>,
+    pub pan_position: Vec2,
+    seed: f32,
+}
+
+impl CameraEffects {
+    pub fn new(anchor: Vec2) -> Self {
+        Self {
+            anchor,
+            trauma: 0.0,
+            shake_offset: Vec2::ZERO,
+            shake_roll: 0.0,
+            zoom: None,
+            pan: None,
+            pan_position: anchor,
+            seed: 0.0,
+        }
+    }
+
+    pub fn is_panning(&self) -> bool {
+        self.pan.is_some()
+    }
+
+    pub fn focus(&self) -> Vec2 {
+        if self.is_panning() { self.pan_position } else { self.anchor }
+    }
+}
+
+impl CameraPan {
+    // Samples the path by distance so every segment moves at the same speed
+    pub fn sample(&self) -> Vec2 {
+        let t = EasingCurve::new(0.0, 1.0, self.ease).sample_clamped(self.timer.fraction());
+        sample_path(&self.points, t)
+    }
+}
+
+pub fn sample_path(points: &[Vec2], t: f32) -> Vec2 {
+    match points {
+        [] => Vec2::ZERO,
+        [point] => *point,
+        _ => {
+            let length: f32 = points.windows(2).map(|w| w[0].distance(w[1])).sum();
+            let mut remaining = length * t.clamp(0.0, 1.0);
+            for w in points.windows(2) {
+                let segment = w[0].distance(w[1]);
+                if remaining <= segment && segment > 0.0 {
+                    return w[0].lerp(w[1], remaining / segment);
+                }
+                remaining -= segment;
+            }
+            points[points.len() - 1]
+        }
+    }
+}
+
+// Cheap smooth noise in -1..1, good enough for shake without pulling in a noise crate
+fn noise(seed: f32, t: f32) -> f32 {
+    let a = (t * 1.0 + seed).sin();
+    let b = (t * 2.3 + seed * 1.7).sin() * 0.5;
+    let c = (t * 4.1 + seed * 2.9).sin() * 0.25;
+    (a + b + c) / 1.75
+}
+
+pub fn update_camera_shake(mut reader: EventReader<CameraShakeEvent>, camera: Single<&mut CameraEffects>, time: Res<Time>) {
+    let mut effects = camera.into_inner();
+    for event in reader.read() {
+        effects.trauma = (effects.trauma + event.0).clamp(0.0, 1.0);
+    }
+
+    if effects.trauma <= 0.0 {
+        effects.shake_offset = Vec2::ZERO;
+        effects.shake_roll = 0.0;
+        return;
+    }
+
+    effects.seed += time.delta_secs() * SHAKE_FREQUENCY;
+    let shake = effects.trauma * effects.trauma;
+    let t = effects.seed;
+    effects.shake_offset = Vec2::new(noise(0.0, t), noise(10.0, t)) * MAX_SHAKE_OFFSET * shake;
+    effects.shake_roll = noise(20.0, t) * MAX_SHAKE_ROLL * shake;
+    effects.trauma = (effects.trauma - TRAUMA_DECAY * time.delta_secs()).max(0.0);
+}
+
+pub fn start_camera_zoom(mut reader: EventReader<CameraZoomEvent>, camera: Single<(&mut CameraEffects, &OrthographicProjection)>) {
+    let (mut effects, projection) = camera.into_inner();
+    for event in reader.read() {
+        effects.zoom = Some(CameraZoom {
+            from: projection.scale,
+            to: event.scale,
+            ease: event.ease,
+            timer: Timer::new(event.duration, TimerMode::Once),
+        });
+    }
+}
+
+pub fn update_camera_zoom(camera: Single<(&mut CameraEffects, &mut OrthographicProjection)>, time: Res<Time>) {
+    let (mut effects, mut projection) = camera.into_inner();
+    let Some(zoom) = &mut effects.zoom else {
+        return;
+    };
+
+    zoom.timer.tick(time.delta());
+    projection.scale = EasingCurve::new(zoom.from, zoom.to, zoom.ease).sample_clamped(zoom.timer.fraction());
+    if zoom.timer.finished() {
+        effects.zoom = None;
+    }
+}
+
+pub fn start_camera_pan(mut reader: EventReader<CameraPanEvent>, mut camera: Single<&mut CameraEffects>) {
+    for event in reader.read() {
+        if event.points.is_empty() {
+            continue;
+        }
+        camera.pan = Some(CameraPan {
+            points: event.points.clone(),
+            ease: event.ease,
+            timer: Timer::new(event.duration, TimerMode::Once),
+        });
+    }
+}
+
+pub fn update_camera_pan(mut camera: Single<&mut CameraEffects>, time: Res<Time>) {
+    let effects = &mut **camera;
+    let Some(pan) = &mut effects.pan else {
+        return;
+    };
+
+    pan.timer.tick(time.delta());
+    effects.pan_position = pan.sample();
+    if pan.timer.finished() {
+        effects.anchor = effects.pan_position;
+        effects.pan = None;
+    }
+}
+
+pub fn apply_camera_effects(camera: Single<(&CameraEffects, &mut Transform), With<Camera2d>>) {
+    let (effects, mut transform) = camera.into_inner();
+    let position = effects.focus() + effects.shake_offset;
+    transform.translation = Vec3::new(position.x, position.y, transform.translation.z);
+    transform.rotation = Quat::from_rotation_z(effects.shake_roll);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_path_by_distance() {
+        struct TestCase {
+            t: f32,
+            expected: Vec2,
+        }
+
+        let points = vec!(Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 30.0));
+        let cases = vec!(
+            TestCase { t: 0.0, expected: Vec2::new(0.0, 0.0) },
+            TestCase { t: 0.25, expected: Vec2::new(10.0, 0.0) },
+            TestCase { t: 0.5, expected: Vec2::new(10.0, 10.0) },
+            TestCase { t: 1.0, expected: Vec2::new(10.0, 30.0) },
+            TestCase { t: 2.0, expected: Vec2::new(10.0, 30.0) },
+        );
+
+        for c in cases {
+            assert_eq!(super::sample_path(&points, c.t), c.expected);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+mod debug;
+pub mod effects;
+
+use bevy::app::{App, Plugin, PostUpdate, Startup, Update};
+use bevy::prelude::{Camera2d, Commands, IntoSystemConfigs, Res, Single, Transform, TransformSystem, With, Without};
+use game_lab_utils::debug_plugin::{DebugKey, Debugger};
+use crate::camera::debug::{debug_camera, debug_camera_effects, debug_camera_window};
+use crate::camera::effects::{apply_camera_effects, start_camera_pan, start_camera_zoom, update_camera_pan, update_camera_shake, update_camera_zoom, CameraEffects, CameraPanEvent, CameraShakeEvent, CameraZoomEvent};
+use crate::player::player::Player;
+use crate::tuning::Tuning;
+
+const EFFECTS_KEY: DebugKey = DebugKey::new("Camera/Effects");
+const ZONE_KEY: DebugKey = DebugKey::new("Camera/Zone");
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraShakeEvent>()
+            .add_event::<CameraZoomEvent>()
+            .add_event::<CameraPanEvent>()
+            .add_systems(Startup, initialize_camera)
+            .add_systems(Update, move_camera)
+            .add_systems(Update, (update_camera_shake, start_camera_zoom, update_camera_zoom, start_camera_pan, update_camera_pan).chain().after(move_camera))
+            .add_systems(PostUpdate, apply_camera_effects.before(TransformSystem::TransformPropagate))
+            .add_debug_system((debug_camera, debug_camera_effects), ZONE_KEY)
+            .add_debug_system(debug_camera_window, EFFECTS_KEY);
+    }
+}
+
+pub fn initialize_camera(mut commands: Commands) {
+    let transform = Transform::from_xyz(1920.0, -1920.0, 0.0); // play pos
+    commands.spawn((
+        Camera2d,
+        CameraEffects::new(transform.translation.truncate()),
+        transform,
+    ));
+}
+
+// Follow only moves the anchor, effects are layered on top of it in `apply_camera_effects`
+pub fn move_camera(camera: Single<&mut CameraEffects, (With<Camera2d>, Without<Player>)>, player: Single<(&Player, &Transform), With<Player>>, tuning: Res<Tuning>) {
+    let mut effects = camera.into_inner();
+    let (player, player_transform) = player.into_inner();
+
+    if effects.is_panning() {
+        return;
+    }
+
+    let anchor = &mut effects.anchor;
+    let zone = tuning.camera.zone;
+    let borders: [f32; 4] = [
+        anchor.x + zone / 2.0, // right
+        anchor.x - zone / 2.0, // left
+        anchor.y + zone / 2.0, // up
+        anchor.y - zone / 2.0, // down
+    ];
+
+    let speed = if player.is_running { player.run_speed } else { player.walk_speed };
+
+    if player_transform.translation.x < borders[0] {
+        anchor.x -= speed;
+    }
+
+    if player_transform.translation.x > borders[1] {
+        anchor.x += speed;
+    }
+
+    if player_transform.translation.y < borders[2] {
+        anchor.y -= speed;
+    }
+
+    if player_transform.translation.y > borders[3] {
+        anchor.y += speed;
+    }
+}
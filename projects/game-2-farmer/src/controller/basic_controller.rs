@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use bevy::input::ButtonInput;
 use bevy::prelude::{Commands, EventWriter, KeyCode, Res, Single};
-use crate::controller::{Action, Direction, ActionEvent, Controller, ControllerSettings};
+use crate::controller::{Action, Direction, ActionEvent, Controller, ControllerSettings, HOTBAR_SLOTS};
 
 const MOVE_DIRECTIONS: [Action; 4] = [
     Action::Move(Direction::North), Action::Move(Direction::West),
@@ -16,6 +16,12 @@ const MODIFIER_ACTIONS: [Action; 5] = [
     Action::Jump, Action::Pause, Action::Sneak
 ];
 
+const HOTBAR_KEYS: [KeyCode; HOTBAR_SLOTS] = [
+    KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3,
+    KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6,
+    KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+];
+
 pub fn initialize_basic_controller(mut commands: Commands) {
     let mut controls = HashMap::new();
 
@@ -36,6 +42,10 @@ pub fn initialize_basic_controller(mut commands: Commands) {
     controls.insert(Action::Pause,vec!( KeyCode::Escape));
     controls.insert(Action::Sneak, vec!(KeyCode::ControlLeft));
 
+    for (slot, key) in HOTBAR_KEYS.iter().enumerate() {
+        controls.insert(Action::HotbarSlot(slot), vec!(*key));
+    }
+
     commands.insert_resource(ControllerSettings { controls });
     commands.spawn(Controller{ last_move_action: Vec::new(), last_look_action: None });
 }
@@ -111,3 +121,17 @@ pub fn modifier_controller(
         }
     }
 }
+
+// Slot selection only fires on the press, holding a number shouldn't keep reselecting
+pub fn hotbar_controller(
+    mut action_writer: EventWriter<ActionEvent>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<ControllerSettings>,
+) {
+    for slot in 0..HOTBAR_SLOTS {
+        if let Some(key) = settings.controls.get(&Action::HotbarSlot(slot))
+            && keys.any_just_pressed(key.clone()) {
+            action_writer.send(ActionEvent(Action::HotbarSlot(slot), 1));
+        }
+    }
+}
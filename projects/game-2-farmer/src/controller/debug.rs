@@ -1,6 +1,7 @@
 use std::fmt::Display;
 use bevy::prelude::{Res, Single};
 use bevy_egui::{egui, EguiContexts};
+use game_lab_utils::debug_plugin::{debug_window, DebugState};
 use crate::controller::{Action, Controller, ControllerSettings, Direction};
 
 impl Display for Action {
@@ -12,6 +13,7 @@ impl Display for Action {
             Action::Jump => write!(f, "Jump"),
             Action::Modifier => write!(f, "Modifier"),
             Action::Sneak => write!(f, "Sneak"),
+            Action::HotbarSlot(slot) => write!(f, "HotbarSlot({})", slot + 1),
             Action::Pause => write!(f, "Pause"),
         }
     }
@@ -28,8 +30,8 @@ impl Display for Direction {
     }
 }
 
-pub fn debug_controller(mut ctx: EguiContexts, controller: Single<&Controller>, settings: Res<ControllerSettings>) {
-    egui::Window::new("Controller").max_width(300.0).resizable([false,false]).movable(false).show(ctx.ctx_mut(), |ui| {
+pub fn debug_controller(mut ctx: EguiContexts, debug_state: Res<DebugState>, controller: Single<&Controller>, settings: Res<ControllerSettings>) {
+    debug_window("Controller", &debug_state).movable(false).show(ctx.ctx_mut(), |ui| {
         egui::Grid::new("my_grid")
             .num_columns(2)
             .spacing([40.0, 4.0])
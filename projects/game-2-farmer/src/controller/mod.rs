@@ -4,6 +4,7 @@ mod basic_controller;
 
 use std::collections::HashMap;
 use bevy::prelude::{Component, Event, KeyCode, Resource};
+use serde::{Deserialize, Serialize};
 
 // TODO: Add loads of config
 // Loads needs to be updated here, but for now it all works as I need for this game.
@@ -18,7 +19,9 @@ use bevy::prelude::{Component, Event, KeyCode, Resource};
 // - Error handling if a controller is left unset it will blow up but for now its fine, one to think of when setting custom keys
 // - Support for multiple keys to one actions
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+pub const HOTBAR_SLOTS: usize = 9;
+
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Direction {
     North,
     East,
@@ -44,6 +47,9 @@ pub enum Action {
     Modifier,   // E.g. Shift
     Sneak,      // Control
 
+    // Hotbar slot, zero based
+    HotbarSlot(usize),  // 1-9
+
     // Game State
     Pause,    // ESC
 }
@@ -1,8 +1,12 @@
 use bevy::app::{App, Plugin, Startup, Update};
-use game_lab_utils::debug_plugin::Debugger;
+use bevy::prelude::{in_state, IntoSystemConfigs};
+use game_lab_utils::debug_plugin::{DebugKey, Debugger};
 use crate::controller::ActionEvent;
-use crate::controller::basic_controller::{initialize_basic_controller, look_controller, modifier_controller, movement_controller};
+use crate::controller::basic_controller::{hotbar_controller, initialize_basic_controller, look_controller, modifier_controller, movement_controller};
 use crate::controller::debug::debug_controller;
+use crate::state::GameState;
+
+const CONTROLLER_KEY: DebugKey = DebugKey::new("Player/Controller");
 
 // I need to extend this, so I work out how to do controller, mouse etc
 pub struct ControllerPlugin;
@@ -15,9 +19,11 @@ impl ControllerPlugin {
 impl Plugin for ControllerPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ActionEvent>()
-            .add_debug_system(debug_controller, "Controller".to_string())
+            .add_debug_system(debug_controller, CONTROLLER_KEY)
             .add_systems(Startup, initialize_basic_controller)
-            .add_systems(Update, (look_controller, movement_controller, modifier_controller));
+            // Pause and the other modifiers still need to come through while not playing
+            .add_systems(Update, (look_controller, movement_controller, hotbar_controller).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, modifier_controller);
     }
 }
 
@@ -0,0 +1,61 @@
+use bevy::prelude::{GlobalTransform, Query, Res, ResMut, Single, With};
+use bevy_egui::{egui, EguiContexts};
+use game_lab_utils::debug_plugin::{debug_window, DebugState};
+use game_lab_utils::gizmo_plugin::WorldLabels;
+use crate::farming::{Crops, EquippedTool, FarmTile, Tool};
+use crate::player::player::Player;
+
+pub fn debug_farming(mut ctx: EguiContexts, debug_state: Res<DebugState>, crops: Res<Crops>, tiles: Query<&FarmTile>, mut player: Single<&mut EquippedTool, With<Player>>) {
+    debug_window("Farming", &debug_state).show(ctx.ctx_mut(), |ui| {
+        let mut tools = vec!(Tool::Hand, Tool::Hoe, Tool::WateringCan);
+        let mut ids: Vec<&String> = crops.0.keys().collect();
+        ids.sort();
+        tools.extend(ids.into_iter().map(|id| Tool::Seeds { crop: id.clone(), item: None }));
+
+        egui::ComboBox::from_label("Equipped")
+            .selected_text(format!("{}", player.0))
+            .show_ui(ui, |ui| {
+                for tool in tools {
+                    let label = format!("{}", tool);
+                    ui.selectable_value(&mut player.0, tool, label);
+                }
+            });
+
+        egui::Grid::new("farming_grid")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .striped(true)
+            .max_col_width(150.0)
+            .show(ui, |ui| {
+                ui.label("Tilled tiles:");
+                ui.label(format!("{}", tiles.iter().count()));
+                ui.end_row();
+
+                ui.label("Watered:");
+                ui.label(format!("{}", tiles.iter().filter(|t| t.watered).count()));
+                ui.end_row();
+
+                for tile in tiles.iter() {
+                    if let Some(crop) = &tile.crop {
+                        ui.label(format!("{:?}", tile.coords));
+                        let name = crops.0.get(&crop.id).map(|d| d.name.as_str()).unwrap_or(&crop.id);
+                        ui.label(format!("{} stage {} day {}", name, crop.stage, crop.days_in_stage));
+                        ui.end_row();
+                    }
+                }
+            });
+    });
+}
+
+pub fn label_farm_tiles(mut labels: ResMut<WorldLabels>, crops: Res<Crops>, tiles: Query<(&FarmTile, &GlobalTransform)>) {
+    for (tile, transform) in tiles.iter() {
+        let mut text = match &tile.crop {
+            Some(crop) => format!("{} {}", crops.0.get(&crop.id).map(|d| d.name.as_str()).unwrap_or(&crop.id), crop.stage),
+            None => "soil".to_string(),
+        };
+        if tile.watered {
+            text.push_str(" (wet)");
+        }
+        labels.add(transform.translation().truncate(), text);
+    }
+}
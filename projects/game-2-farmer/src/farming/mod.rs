@@ -0,0 +1,217 @@
+mod debug;
+pub mod plugin;
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use bevy::math::IVec2;
+use bevy::prelude::{Asset, Component, Entity, Event, Resource, TypePath};
+use serde::Deserialize;
+
+#[derive(Asset, TypePath, Deserialize)]
+pub struct CropDefinitions {
+    pub crops: Vec<CropDefinition>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CropDefinition {
+    pub id: String,
+    pub name: String,
+    // Dry soil holds growth back a day when this is set
+    pub needs_water: bool,
+    pub stages: Vec<CropStage>,
+    pub harvest: CropYield,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CropStage {
+    pub atlas_index: usize,
+    // Watered days spent in this stage before moving on, the last stage is ripe
+    pub days: u32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CropYield {
+    pub item: String,
+    pub amount: u32,
+}
+
+impl CropDefinition {
+    pub fn last_stage(&self) -> usize {
+        self.stages.len().saturating_sub(1)
+    }
+
+    // Empty if the crop can be planted and grown, the last stage is ripe so its days don't matter
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec!();
+        if self.stages.is_empty() {
+            problems.push("has no stages".to_string());
+        }
+        for (i, stage) in self.stages.iter().enumerate().take(self.last_stage()) {
+            if stage.days == 0 {
+                problems.push(format!("stage {} lasts no days", i));
+            }
+        }
+        problems
+    }
+
+    pub fn atlas_index(&self, stage: usize) -> usize {
+        self.stages.get(stage.min(self.last_stage())).map_or(0, |s| s.atlas_index)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Crops(pub HashMap<String, CropDefinition>);
+
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+pub enum Tool {
+    #[default]
+    Hand,
+    Hoe,
+    WateringCan,
+    // The seed item planting uses up, the debug window plants without one
+    Seeds { crop: String, item: Option<String> },
+}
+
+impl Display for Tool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tool::Hand => write!(f, "Hand"),
+            Tool::Hoe => write!(f, "Hoe"),
+            Tool::WateringCan => write!(f, "Watering Can"),
+            Tool::Seeds { crop, .. } => write!(f, "Seeds({})", crop),
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct EquippedTool(pub Tool);
+
+#[derive(Clone, Debug)]
+pub struct PlantedCrop {
+    pub id: String,
+    pub stage: usize,
+    pub days_in_stage: u32,
+    pub entity: Entity,
+}
+
+#[derive(Component)]
+pub struct FarmTile {
+    pub coords: IVec2,
+    pub watered: bool,
+    pub crop: Option<PlantedCrop>,
+}
+
+#[derive(Component)]
+pub struct CropSprite;
+
+// Tilled tiles by grid coords
+#[derive(Resource, Default)]
+pub struct FarmField(pub HashMap<IVec2, Entity>);
+
+// Interact pressed while facing a tile, with whatever was in hand at the time
+#[derive(Event, Clone, Debug)]
+pub struct TileInteractEvent {
+    pub coords: IVec2,
+    pub tool: Tool,
+}
+
+// Triggered when a seed item goes in the ground so whoever planted it can pay for it
+#[derive(Event, Clone, Debug)]
+pub struct CropPlantedEvent {
+    pub seed: String,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct HarvestEvent {
+    pub crop: String,
+    pub item: String,
+    pub amount: u32,
+    pub coords: IVec2,
+}
+
+// Moves a crop on by one day, returns true if it changed stage
+pub fn grow_crop(crop: &mut PlantedCrop, definition: &CropDefinition, watered: bool) -> bool {
+    if crop.stage >= definition.last_stage() || (definition.needs_water && !watered) {
+        return false;
+    }
+    crop.days_in_stage += 1;
+    if crop.days_in_stage >= definition.stages[crop.stage].days {
+        crop.stage += 1;
+        crop.days_in_stage = 0;
+        return true;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grow_crop_stages() {
+        struct TestCase {
+            watered: Vec<bool>,
+            expected_stage: usize,
+        }
+
+        let definition = CropDefinition {
+            id: "wheat".to_string(),
+            name: "Wheat".to_string(),
+            needs_water: true,
+            stages: vec!(
+                CropStage { atlas_index: 0, days: 1 },
+                CropStage { atlas_index: 1, days: 2 },
+                CropStage { atlas_index: 2, days: 0 },
+            ),
+            harvest: CropYield { item: "wheat".to_string(), amount: 1 },
+        };
+
+        let cases = vec!(
+            TestCase { watered: vec!(), expected_stage: 0 },
+            TestCase { watered: vec!(false, false), expected_stage: 0 },
+            TestCase { watered: vec!(true), expected_stage: 1 },
+            TestCase { watered: vec!(true, true, false), expected_stage: 1 },
+            TestCase { watered: vec!(true, true, false, true), expected_stage: 2 },
+            TestCase { watered: vec!(true, true, true, true, true, true), expected_stage: 2 },
+        );
+
+        for c in cases {
+            let mut crop = PlantedCrop { id: "wheat".to_string(), stage: 0, days_in_stage: 0, entity: Entity::PLACEHOLDER };
+            for watered in c.watered {
+                super::grow_crop(&mut crop, &definition, watered);
+            }
+            assert_eq!(crop.stage, c.expected_stage);
+        }
+    }
+
+    #[test]
+    fn crop_problems() {
+        struct TestCase {
+            stages: Vec<CropStage>,
+            expected: Vec<&'static str>,
+        }
+
+        let cases = vec!(
+            TestCase {
+                stages: vec!(CropStage { atlas_index: 0, days: 1 }, CropStage { atlas_index: 1, days: 0 }),
+                expected: vec!(),
+            },
+            TestCase { stages: vec!(), expected: vec!("has no stages") },
+            TestCase {
+                stages: vec!(CropStage { atlas_index: 0, days: 0 }, CropStage { atlas_index: 1, days: 0 }),
+                expected: vec!("stage 0 lasts no days"),
+            },
+        );
+
+        for c in cases {
+            let definition = CropDefinition {
+                id: "wheat".to_string(),
+                name: "Wheat".to_string(),
+                needs_water: false,
+                stages: c.stages,
+                harvest: CropYield { item: "wheat".to_string(), amount: 1 },
+            };
+            assert_eq!(definition.problems(), c.expected);
+        }
+    }
+}
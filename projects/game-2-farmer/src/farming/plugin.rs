@@ -0,0 +1,279 @@
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::{AssetEvent, AssetServer, Assets, Handle};
+use bevy::color::Color;
+use bevy::image::Image;
+use bevy::math::{IVec2, Vec2, Vec3};
+use bevy::prelude::{error, in_state, info, resource_changed, warn, Changed, Entity, Commands, EventReader, EventWriter, IntoSystemConfigs, Local, Query, Res, ResMut, Resource, Single, Sprite, TextureAtlas, TextureAtlasLayout, Transform, Trigger, With, Without};
+use bevy_common_assets::json::JsonAssetPlugin;
+use game_lab_utils::asset_manifest_plugin::{AssetManifest, ImageAssets};
+use game_lab_utils::debug_plugin::{DebugKey, Debugger};
+use game_lab_utils::gizmo_plugin::LABELS_KEY;
+use game_lab_utils::inventory_plugin::{Inventory, ItemRegistry};
+use game_lab_utils::loading_plugin::LoadingTracker;
+use game_lab_utils::y_sort_plugin::{SortingLayer, YSort};
+use crate::controller::{Action, ActionEvent};
+use crate::farming::debug::{debug_farming, label_farm_tiles};
+use crate::hotbar::Hotbar;
+use crate::farming::{grow_crop, CropDefinitions, CropPlantedEvent, CropSprite, Crops, EquippedTool, FarmField, FarmTile, HarvestEvent, PlantedCrop, TileInteractEvent, Tool};
+use crate::map::{tile_to_world, world_to_tile, MapLayout, TILE_SIZE};
+use crate::player::player::{Player, PlayerTarget};
+use crate::state::GameState;
+use crate::world_time::DayRolloverEvent;
+
+// Centre tile of the tilled dirt sheet
+const SOIL_INDEX: usize = 12;
+const WATERED_SOIL_COLOR: Color = Color::srgb(0.65, 0.55, 0.5);
+const FARMING_KEY: DebugKey = DebugKey::new("World/Farming");
+
+pub struct FarmingPlugin;
+
+impl Plugin for FarmingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(JsonAssetPlugin::<CropDefinitions>::new(&["crops.json"]))
+            .add_event::<HarvestEvent>()
+            .add_event::<TileInteractEvent>()
+            .init_resource::<Crops>()
+            .init_resource::<FarmField>()
+            .add_systems(Startup, setup)
+            .add_systems(Update, (sync_crop_definitions, sync_crop_sprites.run_if(resource_changed::<Crops>)).chain())
+            .add_systems(Update, (detect_interact, interact_with_farm_tile, till_soil).chain().run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (grow_crops, update_soil_sprites, collect_harvest, log_harvest).chain().after(till_soil))
+            .add_observer(consume_seeds)
+            .add_debug_system(debug_farming, FARMING_KEY)
+            .add_debug_system(label_farm_tiles, LABELS_KEY);
+    }
+}
+
+#[derive(Resource)]
+pub struct FarmingResources {
+    crops: Handle<CropDefinitions>,
+    soil_image: Handle<Image>,
+    soil_atlas: Handle<TextureAtlasLayout>,
+    crop_image: Handle<Image>,
+    crop_atlas: Handle<TextureAtlasLayout>,
+}
+
+fn setup(mut commands: Commands, mut tracker: ResMut<LoadingTracker>, asset_server: Res<AssetServer>, manifest: Res<AssetManifest>, images: Res<ImageAssets>) {
+    let soil = images.atlas("tilled_dirt");
+    let plants = images.atlas("plants");
+    let resources = FarmingResources {
+        crops: manifest.load_data(&asset_server, "crops"),
+        soil_image: soil.image.clone(),
+        soil_atlas: soil.layout.clone(),
+        crop_image: plants.image.clone(),
+        crop_atlas: plants.layout.clone(),
+    };
+    tracker.track("crops", resources.crops.clone());
+    commands.insert_resource(resources);
+}
+
+fn sync_crop_definitions(
+    mut reader: EventReader<AssetEvent<CropDefinitions>>,
+    mut crops: ResMut<Crops>,
+    definitions: Res<Assets<CropDefinitions>>,
+    resources: Res<FarmingResources>,
+) {
+    for event in reader.read() {
+        if !event.is_loaded_with_dependencies(&resources.crops) && !event.is_modified(&resources.crops) {
+            continue;
+        }
+        if let Some(loaded) = definitions.get(&resources.crops) {
+            crops.0 = loaded.crops.iter()
+                .filter(|c| {
+                    let problems = c.problems();
+                    for problem in &problems {
+                        error!("Skipping crop \"{}\": {}", c.id, problem);
+                    }
+                    problems.is_empty()
+                })
+                .map(|c| (c.id.clone(), c.clone()))
+                .collect();
+        }
+    }
+}
+
+// Crops planted or loaded before their definitions were in show the first atlas index until now
+fn sync_crop_sprites(tiles: Query<&FarmTile>, mut sprites: Query<&mut Sprite, With<CropSprite>>, crops: Res<Crops>) {
+    for tile in tiles.iter() {
+        if let Some(crop) = &tile.crop
+            && let Some(definition) = crops.0.get(&crop.id)
+            && let Ok(mut sprite) = sprites.get_mut(crop.entity)
+            && let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = definition.atlas_index(crop.stage);
+        }
+    }
+}
+
+pub fn spawn_farm_tile(commands: &mut Commands, resources: &FarmingResources, tile: FarmTile) -> Entity {
+    let translation = Vec3::from((tile_to_world(tile.coords), 0.0));
+    commands.spawn((
+        tile,
+        Sprite {
+            image: resources.soil_image.clone(),
+            texture_atlas: Some(TextureAtlas { layout: resources.soil_atlas.clone(), index: SOIL_INDEX }),
+            custom_size: Some(Vec2::splat(TILE_SIZE)),
+            ..Default::default()
+        },
+        Transform::from_translation(translation),
+        YSort::new(SortingLayer::Ground).with_offset(-1.0),
+    )).id()
+}
+
+pub fn spawn_crop_sprite(commands: &mut Commands, resources: &FarmingResources, coords: IVec2, index: usize) -> Entity {
+    commands.spawn((
+        CropSprite,
+        Sprite {
+            image: resources.crop_image.clone(),
+            texture_atlas: Some(TextureAtlas { layout: resources.crop_atlas.clone(), index }),
+            custom_size: Some(Vec2::splat(TILE_SIZE)),
+            ..Default::default()
+        },
+        Transform::from_translation(Vec3::from((tile_to_world(coords), 0.0))),
+        YSort::new(SortingLayer::World),
+    )).id()
+}
+
+// Interact is sent every frame it is held, only the first frame does anything
+fn detect_interact(
+    mut reader: EventReader<ActionEvent>,
+    mut writer: EventWriter<TileInteractEvent>,
+    mut held: Local<bool>,
+    player: Single<&EquippedTool, With<Player>>,
+    target: Single<&Transform, (With<PlayerTarget>, Without<Player>)>,
+) {
+    for event in reader.read() {
+        match event {
+            ActionEvent(Action::Interact, 1) if !*held => {
+                *held = true;
+                writer.send(TileInteractEvent {
+                    coords: world_to_tile(target.translation.truncate()),
+                    tool: player.0.clone(),
+                });
+            },
+            ActionEvent(Action::Interact, 0) => *held = false,
+            _ => {}
+        }
+    }
+}
+
+fn interact_with_farm_tile(
+    mut commands: Commands,
+    mut reader: EventReader<TileInteractEvent>,
+    mut harvest_writer: EventWriter<HarvestEvent>,
+    mut tiles: Query<&mut FarmTile>,
+    field: Res<FarmField>,
+    crops: Res<Crops>,
+    resources: Res<FarmingResources>,
+) {
+    for event in reader.read() {
+        let Some(mut tile) = field.0.get(&event.coords).and_then(|e| tiles.get_mut(*e).ok()) else {
+            continue;
+        };
+
+        // Anything in hand picks a ripe crop
+        if let Some(crop) = &tile.crop
+            && let Some(definition) = crops.0.get(&crop.id)
+            && crop.stage >= definition.last_stage() {
+            harvest_writer.send(HarvestEvent {
+                crop: crop.id.clone(),
+                item: definition.harvest.item.clone(),
+                amount: definition.harvest.amount,
+                coords: event.coords,
+            });
+            commands.entity(crop.entity).despawn();
+            tile.crop = None;
+            continue;
+        }
+
+        match &event.tool {
+            Tool::WateringCan => tile.watered = true,
+            Tool::Seeds { crop: id, item } if tile.crop.is_none() => {
+                let Some(definition) = crops.0.get(id) else {
+                    continue;
+                };
+                let entity = spawn_crop_sprite(&mut commands, &resources, event.coords, definition.atlas_index(0));
+                tile.crop = Some(PlantedCrop { id: id.clone(), stage: 0, days_in_stage: 0, entity });
+                if let Some(seed) = item {
+                    commands.trigger(CropPlantedEvent { seed: seed.clone() });
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+fn till_soil(
+    mut commands: Commands,
+    mut reader: EventReader<TileInteractEvent>,
+    mut field: ResMut<FarmField>,
+    resources: Res<FarmingResources>,
+    layout: Option<Res<MapLayout>>,
+) {
+    let Some(layout) = layout else {
+        return;
+    };
+    for event in reader.read() {
+        if event.tool != Tool::Hoe || field.0.contains_key(&event.coords) || !layout.is_ground(event.coords) {
+            continue;
+        }
+        let entity = spawn_farm_tile(&mut commands, &resources, FarmTile { coords: event.coords, watered: false, crop: None });
+        field.0.insert(event.coords, entity);
+    }
+}
+
+fn grow_crops(
+    mut reader: EventReader<DayRolloverEvent>,
+    mut tiles: Query<&mut FarmTile>,
+    mut sprites: Query<&mut Sprite, With<CropSprite>>,
+    crops: Res<Crops>,
+) {
+    for _ in reader.read() {
+        for mut tile in tiles.iter_mut() {
+            let watered = tile.watered;
+            if let Some(crop) = &mut tile.crop
+                && let Some(definition) = crops.0.get(&crop.id)
+                && grow_crop(crop, definition, watered)
+                && let Ok(mut sprite) = sprites.get_mut(crop.entity)
+                && let Some(atlas) = &mut sprite.texture_atlas {
+                atlas.index = definition.atlas_index(crop.stage);
+            }
+            // Soil dries out overnight
+            tile.watered = false;
+        }
+    }
+}
+
+fn update_soil_sprites(mut tiles: Query<(&FarmTile, &mut Sprite), Changed<FarmTile>>) {
+    for (tile, mut sprite) in tiles.iter_mut() {
+        sprite.color = if tile.watered { WATERED_SOIL_COLOR } else { Color::WHITE };
+    }
+}
+
+fn collect_harvest(mut reader: EventReader<HarvestEvent>, mut inventory: Single<&mut Inventory, With<Player>>, registry: Res<ItemRegistry>) {
+    for event in reader.read() {
+        let leftover = inventory.add(&event.item, event.amount, &registry);
+        if leftover > 0 {
+            warn!("No room for {} {}", leftover, event.item);
+        }
+    }
+}
+
+// Takes from the slot in hand if it holds the seeds, otherwise from anywhere
+fn consume_seeds(trigger: Trigger<CropPlantedEvent>, player: Single<(&mut Inventory, &Hotbar), With<Player>>) {
+    let (mut inventory, hotbar) = player.into_inner();
+    let seed = &trigger.seed;
+    let in_hand = inventory.slots.get(hotbar.selected)
+        .and_then(|s| s.as_ref())
+        .is_some_and(|s| &s.item == seed);
+    if in_hand {
+        inventory.remove_from_slot(hotbar.selected, 1);
+    } else {
+        inventory.remove(seed, 1);
+    }
+}
+
+fn log_harvest(mut reader: EventReader<HarvestEvent>) {
+    for event in reader.read() {
+        info!("Harvested {} {} from {} at {:?}", event.amount, event.item, event.crop, event.coords);
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::color::Color;
+use bevy::prelude::{in_state, AlignItems, BackgroundColor, BorderColor, BuildChildren, ChildBuild, Commands, Component, DetectChanges, EventReader, ImageNode, IntoSystemConfigs, JustifyContent, Node, PositionType, Query, Ref, Res, Single, Text, TextFont, UiRect, Val, Visibility, With};
+use game_lab_utils::inventory_plugin::{Inventory, ItemCategory, ItemDefinition, ItemRegistry};
+use crate::controller::{Action, ActionEvent, HOTBAR_SLOTS};
+use crate::farming::{EquippedTool, Tool};
+use crate::player::player::Player;
+use crate::state::GameState;
+
+const SLOT_SIZE: f32 = 40.0;
+const SLOT_COLOR: Color = Color::srgba(0.1, 0.1, 0.1, 0.6);
+const SLOT_BORDER: Color = Color::srgba(0.4, 0.4, 0.4, 0.8);
+const SELECTED_BORDER: Color = Color::srgb(1.0, 0.85, 0.3);
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hotbar_ui)
+            .add_systems(Update, (select_hotbar_slot.run_if(in_state(GameState::Playing)), equip_from_hotbar, update_hotbar_ui).chain());
+    }
+}
+
+// Which inventory slot is in hand, the hotbar is the first HOTBAR_SLOTS of the inventory
+#[derive(Component, Default)]
+pub struct Hotbar {
+    pub selected: usize,
+}
+
+#[derive(Component)]
+struct HotbarSlot(usize);
+
+#[derive(Component)]
+struct HotbarIcon(usize);
+
+#[derive(Component)]
+struct HotbarCount(usize);
+
+// Items say what they do through their properties so new seeds/tools only need data
+fn tool_for_item(item: &ItemDefinition) -> Tool {
+    match item.category {
+        ItemCategory::Tool => match item.properties.get("tool").map(|t| t.as_str()) {
+            Some("hoe") => Tool::Hoe,
+            Some("watering_can") => Tool::WateringCan,
+            _ => Tool::Hand,
+        },
+        ItemCategory::Seed => match item.properties.get("crop") {
+            Some(crop) => Tool::Seeds { crop: crop.clone(), item: Some(item.id.clone()) },
+            None => Tool::Hand,
+        },
+        _ => Tool::Hand,
+    }
+}
+
+fn spawn_hotbar_ui(mut commands: Commands) {
+    commands.spawn(Node {
+        position_type: PositionType::Absolute,
+        bottom: Val::Px(8.0),
+        width: Val::Percent(100.0),
+        justify_content: JustifyContent::Center,
+        column_gap: Val::Px(4.0),
+        ..Default::default()
+    }).with_children(|parent| {
+        for slot in 0..HOTBAR_SLOTS {
+            parent.spawn((
+                HotbarSlot(slot),
+                Node {
+                    width: Val::Px(SLOT_SIZE),
+                    height: Val::Px(SLOT_SIZE),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                BackgroundColor(SLOT_COLOR),
+                BorderColor(SLOT_BORDER),
+            )).with_children(|slot_node| {
+                slot_node.spawn((
+                    HotbarIcon(slot),
+                    ImageNode::default(),
+                    Node {
+                        width: Val::Px(SLOT_SIZE - 8.0),
+                        height: Val::Px(SLOT_SIZE - 8.0),
+                        ..Default::default()
+                    },
+                    Visibility::Hidden,
+                ));
+                slot_node.spawn((
+                    HotbarCount(slot),
+                    Text::new(""),
+                    TextFont { font_size: 12.0, ..Default::default() },
+                    Node {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(2.0),
+                        bottom: Val::Px(0.0),
+                        ..Default::default()
+                    },
+                ));
+            });
+        }
+    });
+}
+
+fn select_hotbar_slot(mut reader: EventReader<ActionEvent>, mut hotbar: Single<&mut Hotbar, With<Player>>) {
+    for event in reader.read() {
+        if let ActionEvent(Action::HotbarSlot(slot), 1) = event
+            && hotbar.selected != *slot {
+            hotbar.selected = *slot;
+        }
+    }
+}
+
+type HotbarEquipQuery<'a> = (Ref<'a, Hotbar>, Ref<'a, Inventory>, &'a mut EquippedTool);
+
+// Only re-equips when something changed so the farming debug picker can still override it
+fn equip_from_hotbar(mut player: Single<HotbarEquipQuery, With<Player>>, registry: Res<ItemRegistry>) {
+    let (hotbar, inventory, tool) = &mut *player;
+    if !hotbar.is_changed() && !inventory.is_changed() && !registry.is_changed() {
+        return;
+    }
+
+    let equipped = inventory.slots.get(hotbar.selected)
+        .and_then(|slot| slot.as_ref())
+        .and_then(|stack| registry.get(&stack.item))
+        .map(tool_for_item)
+        .unwrap_or_default();
+    if tool.0 != equipped {
+        tool.0 = equipped;
+    }
+}
+
+fn update_hotbar_ui(
+    player: Single<(Ref<Hotbar>, Ref<Inventory>), With<Player>>,
+    registry: Res<ItemRegistry>,
+    mut slots: Query<(&HotbarSlot, &mut BorderColor)>,
+    mut icons: Query<(&HotbarIcon, &mut ImageNode, &mut Visibility)>,
+    mut counts: Query<(&HotbarCount, &mut Text)>,
+) {
+    let (hotbar, inventory) = player.into_inner();
+    if !hotbar.is_changed() && !inventory.is_changed() && !registry.is_changed() {
+        return;
+    }
+
+    for (slot, mut border) in slots.iter_mut() {
+        border.0 = if slot.0 == hotbar.selected { SELECTED_BORDER } else { SLOT_BORDER };
+    }
+
+    for (icon, mut image, mut visibility) in icons.iter_mut() {
+        let stack = inventory.slots.get(icon.0).and_then(|s| s.as_ref());
+        match stack.and_then(|s| registry.icon(&s.item)) {
+            Some((handle, atlas)) => {
+                *image = ImageNode::from_atlas_image(handle, atlas);
+                *visibility = Visibility::Inherited;
+            },
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    for (count, mut text) in counts.iter_mut() {
+        text.0 = match inventory.slots.get(count.0).and_then(|s| s.as_ref()) {
+            Some(stack) if stack.count > 1 => format!("{}", stack.count),
+            _ => String::new(),
+        };
+    }
+}
@@ -1,47 +1,69 @@
 mod camera;
 mod player;
 mod controller;
+mod farming;
+mod hotbar;
 mod map;
+mod save;
+mod shadow;
+mod state;
+mod tuning;
+mod world_time;
 
 use bevy::app::{App};
 use bevy::DefaultPlugins;
-use bevy::prelude::{ImagePlugin, PluginGroup};
-use game_lab_utils::internal_asset_plugin::InternalAssetPlugin;
+use bevy::log::LogPlugin;
+use bevy::math::Vec2;
+use bevy::prelude::{default, ImagePlugin, PluginGroup};
+use game_lab_utils::asset_manifest_plugin::AssetManifestPlugin;
+use game_lab_utils::internal_asset_plugin::{AssetSourcesPlugin, InternalAssetPlugin};
+use game_lab_utils::console_plugin::ConsolePlugin;
+use game_lab_utils::capture_plugin::CaptureSettings;
 use game_lab_utils::debug_plugin::{DebugPlugin};
+use game_lab_utils::gizmo_plugin::GizmoPlugin;
+use game_lab_utils::inventory_plugin::InventoryPlugin;
+use game_lab_utils::system_timings::system_timing_layer;
+use game_lab_utils::tuning_plugin::TuningPlugin;
+use game_lab_utils::y_sort_plugin::YSortPlugin;
 use crate::camera::CameraPlugin;
 use crate::controller::plugin::ControllerPlugin;
-use crate::map::MapPlugin;
+use crate::farming::plugin::FarmingPlugin;
+use crate::hotbar::HotbarPlugin;
+use crate::map::{MapPlugin, TILE_SIZE};
 use crate::player::plugin::PlayerPlugin;
-
-const ASSET_ROOT_FOLDER_HANA: &str = "hana-caraka";
-const ASSET_ROOT_FOLDER_SPROUT: &str = "sprout-lands";
+use crate::save::GameSavePlugin;
+use crate::shadow::ShadowPlugin;
+use crate::state::StatePlugin;
+use crate::tuning::Tuning;
+use crate::world_time::DEFAULT_DAY_LENGTH;
+use crate::world_time::plugin::WorldTimePlugin;
 
 fn main() {
     App::new()
+        .add_plugins(AssetSourcesPlugin::new("game2"))
         .add_plugins(DefaultPlugins
             .set(InternalAssetPlugin::new())
-            .set(ImagePlugin::default_nearest()))
-        .add_plugins(DebugPlugin::new(true))
+            .set(ImagePlugin::default_nearest())
+            .set(LogPlugin { custom_layer: system_timing_layer, ..default() }))
+        .add_plugins(DebugPlugin::new(false)
+            .with_state_file("saves/game-2-farmer/debug.json")
+            // Clips of the farm without the HUD or debug windows over it
+            .with_capture(CaptureSettings { directory: "captures/game-2-farmer".into(), world_only: true, ..default() }))
+        .add_plugins(GizmoPlugin::new().with_tile_size(TILE_SIZE).with_origin(Vec2::new(-TILE_SIZE, 0.0)).with_rows_down())
+        .add_plugins(ConsolePlugin::new().with_startup_script("game://console.txt"))
+        .add_plugins(AssetManifestPlugin::new("game://assets.json"))
+        .add_plugins(YSortPlugin::new())
+        .add_plugins(TuningPlugin::<Tuning>::new("game://game.tuning.ron"))
+        .add_plugins(StatePlugin)
         .add_plugins(ControllerPlugin::new())
         .add_plugins(MapPlugin{})
         .add_plugins(PlayerPlugin)
+        .add_plugins(ShadowPlugin)
         .add_plugins(CameraPlugin)
-        // .add_systems(Update, gizmo_grid.run_if(debug_enable))
+        .add_plugins(WorldTimePlugin { day_length: DEFAULT_DAY_LENGTH })
+        .add_plugins(InventoryPlugin::new("game://items.json"))
+        .add_plugins(FarmingPlugin)
+        .add_plugins(HotbarPlugin)
+        .add_plugins(GameSavePlugin)
         .run();
 }
-
-fn asset_folder_hana(folder: &str) -> String {
-    format!("internal/{}/{}", ASSET_ROOT_FOLDER_HANA, folder)
-}
-
-fn asset_folder_sprout(folder: &str) -> String {
-    format!("internal/{}/{}", ASSET_ROOT_FOLDER_SPROUT, folder)
-}
-
-// fn gizmo_grid(mut gizmos: Gizmos, q: Single<(&Camera, &Transform)>) {
-//     let (_, transform) = q.into_inner();
-//     let mut translation = transform.translation.truncate() + Vec2::new(16.0, 16.0);
-//     translation.x = (translation.x / 32.0).round() * 32.0;
-//     translation.y = (translation.y / 32.0).round() * 32.0;
-//     gizmos.grid_2d(Isometry2d::new(translation, Rot2::IDENTITY), UVec2::splat(100), Vec2::splat(32.0), GREY);
-// }
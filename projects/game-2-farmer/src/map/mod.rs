@@ -2,15 +2,51 @@ use std::time::Duration;
 use bevy::app::{App, Plugin, Startup, Update};
 use bevy::asset::{Asset, AssetServer, Assets, Handle};
 use bevy::image::Image;
-use bevy::math::{UVec2, Vec2};
-use bevy::prelude::{Commands, Component, Query, Res, ResMut, Resource, Single, TextureAtlas, TextureAtlasLayout, Time, Timer, TimerMode, Transform, TypePath, With};
+use bevy::math::{IVec2, Vec2};
+use bevy::prelude::{Commands, Component, DetectChanges, IntoSystemConfigs, OnExit, Query, Res, ResMut, Resource, Single, TextureAtlas, TextureAtlasLayout, Time, Timer, TimerMode, Transform, TypePath, With};
 use bevy::sprite::Sprite;
 use bevy_common_assets::json::JsonAssetPlugin;
 use ::serde::Deserialize;
-use crate::{asset_folder_sprout};
+use game_lab_utils::asset_manifest_plugin::{AssetManifest, ImageAssets};
+use game_lab_utils::gizmo_plugin::DebugCollider;
+use game_lab_utils::loading_plugin::LoadingTracker;
+use game_lab_utils::y_sort_plugin::{SortingLayer, YSort};
+use crate::state::GameState;
+use crate::tuning::Tuning;
 
 pub struct MapPlugin { }
 
+pub const MAP_WIDTH: usize = 100;
+pub const TILE_SIZE: f32 = 32.0;
+
+// Which tiles have land on them, built once the level has loaded
+#[derive(Resource)]
+pub struct MapLayout {
+    pub ground: Vec<bool>,
+}
+
+impl MapLayout {
+    pub fn is_ground(&self, coords: IVec2) -> bool {
+        if coords.x < 0 || coords.y < 0 || coords.x as usize >= MAP_WIDTH {
+            return false;
+        }
+        let index = coords.y as usize * MAP_WIDTH + coords.x as usize;
+        self.ground.get(index).copied().unwrap_or(false)
+    }
+}
+
+// Tiles are centred on their grid square, hence the half tile offsets
+pub fn tile_to_world(coords: IVec2) -> Vec2 {
+    Vec2::new(coords.x as f32 * TILE_SIZE - TILE_SIZE / 2.0, -(coords.y as f32 * TILE_SIZE) - TILE_SIZE / 2.0)
+}
+
+pub fn world_to_tile(position: Vec2) -> IVec2 {
+    IVec2::new(
+        ((position.x + TILE_SIZE / 2.0) / TILE_SIZE).round() as i32,
+        ((-position.y - TILE_SIZE / 2.0) / TILE_SIZE).round() as i32,
+    )
+}
+
 #[derive(Asset, TypePath, Deserialize)]
 struct MapData {
     map: Layer,
@@ -34,7 +70,8 @@ impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(JsonAssetPlugin::<MapData>::new(&[".json"]))
             .add_systems(Startup, setup)
-            .add_systems(Update, (load_level, water_tile));
+            .add_systems(OnExit(GameState::Loading), load_level)
+            .add_systems(Update, (set_water_speed, water_tile).chain());
 
             // .add_systems(Update, );//.add_systems(Update, update);
     }
@@ -48,26 +85,23 @@ struct MapState {
     water: Handle<Image>,
     atlas: Handle<TextureAtlasLayout>,
     water_atlas: Handle<TextureAtlasLayout>,
-    completed: bool,
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,) {
-    let handle = asset_server.load("internal/maps/game2/tiled/level_0.json");
-    let image = asset_server.load(asset_folder_sprout("tilesets/grass/grass.png"));
-    let water = asset_server.load(asset_folder_sprout("tilesets/water.png"));
-    let atlas = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(UVec2::splat(16),39,7,None, None));
-    let water_atlas = texture_atlas_layouts.add(TextureAtlasLayout::from_grid(UVec2::splat(16),4,1,None, None));
+fn setup(mut commands: Commands, mut tracker: ResMut<LoadingTracker>, asset_server: Res<AssetServer>, manifest: Res<AssetManifest>, images: Res<ImageAssets>, tuning: Res<Tuning>) {
+    let handle: Handle<MapData> = manifest.load_data(&asset_server, "level_0");
+    tracker.track("level", handle.clone());
+    let grass = images.atlas("grass");
+    let water = images.atlas("water");
     commands.insert_resource(MapState {
         level: handle,
-        image,
-        atlas,
-        water_atlas,
+        image: grass.image.clone(),
+        atlas: grass.layout.clone(),
+        water_atlas: water.layout.clone(),
         // layer: 1,
-        water,
-        completed: false,
+        water: water.image.clone(),
     });
 
-    commands.spawn(WaterTimer{ timer: Timer::new(Duration::from_millis(300), TimerMode::Repeating) });
+    commands.spawn(WaterTimer{ timer: Timer::new(Duration::from_millis(tuning.map.water_frame_ms), TimerMode::Repeating) });
 }
 
 #[derive(Component)]
@@ -81,12 +115,12 @@ struct WaterTimer {
    timer: Timer,
 }
 
-fn load_level(mut commands: Commands, datas: Res<Assets<MapData>>, mut map: ResMut<MapState>) {
-    if map.completed {
-        return;
-    }
+// Runs once the loading screen is done, so the level data and tile sheets are already in
+fn load_level(mut commands: Commands, datas: Res<Assets<MapData>>, map: Res<MapState>) {
 
     if let  Some(t) = datas.get(map.level.id()) {
+        let mut ground = vec![false; t.map.layer.first().map(|l| l.data.content.len()).unwrap_or(0)];
+        let mut water = vec![];
         for layer_index in 0..t.map.layer.len() {
            let layer = t.map.layer.get(layer_index).unwrap();
             if layer_index == 0 {
@@ -94,7 +128,7 @@ fn load_level(mut commands: Commands, datas: Res<Assets<MapData>>, mut map: ResM
                     // let tile = layer.data.content[x];
                     let xpos = (((x % 100) * 32) as f32) - 16.0;
                     let ypos = -(((x / 100) * 32) as f32) - 16.0;
-                    commands.spawn((
+                    let entity = commands.spawn((
                         WaterTile,
                         Sprite {
                             image: map.water.clone(),
@@ -106,8 +140,10 @@ fn load_level(mut commands: Commands, datas: Res<Assets<MapData>>, mut map: ResM
                             ..Default::default()
                         },
                         // Transform::from_xyz(0.0,0.0,1.0)
-                        Transform::from_xyz(xpos as f32, ypos as f32,0.0)
-                    ));
+                        Transform::from_xyz(xpos as f32, ypos as f32,0.0),
+                        YSort::new(SortingLayer::Background),
+                    )).id();
+                    water.push((x, entity));
                 }
             } else {
                 for x in 0..layer.data.content.len() {
@@ -118,6 +154,9 @@ fn load_level(mut commands: Commands, datas: Res<Assets<MapData>>, mut map: ResM
                     if tile == -1 {
                         continue;
                     }
+                    if let Some(g) = ground.get_mut(x) {
+                        *g = true;
+                    }
                     commands.spawn((
                         Tile,
                         Sprite {
@@ -130,12 +169,25 @@ fn load_level(mut commands: Commands, datas: Res<Assets<MapData>>, mut map: ResM
                             ..Default::default()
                         },
                         // Transform::from_xyz(0.0,0.0,1.0)
-                        Transform::from_xyz(xpos as f32, ypos as f32, 1.0)
+                        Transform::from_xyz(xpos as f32, ypos as f32, 1.0),
+                        YSort::new(SortingLayer::Ground),
                     ));
                 }
             }
         }
-        map.completed = true;
+        // Only the water that isn't under land blocks anything, the rest isn't worth outlining
+        for (x, entity) in water {
+            if !ground.get(x).copied().unwrap_or(false) {
+                commands.entity(entity).insert(DebugCollider::new(Vec2::splat(TILE_SIZE)));
+            }
+        }
+        commands.insert_resource(MapLayout { ground });
+    }
+}
+
+fn set_water_speed(tuning: Res<Tuning>, mut timer: Single<&mut WaterTimer>) {
+    if tuning.is_changed() {
+        timer.timer.set_duration(Duration::from_millis(tuning.map.water_frame_ms));
     }
 }
 
@@ -119,6 +119,10 @@ pub fn animated_player_sprite(time: Res<Time>, player: Single<(&mut Sprite, &mut
 
 #[cfg(test)]
 mod tests {
+    use bevy::math::Vec2;
+    use bevy::prelude::{IntoSystemConfigs, Transform, Update, resource_added};
+    use game_lab_utils::asset_manifest_plugin::{AssetManifestPlugin, ImageAssets};
+    use game_lab_utils::golden_image::{DEFAULT_TOLERANCE, GoldenScene, assert_golden, golden_path};
     use super::*;
 
     #[test]
@@ -196,4 +200,30 @@ mod tests {
             assert_eq!(a.last, c.expected_last);
         }
     }
-}
\ No newline at end of file
+
+    // First frame of each direction on the top row and last frame below, from the walk sheet
+    #[test]
+    #[ignore = "needs a software renderer, run with cargo test -- --ignored"]
+    fn walk_frames_match_golden() {
+        fn spawn_frames(mut commands: Commands, images: Res<ImageAssets>) {
+            let atlas = images.atlas("player_walk");
+            for (column, dir) in [Direction::East, Direction::West, Direction::South, Direction::North].into_iter().enumerate() {
+                let indices = PlayerAnimationsIndices::from_dir(dir, 8);
+                for (row, index) in [indices.first, indices.last].into_iter().enumerate() {
+                    commands.spawn((
+                        Sprite::from_atlas_image(images.image("player_walk"), atlas.texture_atlas(index)),
+                        Transform::from_xyz(column as f32 * 80.0 - 120.0, 40.0 - row as f32 * 80.0, 0.0),
+                    ));
+                }
+            }
+        }
+
+        let scene = GoldenScene::with_fixtures(env!("CARGO_MANIFEST_DIR"), "game2").with_size(320, 160).with_camera_at(Vec2::ZERO);
+        let frame = scene.render(|app| {
+            app.add_plugins(AssetManifestPlugin::new("game://assets.json"))
+                .add_systems(Update, spawn_frames.run_if(resource_added::<ImageAssets>));
+        });
+
+        assert_golden(&golden_path(env!("CARGO_MANIFEST_DIR"), "walk_frames"), &frame, DEFAULT_TOLERANCE);
+    }
+}
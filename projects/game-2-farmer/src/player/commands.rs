@@ -0,0 +1,20 @@
+use bevy::prelude::{In, Single, Transform, With};
+use game_lab_utils::console_plugin::{ConsoleArgs, ConsoleResult};
+use crate::player::player::Player;
+
+pub fn teleport_player(In(args): In<ConsoleArgs>, mut transform: Single<&mut Transform, With<Player>>) -> ConsoleResult {
+    transform.translation.x = args.float(0);
+    transform.translation.y = args.float(1);
+    Ok(format!("Player at {}, {}", transform.translation.x, transform.translation.y))
+}
+
+// Running stays the same amount faster than walking unless it's given too
+pub fn set_player_speed(In(args): In<ConsoleArgs>, mut player: Single<&mut Player>) -> ConsoleResult {
+    let walk_speed = args.float(0);
+    if walk_speed <= 0.0 {
+        return Err("Speed should be above 0".to_string());
+    }
+    player.run_speed = if args.len() > 1 { args.float(1) } else { player.run_speed * walk_speed / player.walk_speed };
+    player.walk_speed = walk_speed;
+    Ok(format!("Walk speed {}, run speed {}", player.walk_speed, player.run_speed))
+}
@@ -1,10 +1,11 @@
 use std::fmt::{Display};
 use bevy::color::palettes::css::{RED, BLUE, GREEN};
 use bevy::math::Isometry2d;
-use bevy::prelude::{GizmoPrimitive2d, Gizmos, Rectangle, Single, Transform, With};
+use bevy::prelude::{GizmoPrimitive2d, Gizmos, Rectangle, Res, Single, Transform, With};
 use bevy::sprite::Sprite;
 use bevy_egui::{egui, EguiContexts};
 use bevy_egui::egui::Color32;
+use game_lab_utils::debug_plugin::{debug_window, DebugState};
 use crate::player::animation::{PlayerTimers, PlayerAnimationsIndices, AnimationState, PlayerAnimationState};
 use crate::player::player::{Player, PlayerDirection, PlayerTarget};
 
@@ -47,9 +48,9 @@ pub fn draw_sprite_bounding_box(
     );
 }
 
-pub fn debug_player_state(mut ctx: EguiContexts, query: Single<(&Player, &Sprite, &PlayerAnimationsIndices, &PlayerDirection, &PlayerTimers, &PlayerAnimationState)>) {
+pub fn debug_player_state(mut ctx: EguiContexts, debug_state: Res<DebugState>, query: Single<(&Player, &Sprite, &PlayerAnimationsIndices, &PlayerDirection, &PlayerTimers, &PlayerAnimationState)>) {
     let (player, sprite, animation_indices, direction, timers, state) = query.into_inner();
-    egui::Window::new("PlayerState").max_width(300.0).resizable([false,false]).movable(false).show(ctx.ctx_mut(), |ui| {
+    debug_window("PlayerState", &debug_state).movable(false).show(ctx.ctx_mut(), |ui| {
         ui.scope(|ui| {
 
             egui::Grid::new("my_grid")
@@ -1,6 +1,7 @@
 pub mod plugin;
 pub mod player;
-mod animation;
+pub mod animation;
 mod debug;
 mod controller;
+mod commands;
 mod sprite_sheet;
@@ -1,18 +1,26 @@
-use crate::asset_folder_hana;
 use crate::player::animation::{PlayerTimers, PlayerAnimationsIndices, PlayerAnimationState, AnimationState};
 use crate::player::controller::{PlayerDirectionChange, PlayerMovementEvent};
 use crate::player::sprite_sheet::{PlayerSpriteSheet, SPRITE_SHEET_CONFIG};
-use bevy::asset::{AssetServer, Assets};
 use bevy::prelude::*;
-use bevy::sprite::{AlphaMode2d, Material2d, Sprite};
+use bevy::sprite::Sprite;
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::time::Duration;
 use bevy::math::vec2;
-use bevy::render::render_resource::{AsBindGroup, ShaderRef};
 use crate::controller::Direction;
-
-#[derive(Component, Clone, Debug)]
+use crate::farming::EquippedTool;
+use crate::hotbar::Hotbar;
+use crate::shadow::ShadowCaster;
+use crate::tuning::Tuning;
+use crate::world_time::PointLight2d;
+use game_lab_utils::inventory_plugin::{Inventory, ItemStack};
+use game_lab_utils::asset_manifest_plugin::ImageAssets;
+use game_lab_utils::y_sort_plugin::{SortingLayer, YSort};
+
+// Hotbar plus a backpack's worth
+const INVENTORY_SIZE: usize = 27;
+
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
 pub struct Player {
     pub walk_speed: f32,
     pub run_speed: f32,
@@ -32,16 +40,16 @@ pub struct PlayerTarget {
     pub size: Vec2,
 }
 
-pub fn initialize_player_resources(mut commands: Commands, mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>, asset_server: Res<AssetServer>) {
+pub fn initialize_player_resources(mut commands: Commands, images: Res<ImageAssets>, tuning: Res<Tuning>) {
     let mut sprite_sheet_config = HashMap::new();
 
     for ss in SPRITE_SHEET_CONFIG {
-        let atlas = TextureAtlasLayout::from_grid(ss.tile_size, ss.columns, ss.rows, None, None);
+        let atlas = images.atlas(ss.atlas);
         sprite_sheet_config.insert(ss.state, PlayerSpriteSheet::new(
-            asset_server.load(asset_folder_hana(ss.image_url)),
-            texture_atlas_layouts.add(atlas),
-            ss.columns,
-            Duration::from_millis(ss.frame_duration),
+            atlas.image.clone(),
+            atlas.layout.clone(),
+            atlas.columns,
+            tuning.player.frame_duration(ss.state),
             ss.sprite_size,
             ss.rendered_area
         ));
@@ -50,41 +58,13 @@ pub fn initialize_player_resources(mut commands: Commands, mut texture_atlas_lay
     commands.insert_resource(PlayerResource { sprite_sheet_config });
 }
 
-#[derive(Asset, TypePath, AsBindGroup ,Debug, Clone)]
-pub struct CustomMaterial {
-
-}
-
-impl Material2d for CustomMaterial {
-    fn fragment_shader() -> ShaderRef {
-        "shaders/shadow.wgsl".into()
-    }
-    fn alpha_mode(&self) -> AlphaMode2d {
-        AlphaMode2d::Blend
-    }
-}
-
-#[derive(Component)]
-pub struct Shadow;
-
-pub fn initialize_player(
-    mut commands: Commands, player_resources: Res<PlayerResource>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<CustomMaterial>>,
-) {
+pub fn initialize_player(mut commands: Commands, player_resources: Res<PlayerResource>, tuning: Res<Tuning>) {
     let default_state = player_resources.sprite_sheet_config.get(&AnimationState::default()).unwrap();
     let animation_indices = PlayerAnimationsIndices::from_dir(Direction::default(), default_state.columns);
-    commands.spawn((
-       Shadow,
-        Mesh2d(meshes.add(Mesh::from(Circle::new(30.0)))),
-        MeshMaterial2d(materials.add(CustomMaterial{})),
-        Transform::from_translation(Vec3::new(1936.0, -1936.0, 9.0)),
-    ));
-
     commands.spawn((
         Player {
-            walk_speed: 1.3,
-            run_speed: 2.0,
+            walk_speed: tuning.player.walk_speed,
+            run_speed: tuning.player.run_speed,
             is_running: false,
         },
         PlayerAnimationState(AnimationState::default()),
@@ -104,6 +84,19 @@ pub fn initialize_player(
             animations: Timer::new(default_state.duration, TimerMode::Repeating),
         },
         Transform::from_translation(Vec3::new(1936.0, -1936.0, 10.0)),
+        ShadowCaster::default(),
+        // Lantern, only shows once it gets dark
+        PointLight2d { radius: 96.0, intensity: 0.9 },
+        EquippedTool::default(),
+        Inventory::with_items(INVENTORY_SIZE, vec!(
+            ItemStack::new("hoe", 1),
+            ItemStack::new("watering_can", 1),
+            ItemStack::new("wheat_seeds", 10),
+            ItemStack::new("tomato_seeds", 5),
+        )),
+        Hotbar::default(),
+        // Sort from the feet rather than the centre of the sprite
+        YSort::new(SortingLayer::World).with_offset(-12.0),
     ));
 
     commands.spawn((
@@ -113,6 +106,24 @@ pub fn initialize_player(
     ));
 }
 
+// The speed console command holds until the tuning next changes
+pub fn apply_player_tuning(
+    tuning: Res<Tuning>,
+    mut player_resources: ResMut<PlayerResource>,
+    player: Single<(&mut Player, &mut PlayerTimers, &PlayerAnimationState)>,
+) {
+    if !tuning.is_changed() {
+        return;
+    }
+    for (state, sheet) in player_resources.sprite_sheet_config.iter_mut() {
+        sheet.duration = tuning.player.frame_duration(*state);
+    }
+    let (mut player, mut timers, state) = player.into_inner();
+    player.walk_speed = tuning.player.walk_speed;
+    player.run_speed = tuning.player.run_speed;
+    timers.animations.set_duration(tuning.player.frame_duration(state.0));
+}
+
 pub fn update_player_direction(mut reader: EventReader<PlayerDirectionChange>, mut direction: Single<&mut PlayerDirection>) {
     for event in reader.read() {
         let new_direction = event.0;
@@ -156,12 +167,6 @@ pub fn update_player_target(
     }
 }
 
-pub fn move_shadow(player_transform: Query<&Transform, With<Player>>, shadow: Single<(&mut Transform, &Shadow), Without<Player>>,) {
-    let (mut transform, _) = shadow.into_inner();
-    transform.translation.x = player_transform.single().translation.x - 1.0;
-    transform.translation.y = player_transform.single().translation.y - 6.0;
-}
-
 pub fn update_player_transform(
     mut reader: EventReader<PlayerMovementEvent>,
     player: Single<(&mut Transform, &Player)>,
@@ -1,25 +1,70 @@
 use crate::player::player::*;
 use crate::player::animation::{animated_player_sprite, update_player_animation_indices, update_player_animation_state, update_sprite_texture_atlas};
 use crate::player::debug::{debug_player_state, draw_sprite_bounding_box, draw_target_block};
+use crate::player::commands::{set_player_speed, teleport_player};
 use crate::player::controller::{apply_actions, modify_player_direction, modify_player_position, PlayerDirectionChange, PlayerMovementEvent};
 use bevy::app::{App, Startup};
-use bevy::prelude::{IntoSystemConfigs, Plugin, Update};
-use bevy::sprite::Material2dPlugin;
-use game_lab_utils::debug_plugin::{debug_enable, Debugger};
+use bevy::prelude::{in_state, IntoSystemConfigs, KeyCode, Plugin, Update};
+use game_lab_utils::console_plugin::{ArgKind, ConsoleApp, ConsoleCommand};
+use game_lab_utils::debug_plugin::{DebugKey, Debugger};
+use game_lab_utils::inventory_plugin::give_item;
+use crate::state::GameState;
+
+const STATE_KEY: DebugKey = DebugKey::new("Player/State").with_hotkey(KeyCode::F2);
+const BOUNDS_KEY: DebugKey = DebugKey::new("Player/Bounds").with_hotkey(KeyCode::F3);
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PlayerDirectionChange>()
+        app.register_type::<Player>()
+            .add_event::<PlayerDirectionChange>()
             .add_event::<PlayerMovementEvent>()
-            .add_plugins(Material2dPlugin::<CustomMaterial>::default(),)
             .add_systems(Startup, (initialize_player_resources, initialize_player).chain())
-            .add_systems(Update, (apply_actions, update_player_transform))
-            .add_systems(Update, (update_player_direction, move_shadow, update_player_animation_state, update_sprite_texture_atlas, animated_player_sprite, update_player_animation_indices, update_player_target))
-            .add_systems(Update, (draw_sprite_bounding_box.run_if(debug_enable), draw_target_block.run_if(debug_enable)))
-            .add_debug_system(debug_player_state, "Player".to_string())
+            .add_systems(Update, (apply_actions, update_player_transform).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (apply_player_tuning, update_player_direction, update_player_animation_state, update_sprite_texture_atlas, animated_player_sprite, update_player_animation_indices, update_player_target))
+            .add_debug_system((draw_sprite_bounding_box, draw_target_block), BOUNDS_KEY)
+            .add_debug_system(debug_player_state, STATE_KEY)
+            .add_console_command(ConsoleCommand::new("tp", "Moves the player").arg("x", ArgKind::Float).arg("y", ArgKind::Float), teleport_player)
+            .add_console_command(ConsoleCommand::new("speed", "Sets the player's walk speed, and run speed").arg("walk", ArgKind::Float).optional_arg("run", ArgKind::Float), set_player_speed)
+            .add_console_command(ConsoleCommand::new("give", "Adds items to the player's inventory").arg("item", ArgKind::Text).optional_arg("count", ArgKind::Int), give_item::<Player>)
             .add_observer(modify_player_direction)
             .add_observer(modify_player_position);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{Transform, With};
+    use game_lab_utils::asset_manifest_plugin::AssetManifestPlugin;
+    use game_lab_utils::test_harness::TestApp;
+    use crate::controller::Direction;
+    use crate::controller::plugin::ControllerPlugin;
+    use crate::player::animation::{AnimationState, PlayerAnimationState};
+    use crate::state::StatePlugin;
+    use crate::tuning::Tuning;
+    use game_lab_utils::tuning_plugin::TuningPlugin;
+    use super::*;
+
+    #[test]
+    fn holding_w_walks_north() {
+        let mut app = TestApp::with_fixtures(env!("CARGO_MANIFEST_DIR"), "game2");
+        app.add_plugins((StatePlugin, AssetManifestPlugin::new("game://assets.json"), ControllerPlugin::new(), TuningPlugin::<Tuning>::new("game://game.tuning.ron"), PlayerPlugin));
+        app.set_state(GameState::Playing).step(2);
+        let start = app.query_single::<&Transform, With<Player>>().translation;
+        assert_eq!(app.single::<PlayerAnimationState>().0, AnimationState::Idle);
+
+        app.press(KeyCode::KeyW).step(10);
+        assert_eq!(app.single::<PlayerAnimationState>().0, AnimationState::Walking);
+        assert_eq!(app.single::<PlayerDirection>().0, Direction::North);
+        let walked = app.query_single::<&Transform, With<Player>>().translation;
+        assert!(walked.y > start.y);
+        assert_eq!(walked.x, start.x);
+
+        app.release(KeyCode::KeyW).step(3);
+        assert_eq!(app.single::<PlayerAnimationState>().0, AnimationState::Idle);
+        let stopped = app.query_single::<&Transform, With<Player>>().translation;
+        app.step(5);
+        assert_eq!(app.query_single::<&Transform, With<Player>>().translation, stopped);
+    }
+}
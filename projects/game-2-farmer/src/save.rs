@@ -0,0 +1,164 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::math::IVec2;
+use bevy::prelude::{Camera2d, Commands, EventReader, EventWriter, IntoSystemConfigs, Query, Res, ResMut, Single, Transform, With, Without};
+use game_lab_utils::inventory_plugin::Inventory;
+use game_lab_utils::save_plugin::{loading, saving, SavePlugin, SaveRequest, SaveState, SaveSystems, AUTOSAVE_SLOT};
+use serde::{Deserialize, Serialize};
+use crate::camera::effects::CameraEffects;
+use crate::controller::{Direction, HOTBAR_SLOTS};
+use crate::farming::plugin::{spawn_crop_sprite, spawn_farm_tile, FarmingResources};
+use crate::farming::{Crops, FarmField, FarmTile, PlantedCrop};
+use crate::hotbar::Hotbar;
+use crate::player::player::{Player, PlayerDirection};
+use crate::world_time::{DayRolloverEvent, Season, WorldTime};
+
+// Bump when a section changes shape and add a migration from the old version
+const SAVE_VERSION: u32 = 1;
+
+const PLAYER_SECTION: &str = "player";
+const CALENDAR_SECTION: &str = "calendar";
+const FARM_SECTION: &str = "farm";
+
+pub struct GameSavePlugin;
+
+impl Plugin for GameSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(SavePlugin::new("game-2-farmer", SAVE_VERSION).with_autoload(AUTOSAVE_SLOT))
+            .add_systems(Update, autosave_overnight.before(SaveSystems::Begin))
+            .add_systems(Update, (save_player, save_calendar, save_farm).in_set(SaveSystems::Collect).run_if(saving))
+            .add_systems(Update, (load_player, load_calendar, load_farm).in_set(SaveSystems::Apply).run_if(loading));
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PlayerSave {
+    position: [f32; 2],
+    direction: Direction,
+    inventory: Inventory,
+    hotbar: usize,
+}
+
+// Day length is a setting rather than progress so it isn't saved
+#[derive(Serialize, Deserialize)]
+struct CalendarSave {
+    time_of_day: f32,
+    day: u32,
+    season: Season,
+    year: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FarmSave {
+    tiles: Vec<FarmTileSave>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FarmTileSave {
+    coords: [i32; 2],
+    watered: bool,
+    crop: Option<CropSave>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CropSave {
+    id: String,
+    stage: usize,
+    days_in_stage: u32,
+}
+
+fn autosave_overnight(mut reader: EventReader<DayRolloverEvent>, mut writer: EventWriter<SaveRequest>) {
+    if reader.read().last().is_some() {
+        writer.send(SaveRequest(AUTOSAVE_SLOT));
+    }
+}
+
+fn save_player(mut state: ResMut<SaveState>, player: Single<(&Transform, &PlayerDirection, &Inventory, &Hotbar), With<Player>>) {
+    let (transform, direction, inventory, hotbar) = player.into_inner();
+    state.write(PLAYER_SECTION, &PlayerSave {
+        position: transform.translation.truncate().to_array(),
+        direction: direction.0,
+        inventory: inventory.clone(),
+        hotbar: hotbar.selected,
+    });
+}
+
+fn save_calendar(mut state: ResMut<SaveState>, world_time: Res<WorldTime>) {
+    state.write(CALENDAR_SECTION, &CalendarSave {
+        time_of_day: world_time.time_of_day,
+        day: world_time.day,
+        season: world_time.season,
+        year: world_time.year,
+    });
+}
+
+// Puts the saved values onto the entities Startup already spawned
+fn load_player(
+    state: Res<SaveState>,
+    player: Single<(&mut Transform, &mut PlayerDirection, &mut Inventory, &mut Hotbar), With<Player>>,
+    mut camera: Single<&mut CameraEffects, (With<Camera2d>, Without<Player>)>,
+) {
+    let Some(save) = state.read::<PlayerSave>(PLAYER_SECTION) else {
+        return;
+    };
+    let (mut transform, mut direction, mut inventory, mut hotbar) = player.into_inner();
+    transform.translation.x = save.position[0];
+    transform.translation.y = save.position[1];
+    direction.0 = save.direction;
+    *inventory = save.inventory;
+    hotbar.selected = save.hotbar.min(HOTBAR_SLOTS - 1);
+    // Snap rather than drift the camera across the map
+    camera.anchor = transform.translation.truncate();
+}
+
+fn save_farm(mut state: ResMut<SaveState>, tiles: Query<&FarmTile>) {
+    let mut tiles: Vec<FarmTileSave> = tiles.iter().map(|tile| FarmTileSave {
+        coords: tile.coords.to_array(),
+        watered: tile.watered,
+        crop: tile.crop.as_ref().map(|crop| CropSave { id: crop.id.clone(), stage: crop.stage, days_in_stage: crop.days_in_stage }),
+    }).collect();
+    // Query order changes from run to run, sorting keeps the save file stable
+    tiles.sort_by_key(|tile| tile.coords);
+    state.write(FARM_SECTION, &FarmSave { tiles });
+}
+
+fn load_calendar(state: Res<SaveState>, mut world_time: ResMut<WorldTime>) {
+    let Some(save) = state.read::<CalendarSave>(CALENDAR_SECTION) else {
+        return;
+    };
+    world_time.time_of_day = save.time_of_day;
+    world_time.day = save.day;
+    world_time.season = save.season;
+    world_time.year = save.year;
+}
+
+// Replaces whatever has been farmed so far with the saved field
+fn load_farm(
+    mut commands: Commands,
+    state: Res<SaveState>,
+    mut field: ResMut<FarmField>,
+    tiles: Query<&FarmTile>,
+    crops: Res<Crops>,
+    resources: Res<FarmingResources>,
+) {
+    let Some(save) = state.read::<FarmSave>(FARM_SECTION) else {
+        return;
+    };
+    for (_, entity) in field.0.drain() {
+        if let Ok(tile) = tiles.get(entity) && let Some(crop) = &tile.crop {
+            commands.entity(crop.entity).despawn();
+        }
+        commands.entity(entity).despawn();
+    }
+
+    for tile in save.tiles {
+        let coords = IVec2::from(tile.coords);
+        // Definitions may still be loading, the sprite catches up when they're in
+        let crop = tile.crop.map(|crop| {
+            let index = crops.0.get(&crop.id).map_or(0, |d| d.atlas_index(crop.stage));
+            let entity = spawn_crop_sprite(&mut commands, &resources, coords, index);
+            PlantedCrop { id: crop.id, stage: crop.stage, days_in_stage: crop.days_in_stage, entity }
+        });
+        let entity = spawn_farm_tile(&mut commands, &resources, FarmTile { coords, watered: tile.watered, crop });
+        field.0.insert(coords, entity);
+    }
+}
@@ -0,0 +1,238 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::asset::{Asset, Assets, Handle};
+use bevy::color::{Alpha, Color, LinearRgba};
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::{Added, Changed, Commands, Component, Entity, IntoSystemConfigs, Mesh, Mesh2d, Query, Rectangle, RemovedComponents, Res, ResMut, Shader, Transform, TypePath, Without};
+use bevy::render::mesh::MeshVertexBufferLayoutRef;
+use bevy::render::render_resource::{AsBindGroup, RenderPipelineDescriptor, SpecializedMeshPipelineError};
+use bevy::sprite::{AlphaMode2d, Material2d, Material2dKey, Material2dPlugin, MeshMaterial2d};
+use game_lab_utils::asset_manifest_plugin::{FragmentShader, ShaderAssets};
+use game_lab_utils::diagnostic_plugin::DiagnosticsApp;
+use game_lab_utils::y_sort_plugin::{SortingLayer, YSort};
+
+const SHADOW_SHADER: &str = "shadow";
+// How quickly the shadow shrinks and fades as the caster leaves the ground
+const HEIGHT_FALLOFF: f32 = 0.04;
+
+pub struct ShadowPlugin;
+
+impl Plugin for ShadowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<ShadowMaterial>::default())
+            .add_asset_count::<ShadowMaterial>()
+            .add_systems(Update, (spawn_shadows, update_shadow_materials, follow_casters, despawn_shadows).chain());
+    }
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[bind_group_data(FragmentShader)]
+pub struct ShadowMaterial {
+    #[uniform(0)]
+    pub color: LinearRgba,
+    // World size of the shadow, the quad is scaled to match
+    #[uniform(0)]
+    pub size: Vec2,
+    // Size in world pixels of each block the shadow is snapped to
+    #[uniform(0)]
+    pub pixel_size: f32,
+    // Number of rings the falloff is split into, each one a step lighter than the last
+    #[uniform(0)]
+    pub softness_steps: u32,
+    pub shader: Handle<Shader>,
+}
+
+impl From<&ShadowMaterial> for FragmentShader {
+    fn from(material: &ShadowMaterial) -> Self {
+        FragmentShader(material.shader.clone())
+    }
+}
+
+impl Material2d for ShadowMaterial {
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        key.bind_group_data.specialize(descriptor);
+        Ok(())
+    }
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct ShadowCaster {
+    pub offset: Vec2,
+    pub size: Vec2,
+    pub color: Color,
+    pub pixel_size: f32,
+    pub softness_steps: u32,
+    // Distance the caster is off the ground, e.g. mid jump
+    pub height: f32,
+}
+
+impl Default for ShadowCaster {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::new(-1.0, -6.0),
+            size: Vec2::splat(20.0),
+            color: Color::srgba(0.0, 0.0, 0.0, 0.8),
+            pixel_size: 3.0,
+            softness_steps: 4,
+            height: 0.0,
+        }
+    }
+}
+
+impl ShadowCaster {
+    pub fn height_scale(&self) -> f32 {
+        1.0 / (1.0 + self.height.max(0.0) * HEIGHT_FALLOFF)
+    }
+
+    fn scale(&self) -> Vec3 {
+        Vec3::from((self.size * self.height_scale(), 1.0))
+    }
+
+    fn material(&self, shader: Handle<Shader>) -> ShadowMaterial {
+        let color = self.color.to_linear();
+        ShadowMaterial {
+            color: color.with_alpha(color.alpha * self.height_scale()),
+            size: self.size,
+            pixel_size: self.pixel_size,
+            softness_steps: self.softness_steps.max(1),
+            shader,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Shadow {
+    pub caster: Entity,
+}
+
+pub fn spawn_shadows(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ShadowMaterial>>,
+    casters: Query<(Entity, &ShadowCaster, &Transform), Added<ShadowCaster>>,
+    shaders: Res<ShaderAssets>,
+) {
+    for (entity, caster, transform) in casters.iter() {
+        let translation = transform.translation.truncate() + caster.offset;
+        commands.spawn((
+            Shadow { caster: entity },
+            // A unit quad scaled in follow_casters, so a change of size needs no new mesh
+            Mesh2d(meshes.add(Rectangle::new(1.0, 1.0))),
+            MeshMaterial2d(materials.add(caster.material(shaders.shader(SHADOW_SHADER)))),
+            Transform::from_translation(Vec3::from((translation, 0.0))).with_scale(caster.scale()),
+            YSort::new(SortingLayer::Shadows),
+        ));
+    }
+}
+
+pub fn update_shadow_materials(
+    mut materials: ResMut<Assets<ShadowMaterial>>,
+    casters: Query<&ShadowCaster, Changed<ShadowCaster>>,
+    shadows: Query<(&Shadow, &MeshMaterial2d<ShadowMaterial>)>,
+    shaders: Res<ShaderAssets>,
+) {
+    for (shadow, material) in shadows.iter() {
+        let Ok(caster) = casters.get(shadow.caster) else {
+            continue;
+        };
+        if let Some(m) = materials.get_mut(&material.0) {
+            *m = caster.material(shaders.shader(SHADOW_SHADER));
+        }
+    }
+}
+
+pub fn follow_casters(
+    casters: Query<(&ShadowCaster, &Transform), Without<Shadow>>,
+    mut shadows: Query<(&Shadow, &mut Transform)>,
+) {
+    for (shadow, mut transform) in shadows.iter_mut() {
+        let Ok((caster, caster_transform)) = casters.get(shadow.caster) else {
+            continue;
+        };
+        let translation = caster_transform.translation.truncate() + caster.offset;
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+        transform.scale = caster.scale();
+    }
+}
+
+pub fn despawn_shadows(
+    mut commands: Commands,
+    mut removed: RemovedComponents<ShadowCaster>,
+    shadows: Query<(Entity, &Shadow)>,
+) {
+    for caster in removed.read() {
+        for (entity, shadow) in shadows.iter() {
+            if shadow.caster == caster {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::{Sprite, Transform, World};
+    use game_lab_utils::asset_manifest_plugin::AssetManifestPlugin;
+    use game_lab_utils::golden_image::{DEFAULT_TOLERANCE, GoldenScene, assert_golden, golden_path};
+    use game_lab_utils::internal_asset_plugin::asset_root;
+    use super::*;
+
+    // Renders the game's own shadow shader, so a change to its rings or pixel snapping shows up
+    #[test]
+    #[ignore = "needs a software renderer, run with cargo test -- --ignored"]
+    fn shadows_match_golden() {
+        let scene = GoldenScene::with_fixtures(env!("CARGO_MANIFEST_DIR"), "game2")
+            .with_source("assets", asset_root())
+            .with_size(192, 64);
+        let frame = scene.render(|app| {
+            app.add_plugins((AssetManifestPlugin::new("game://shadows.assets.json"), ShadowPlugin));
+            // The shadows are black, so they go over a light floor
+            app.world_mut().spawn((Sprite::from_color(Color::WHITE, Vec2::new(192.0, 64.0)), Transform::from_xyz(0.0, 0.0, -1.0)));
+            let casters = vec!(
+                (-64.0, ShadowCaster { size: Vec2::new(32.0, 16.0), ..Default::default() }),
+                (0.0, ShadowCaster { size: Vec2::new(48.0, 24.0), pixel_size: 2.0, softness_steps: 8, ..Default::default() }),
+                (64.0, ShadowCaster { size: Vec2::new(32.0, 16.0), height: 20.0, ..Default::default() }),
+            );
+            for (x, caster) in casters {
+                app.world_mut().spawn((caster, Transform::from_xyz(x, 0.0, 0.0)));
+            }
+        });
+
+        assert_golden(&golden_path(env!("CARGO_MANIFEST_DIR"), "shadows"), &frame, DEFAULT_TOLERANCE);
+    }
+
+    #[test]
+    fn follows_caster_size() {
+        struct TestCase {
+            size: Vec2,
+            height: f32,
+            expected_scale: Vec3,
+        }
+
+        let cases = vec!(
+            TestCase { size: Vec2::new(20.0, 10.0), height: 0.0, expected_scale: Vec3::new(20.0, 10.0, 1.0) },
+            TestCase { size: Vec2::new(40.0, 16.0), height: 0.0, expected_scale: Vec3::new(40.0, 16.0, 1.0) },
+            TestCase { size: Vec2::new(40.0, 16.0), height: 25.0, expected_scale: Vec3::new(20.0, 8.0, 1.0) },
+        );
+
+        let mut world = World::new();
+        let caster = world.spawn((ShadowCaster::default(), Transform::default())).id();
+        let shadow = world.spawn((Shadow { caster }, Transform::default())).id();
+        for c in cases {
+            let mut shadow_caster = world.get_mut::<ShadowCaster>(caster).unwrap();
+            shadow_caster.size = c.size;
+            shadow_caster.height = c.height;
+            world.run_system_once(follow_casters).unwrap();
+            assert_eq!(world.get::<Transform>(shadow).unwrap().scale, c.expected_scale);
+        }
+    }
+}
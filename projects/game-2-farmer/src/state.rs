@@ -0,0 +1,41 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{in_state, Condition, EventReader, EventWriter, IntoSystemConfigs, States};
+use game_lab_utils::game_state_plugin::{GameStatePlugin, GameStates, TogglePause};
+use game_lab_utils::loading_plugin::LoadingPlugin;
+use crate::controller::{Action, ActionEvent};
+
+#[derive(States, Default, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    Loading,
+    Playing,
+    Paused,
+}
+
+impl GameStates for GameState {
+    const MAIN_MENU: Self = GameState::MainMenu;
+    const LOADING: Self = GameState::Loading;
+    const PLAYING: Self = GameState::Playing;
+    const PAUSED: Self = GameState::Paused;
+}
+
+pub struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        // Pause comes from the controller so it follows the player's bindings
+        app.add_plugins(GameStatePlugin::new(GameState::MainMenu).without_pause_key())
+            .add_plugins(LoadingPlugin::new(GameState::Loading, GameState::Playing))
+            .add_systems(Update, toggle_pause.run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))));
+    }
+}
+
+fn toggle_pause(mut reader: EventReader<ActionEvent>, mut writer: EventWriter<TogglePause>) {
+    for event in reader.read() {
+        // Pause is sent every frame it is held, flip once on release
+        if let ActionEvent(Action::Pause, 0) = event {
+            writer.send(TogglePause);
+        }
+    }
+}
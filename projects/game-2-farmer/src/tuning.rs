@@ -0,0 +1,66 @@
+use std::time::Duration;
+use bevy::prelude::{Asset, ReflectResource, Resource};
+use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
+use crate::player::animation::AnimationState;
+
+// Loaded from game://game.tuning.ron, these defaults are only used until it's in
+#[derive(Asset, Resource, Reflect, Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Tuning {
+    pub player: PlayerTuning,
+    pub camera: CameraTuning,
+    pub map: MapTuning,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct PlayerTuning {
+    pub walk_speed: f32,
+    pub run_speed: f32,
+    pub idle_frame_ms: u64,
+    pub walk_frame_ms: u64,
+    pub run_frame_ms: u64,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        Self { walk_speed: 1.3, run_speed: 2.0, idle_frame_ms: 200, walk_frame_ms: 100, run_frame_ms: 100 }
+    }
+}
+
+impl PlayerTuning {
+    pub fn frame_duration(&self, state: AnimationState) -> Duration {
+        Duration::from_millis(match state {
+            AnimationState::Idle => self.idle_frame_ms,
+            AnimationState::Walking => self.walk_frame_ms,
+            AnimationState::Running => self.run_frame_ms,
+        })
+    }
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct CameraTuning {
+    // Square around the anchor the player can move in before the camera follows
+    pub zone: f32,
+}
+
+impl Default for CameraTuning {
+    fn default() -> Self {
+        Self { zone: 200.0 }
+    }
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct MapTuning {
+    pub water_frame_ms: u64,
+}
+
+impl Default for MapTuning {
+    fn default() -> Self {
+        Self { water_frame_ms: 300 }
+    }
+}
@@ -0,0 +1,52 @@
+use bevy::color::palettes::basic::YELLOW;
+use bevy::math::Isometry2d;
+use bevy::prelude::{EventWriter, GlobalTransform, Gizmos, Query, Res, ResMut};
+use bevy_egui::{egui, EguiContexts};
+use game_lab_utils::debug_plugin::{debug_window, DebugState};
+use crate::world_time::lighting::PointLight2d;
+use crate::world_time::{DayRolloverEvent, WorldTime};
+
+pub fn debug_lights(mut gizmos: Gizmos, lights: Query<(&PointLight2d, &GlobalTransform)>) {
+    for (light, transform) in lights.iter() {
+        gizmos.circle_2d(Isometry2d::from_translation(transform.translation().truncate()), light.radius, YELLOW);
+    }
+}
+
+pub fn debug_world_time(mut ctx: EguiContexts, debug_state: Res<DebugState>, mut world_time: ResMut<WorldTime>, mut rollover_writer: EventWriter<DayRolloverEvent>) {
+    debug_window("Time", &debug_state).show(ctx.ctx_mut(), |ui| {
+        egui::Grid::new("time_grid")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .striped(true)
+            .max_col_width(150.0)
+            .show(ui, |ui| {
+                ui.label("Clock:");
+                ui.label(world_time.clock());
+                ui.end_row();
+
+                ui.label("Day:");
+                ui.label(format!("{} {} (Year {})", world_time.season, world_time.day, world_time.year));
+                ui.end_row();
+
+                ui.label("Day length:");
+                ui.label(format!("{:?}", world_time.day_length));
+                ui.end_row();
+
+                ui.label("Paused:");
+                ui.label(format!("{:?}", world_time.paused));
+                ui.end_row();
+            });
+
+        ui.add(egui::Slider::new(&mut world_time.time_of_day, 0.0..=0.999).text("Time of day"));
+        ui.horizontal(|ui| {
+            let label = if world_time.paused { "Resume" } else { "Pause" };
+            if ui.button(label).clicked() {
+                world_time.paused = !world_time.paused;
+            }
+            if ui.button("Next day").clicked() {
+                world_time.next_day();
+                rollover_writer.send(DayRolloverEvent { day: world_time.day, season: world_time.season, year: world_time.year });
+            }
+        });
+    });
+}
@@ -0,0 +1,147 @@
+use bevy::asset::{Asset, Assets, Handle};
+use bevy::color::{Color, LinearRgba, Mix};
+use bevy::math::Vec4;
+use bevy::prelude::{BuildChildren, Camera2d, ChildBuild, Commands, Component, Entity, GlobalTransform, Mesh, Mesh2d, Query, Rectangle, Res, ResMut, Resource, Shader, Single, Transform, TypePath, With};
+use bevy::render::mesh::MeshVertexBufferLayoutRef;
+use bevy::render::render_resource::{AsBindGroup, RenderPipelineDescriptor, SpecializedMeshPipelineError};
+use bevy::sprite::{AlphaMode2d, Material2d, Material2dKey, MeshMaterial2d};
+use game_lab_utils::asset_manifest_plugin::{FragmentShader, ShaderAssets};
+use crate::world_time::WorldTime;
+
+pub const MAX_LIGHTS: usize = 16;
+const LIGHTING_SHADER: &str = "lighting";
+// Big enough to cover the screen when zoomed out, the overlay follows the camera
+const OVERLAY_SIZE: f32 = 8000.0;
+// In front of every sorting layer
+const OVERLAY_Z: f32 = 900.0;
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[bind_group_data(FragmentShader)]
+pub struct LightingMaterial {
+    // Tint over the whole scene, alpha is how dark it gets
+    #[uniform(0)]
+    pub ambient: LinearRgba,
+    // xy world position, z radius, w intensity
+    #[uniform(0)]
+    pub lights: [Vec4; MAX_LIGHTS],
+    #[uniform(0)]
+    pub light_count: u32,
+    pub shader: Handle<Shader>,
+}
+
+impl From<&LightingMaterial> for FragmentShader {
+    fn from(material: &LightingMaterial) -> Self {
+        FragmentShader(material.shader.clone())
+    }
+}
+
+impl Material2d for LightingMaterial {
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        key.bind_group_data.specialize(descriptor);
+        Ok(())
+    }
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct PointLight2d {
+    pub radius: f32,
+    // How much darkness is pushed back at the centre, 1.0 fully clears it
+    pub intensity: f32,
+}
+
+#[derive(Component)]
+pub struct LightingOverlay;
+
+// Ambient tint at points through the day, blended between neighbours
+#[derive(Resource)]
+pub struct AmbientKeyframes(pub Vec<(f32, Color)>);
+
+impl Default for AmbientKeyframes {
+    fn default() -> Self {
+        Self(vec!(
+            (0.00, Color::srgba(0.05, 0.05, 0.20, 0.65)), // midnight
+            (0.20, Color::srgba(0.05, 0.05, 0.20, 0.60)), // night
+            (0.27, Color::srgba(1.00, 0.60, 0.40, 0.25)), // dawn
+            (0.35, Color::srgba(1.00, 1.00, 1.00, 0.00)), // day
+            (0.70, Color::srgba(1.00, 1.00, 1.00, 0.00)), // day
+            (0.78, Color::srgba(0.90, 0.40, 0.30, 0.30)), // dusk
+            (0.86, Color::srgba(0.05, 0.05, 0.20, 0.60)), // night
+            (1.00, Color::srgba(0.05, 0.05, 0.20, 0.65)), // midnight
+        ))
+    }
+}
+
+impl AmbientKeyframes {
+    pub fn sample(&self, time_of_day: f32) -> LinearRgba {
+        let frames = &self.0;
+        for w in frames.windows(2) {
+            let (start, from) = w[0];
+            let (end, to) = w[1];
+            if time_of_day >= start && time_of_day <= end {
+                let t = if end > start { (time_of_day - start) / (end - start) } else { 0.0 };
+                return from.to_linear().mix(&to.to_linear(), t);
+            }
+        }
+        frames.last().map(|(_, c)| c.to_linear()).unwrap_or(LinearRgba::NONE)
+    }
+}
+
+pub fn initialize_lighting(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<LightingMaterial>>,
+    camera: Single<Entity, With<Camera2d>>,
+    shaders: Res<ShaderAssets>,
+) {
+    let material = LightingMaterial {
+        ambient: LinearRgba::NONE,
+        lights: [Vec4::ZERO; MAX_LIGHTS],
+        light_count: 0,
+        shader: shaders.shader(LIGHTING_SHADER),
+    };
+    commands.entity(*camera).with_children(|parent| {
+        parent.spawn((
+            LightingOverlay,
+            Mesh2d(meshes.add(Rectangle::new(OVERLAY_SIZE, OVERLAY_SIZE))),
+            MeshMaterial2d(materials.add(material)),
+            Transform::from_xyz(0.0, 0.0, OVERLAY_Z),
+        ));
+    });
+}
+
+pub fn update_lighting(
+    mut materials: ResMut<Assets<LightingMaterial>>,
+    world_time: Res<WorldTime>,
+    keyframes: Res<AmbientKeyframes>,
+    overlay: Single<&MeshMaterial2d<LightingMaterial>, With<LightingOverlay>>,
+    camera: Single<&GlobalTransform, With<Camera2d>>,
+    lights: Query<(&PointLight2d, &GlobalTransform)>,
+) {
+    let Some(material) = materials.get_mut(&overlay.0) else {
+        return;
+    };
+    material.ambient = keyframes.sample(world_time.time_of_day);
+
+    // Only the lights closest to the camera make the cut
+    let centre = camera.translation().truncate();
+    let mut nearest: Vec<(f32, Vec4)> = lights.iter()
+        .map(|(light, transform)| {
+            let position = transform.translation().truncate();
+            (position.distance_squared(centre), Vec4::new(position.x, position.y, light.radius, light.intensity))
+        })
+        .collect();
+    nearest.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    material.lights = [Vec4::ZERO; MAX_LIGHTS];
+    for (i, (_, light)) in nearest.iter().take(MAX_LIGHTS).enumerate() {
+        material.lights[i] = *light;
+    }
+    material.light_count = nearest.len().min(MAX_LIGHTS) as u32;
+}
@@ -0,0 +1,215 @@
+mod debug;
+mod lighting;
+pub mod plugin;
+
+use std::fmt::Display;
+use std::time::Duration;
+use bevy::prelude::{Event, Resource};
+use serde::{Deserialize, Serialize};
+
+pub use lighting::PointLight2d;
+
+pub const DEFAULT_DAY_LENGTH: Duration = Duration::from_secs(12 * 60);
+const DEFAULT_DAYS_PER_SEASON: u32 = 28;
+// Days start at 6am so the first frame isn't in the dark
+const DEFAULT_START_TIME: f32 = 0.25;
+
+#[derive(Eq, PartialEq, Clone, Copy, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum Season {
+    #[default]
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn next(&self) -> Self {
+        match self {
+            Season::Spring => Season::Summer,
+            Season::Summer => Season::Autumn,
+            Season::Autumn => Season::Winter,
+            Season::Winter => Season::Spring,
+        }
+    }
+}
+
+impl Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Season::Spring => write!(f, "Spring"),
+            Season::Summer => write!(f, "Summer"),
+            Season::Autumn => write!(f, "Autumn"),
+            Season::Winter => write!(f, "Winter"),
+        }
+    }
+}
+
+// Sent once per day that passes, crops, saving etc. hook into this
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DayRolloverEvent {
+    pub day: u32,
+    pub season: Season,
+    pub year: u32,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SeasonChangeEvent(pub Season);
+
+#[derive(Resource, Clone, Debug)]
+pub struct WorldTime {
+    // Real time a full in-game day takes
+    pub day_length: Duration,
+    pub days_per_season: u32,
+    // Fraction of the day that has passed, 0.0 is midnight and 0.5 is midday
+    pub time_of_day: f32,
+    pub day: u32,
+    pub season: Season,
+    pub year: u32,
+    pub paused: bool,
+}
+
+impl Default for WorldTime {
+    fn default() -> Self {
+        Self {
+            day_length: DEFAULT_DAY_LENGTH,
+            days_per_season: DEFAULT_DAYS_PER_SEASON,
+            time_of_day: DEFAULT_START_TIME,
+            day: 1,
+            season: Season::default(),
+            year: 1,
+            paused: false,
+        }
+    }
+}
+
+impl WorldTime {
+    pub fn hour(&self) -> u32 {
+        (self.time_of_day * 24.0) as u32 % 24
+    }
+
+    pub fn minute(&self) -> u32 {
+        ((self.time_of_day * 24.0 * 60.0) as u32) % 60
+    }
+
+    pub fn clock(&self) -> String {
+        format!("{:02}:{:02}", self.hour(), self.minute())
+    }
+
+    pub fn set_time(&mut self, hour: u32, minute: u32) {
+        self.time_of_day = ((hour % 24) * 60 + minute % 60) as f32 / (24.0 * 60.0);
+    }
+
+    // Moves the clock on by real time, returns the date at each midnight that was crossed
+    pub fn advance(&mut self, delta: Duration) -> Vec<DayRolloverEvent> {
+        if self.paused || self.day_length.is_zero() {
+            return vec!();
+        }
+        self.time_of_day += delta.as_secs_f32() / self.day_length.as_secs_f32();
+
+        let mut rollovers = vec!();
+        while self.time_of_day >= 1.0 {
+            self.time_of_day -= 1.0;
+            self.next_day();
+            rollovers.push(DayRolloverEvent { day: self.day, season: self.season, year: self.year });
+        }
+        rollovers
+    }
+
+    pub fn next_day(&mut self) {
+        self.day += 1;
+        if self.day > self.days_per_season {
+            self.day = 1;
+            self.season = self.season.next();
+            if self.season == Season::Spring {
+                self.year += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance() {
+        struct TestCase {
+            elapsed: Duration,
+            expected_rollovers: u32,
+            expected_day: u32,
+            expected_season: Season,
+            expected_clock: &'static str,
+        }
+
+        let cases = vec!(
+            TestCase {
+                elapsed: Duration::from_secs(0),
+                expected_rollovers: 0,
+                expected_day: 1,
+                expected_season: Season::Spring,
+                expected_clock: "06:00",
+            },
+            TestCase {
+                elapsed: Duration::from_secs(6 * 60),
+                expected_rollovers: 0,
+                expected_day: 1,
+                expected_season: Season::Spring,
+                expected_clock: "18:00",
+            },
+            TestCase {
+                elapsed: Duration::from_secs(12 * 60),
+                expected_rollovers: 1,
+                expected_day: 2,
+                expected_season: Season::Spring,
+                expected_clock: "06:00",
+            },
+            TestCase {
+                elapsed: Duration::from_secs(28 * 12 * 60),
+                expected_rollovers: 28,
+                expected_day: 1,
+                expected_season: Season::Summer,
+                expected_clock: "06:00",
+            },
+        );
+
+        for c in cases {
+            let mut time = WorldTime::default();
+            assert_eq!(time.advance(c.elapsed).len() as u32, c.expected_rollovers);
+            assert_eq!(time.day, c.expected_day);
+            assert_eq!(time.season, c.expected_season);
+            assert_eq!(time.clock(), c.expected_clock);
+        }
+    }
+
+    #[test]
+    fn advance_multiple_days() {
+        // A long frame (or a sleep skip) that wraps a whole year still reports every day on its own date
+        let mut time = WorldTime { days_per_season: 2, ..Default::default() };
+        let rollovers = time.advance(time.day_length * 9);
+
+        let dates: Vec<_> = rollovers.iter().map(|e| (e.day, e.season, e.year)).collect();
+        let expected = vec!(
+            (2, Season::Spring, 1),
+            (1, Season::Summer, 1),
+            (2, Season::Summer, 1),
+            (1, Season::Autumn, 1),
+            (2, Season::Autumn, 1),
+            (1, Season::Winter, 1),
+            (2, Season::Winter, 1),
+            (1, Season::Spring, 2),
+            (2, Season::Spring, 2),
+        );
+        assert_eq!(dates, expected);
+        assert_eq!(time.clock(), "06:00");
+    }
+
+    #[test]
+    fn set_time() {
+        let mut time = WorldTime::default();
+        for (hour, minute, expected_clock) in [(18, 0, "18:00"), (6, 30, "06:30"), (23, 59, "23:59"), (0, 1, "00:01"), (13, 7, "13:07")] {
+            time.set_time(hour, minute);
+            assert_eq!(time.clock(), expected_clock);
+        }
+    }
+}
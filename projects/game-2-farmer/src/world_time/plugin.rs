@@ -0,0 +1,66 @@
+use std::time::Duration;
+use bevy::app::{App, Plugin, PostStartup, Update};
+use bevy::prelude::{in_state, info, EventReader, EventWriter, In, IntoSystemConfigs, Res, ResMut, Time};
+use bevy::sprite::Material2dPlugin;
+use game_lab_utils::console_plugin::{ArgKind, ConsoleApp, ConsoleArgs, ConsoleCommand, ConsoleResult};
+use game_lab_utils::debug_plugin::{DebugKey, Debugger};
+use game_lab_utils::diagnostic_plugin::DiagnosticsApp;
+use crate::state::GameState;
+use crate::world_time::debug::{debug_lights, debug_world_time};
+use crate::world_time::lighting::{initialize_lighting, update_lighting, AmbientKeyframes, LightingMaterial};
+use crate::world_time::{DayRolloverEvent, SeasonChangeEvent, WorldTime};
+
+const TIME_KEY: DebugKey = DebugKey::new("World/Time");
+const LIGHTS_KEY: DebugKey = DebugKey::new("World/Lights");
+
+pub struct WorldTimePlugin {
+    // Real time a full in-game day takes
+    pub day_length: Duration,
+}
+
+impl Plugin for WorldTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DayRolloverEvent>()
+            .add_event::<SeasonChangeEvent>()
+            .insert_resource(WorldTime { day_length: self.day_length, ..Default::default() })
+            .init_resource::<AmbientKeyframes>()
+            .add_plugins(Material2dPlugin::<LightingMaterial>::default())
+            .add_asset_count::<LightingMaterial>()
+            // Camera is spawned in Startup, the overlay hangs off it
+            .add_systems(PostStartup, initialize_lighting)
+            .add_systems(Update, (advance_world_time.run_if(in_state(GameState::Playing)), log_calendar_events, update_lighting).chain())
+            .add_debug_system(debug_lights, LIGHTS_KEY)
+            .add_debug_system(debug_world_time, TIME_KEY)
+            .add_console_command(ConsoleCommand::new("time", "Sets the time of day").arg("time", ArgKind::Time), set_world_time);
+    }
+}
+
+fn advance_world_time(
+    mut world_time: ResMut<WorldTime>,
+    mut rollover_writer: EventWriter<DayRolloverEvent>,
+    mut season_writer: EventWriter<SeasonChangeEvent>,
+    time: Res<Time>,
+) {
+    for rollover in world_time.advance(time.delta()) {
+        // Day one is the first day of a new season
+        if rollover.day == 1 {
+            season_writer.send(SeasonChangeEvent(rollover.season));
+        }
+        rollover_writer.send(rollover);
+    }
+}
+
+fn set_world_time(In(args): In<ConsoleArgs>, mut world_time: ResMut<WorldTime>) -> ConsoleResult {
+    let (hour, minute) = args.time(0);
+    world_time.set_time(hour, minute);
+    Ok(format!("It's {}", world_time.clock()))
+}
+
+fn log_calendar_events(mut rollover_reader: EventReader<DayRolloverEvent>, mut season_reader: EventReader<SeasonChangeEvent>) {
+    for event in season_reader.read() {
+        info!("Season changed to {}", event.0);
+    }
+    for event in rollover_reader.read() {
+        info!("Day {} of {}, year {}", event.day, event.season, event.year);
+    }
+}